@@ -0,0 +1,38 @@
+//! Compiles the embedded word list into the format of `src/compiled.rs`, so
+//! the binary does not have to parse it on every run, and fingerprints the
+//! bundled word lists for the result cache.
+
+#[allow(dead_code)]
+#[path = "src/compiled.rs"]
+mod compiled;
+
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+/// Word lists whose contents affect results.
+const RESOURCES: &[&str] = &[
+    "resources/words.txt",
+    "resources/locales/en-US.txt",
+    "resources/locales/en-GB.txt",
+    "resources/frequency.txt",
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/compiled.rs");
+
+    // The hash only has to change when the resources do, so `DefaultHasher`
+    // being unstable across Rust releases does not matter.
+    let mut hasher = DefaultHasher::new();
+    for resource in RESOURCES {
+        println!("cargo:rerun-if-changed={}", resource);
+        fs::read(resource).expect("Failed to read a bundled word list").hash(&mut hasher);
+    }
+    println!("cargo:rustc-env=SPELL_CHECK_RESOURCES_HASH={:016x}", hasher.finish());
+
+    let words = fs::read_to_string("resources/words.txt").expect("Failed to read resources/words.txt");
+    let out = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is not set")).join("words.dict");
+    fs::write(&out, compiled::encode(words.lines())).expect("Failed to write the compiled word list");
+}
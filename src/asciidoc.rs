@@ -0,0 +1,431 @@
+//! AsciiDoc prose extraction.
+//!
+//! Finds the prose of an AsciiDoc document: titles, paragraphs, lists, table
+//! cells and the text of quote, example and sidebar blocks. Listing, literal,
+//! passthrough and math blocks, literal paragraphs, attribute entries, block
+//! attribute lines, block macros, monospace text, passthroughs, attribute
+//! references, anchors, cross reference targets and macro targets are left
+//! out. Comments are returned as comments.
+
+use crate::lexer::{Region, RegionKind, lines};
+use std::ops::Range;
+
+/// Inline macros, as in `name:target[text]`, whose text is prose.
+const TEXT_MACROS: &[&str] = &[
+    "link",
+    "xref",
+    "mailto",
+    "image",
+    "kbd",
+    "btn",
+    "menu",
+    "footnote",
+    "footnoteref",
+    "http",
+    "https",
+    "ftp",
+    "irc",
+    "indexterm2",
+];
+
+/// Inline macros that are skipped entirely.
+const SKIPPED_MACROS: &[&str] = &[
+    "pass",
+    "stem",
+    "latexmath",
+    "asciimath",
+    "icon",
+    "anchor",
+    "indexterm",
+    "include",
+];
+
+/// Block styles whose following paragraph is not prose.
+const VERBATIM_STYLES: &[&str] = &[
+    "source",
+    "listing",
+    "literal",
+    "stem",
+    "latexmath",
+    "asciimath",
+    "pass",
+    "comment",
+];
+
+/// Extracts the prose and comments of an AsciiDoc document.
+pub fn extract_regions(content: &str) -> Vec<Region> {
+    let mut document = Document {
+        content,
+        lines: lines(content),
+        regions: Vec::new(),
+        paragraph: None,
+    };
+    document.run();
+    document.regions.sort_by_key(|r| r.range.start);
+    document.regions
+}
+
+struct Document<'a> {
+    content: &'a str,
+    lines: Vec<Range<usize>>,
+    regions: Vec<Region>,
+    paragraph: Option<Range<usize>>,
+}
+
+/// Returns `true` for section titles such as `== Usage`.
+fn is_section_title(line: &str) -> bool {
+    let level = line.bytes().take_while(|&b| b == b'=' || b == b'#').count();
+    (1..=6).contains(&level) && line.as_bytes().get(level) == Some(&b' ')
+}
+
+/// Returns the delimiter character of a block delimiter line such as `----`,
+/// `====` or `|===`.
+fn delimiter(line: &str) -> Option<char> {
+    if line == "--" {
+        return Some('-');
+    }
+    if line.len() >= 4
+        && matches!(line.as_bytes()[0], b'|' | b',' | b':' | b'!')
+        && line[1..].bytes().all(|b| b == b'=')
+    {
+        return Some('|');
+    }
+    let c = line.chars().next()?;
+    (line.len() >= 4 && "-./+*=_".contains(c) && line.chars().all(|ch| ch == c)).then_some(c)
+}
+
+/// Returns `true` for an attribute entry such as `:toc: left` or `:!sectnums:`.
+fn is_attribute_entry(line: &str) -> bool {
+    let Some(rest) = line.strip_prefix(':') else {
+        return false;
+    };
+    let Some(close) = rest.find(':') else {
+        return false;
+    };
+    let name = rest[..close].trim_start_matches('!').trim_end_matches('!');
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        && rest[close + 1..]
+            .chars()
+            .next()
+            .is_none_or(char::is_whitespace)
+}
+
+/// Returns `true` for a block macro such as `image::diagram.png[]` or `ifdef::env[]`.
+fn is_block_macro(line: &str) -> bool {
+    match line.find("::") {
+        Some(p) => {
+            p > 0 && line[..p].chars().all(|c| c.is_ascii_alphanumeric()) && line.ends_with(']')
+        }
+        None => false,
+    }
+}
+
+impl<'a> Document<'a> {
+    fn line(&self, i: usize) -> &'a str {
+        &self.content[self.lines[i].clone()]
+    }
+
+    fn run(&mut self) {
+        let mut i = 0;
+        let mut verbatim_next = false;
+        while i < self.lines.len() {
+            let line = self.line(i).trim_end();
+            if line.is_empty() {
+                self.flush();
+                i += 1;
+                continue;
+            }
+            if let Some(c) = delimiter(line) {
+                self.flush();
+                let open = line.to_string();
+                let verbatim = std::mem::take(&mut verbatim_next);
+                let close = (i + 1..self.lines.len()).find(|&j| self.line(j).trim_end() == open);
+                match c {
+                    '/' => {
+                        let end = close.map_or(self.content.len(), |j| self.lines[j].end);
+                        self.regions.push(Region {
+                            kind: RegionKind::Comment,
+                            range: self.lines[i].start..end,
+                        });
+                        i = close.map_or(self.lines.len(), |j| j + 1);
+                    }
+                    '.' | '+' => i = close.map_or(self.lines.len(), |j| j + 1),
+                    '-' if open.len() >= 4 || verbatim => {
+                        i = close.map_or(self.lines.len(), |j| j + 1)
+                    }
+                    _ if verbatim => i = close.map_or(self.lines.len(), |j| j + 1),
+                    // Open, example, sidebar, quote blocks and tables hold prose.
+                    _ => i += 1,
+                }
+                continue;
+            }
+            if self.paragraph.is_none() {
+                if line.starts_with("//") {
+                    self.regions.push(Region {
+                        kind: RegionKind::Comment,
+                        range: self.lines[i].clone(),
+                    });
+                    i += 1;
+                    continue;
+                }
+                if is_attribute_entry(line) || is_block_macro(line) {
+                    i += 1;
+                    continue;
+                }
+                if line.starts_with('[') && line.ends_with(']') {
+                    // Block attributes, such as `[source,rust]`, or an anchor.
+                    let style = line
+                        .trim_start_matches('[')
+                        .split([',', ']', '#', '.', '%'])
+                        .next()
+                        .unwrap_or("");
+                    verbatim_next = VERBATIM_STYLES.contains(&style);
+                    i += 1;
+                    continue;
+                }
+                if std::mem::take(&mut verbatim_next) || line.starts_with([' ', '\t']) {
+                    // A styled verbatim paragraph or a literal paragraph.
+                    i = (i..self.lines.len())
+                        .find(|&j| self.line(j).trim().is_empty())
+                        .unwrap_or(self.lines.len());
+                    continue;
+                }
+            }
+            let range = self.lines[i].clone();
+            if is_section_title(line) {
+                // A title is a block of its own, so the header's attribute
+                // entries that follow it are still recognized.
+                self.flush();
+                self.paragraph = Some(range);
+                self.flush();
+                i += 1;
+                continue;
+            }
+            self.paragraph = Some(
+                self.paragraph
+                    .take()
+                    .map_or(range.clone(), |p| p.start..range.end),
+            );
+            i += 1;
+        }
+        self.flush();
+    }
+
+    fn flush(&mut self) {
+        let Some(range) = self.paragraph.take() else {
+            return;
+        };
+        Inline {
+            src: self.content.as_bytes(),
+            pos: range.start,
+            end: range.end,
+            text_start: range.start,
+            regions: &mut self.regions,
+        }
+        .run();
+    }
+}
+
+/// The inline pass over one paragraph.
+struct Inline<'a> {
+    src: &'a [u8],
+    pos: usize,
+    end: usize,
+    /// Where the current run of prose started.
+    text_start: usize,
+    regions: &'a mut Vec<Region>,
+}
+
+impl Inline<'_> {
+    fn run(mut self) {
+        while self.pos < self.end {
+            match self.src[self.pos] {
+                b'\\' => self.pos = (self.pos + 2).min(self.end),
+                b'`' => self.delimited(b"`"),
+                b'+' if self.starts_word() => self.passthrough(),
+                b'{' => self.attribute_reference(),
+                b'<' if self.src[self.pos..self.end].starts_with(b"<<") => self.cross_reference(),
+                b'[' if self.src[self.pos..self.end].starts_with(b"[[")
+                    || self.peek(1) == Some(b'#') =>
+                {
+                    let close = self.find(self.pos, b"]").map_or(self.end, |c| c + 1);
+                    let close = close
+                        + self.src[close..self.end]
+                            .iter()
+                            .take_while(|&&b| b == b']')
+                            .count();
+                    self.skip(self.pos..close);
+                }
+                b if b.is_ascii_alphabetic() && self.starts_word() => self.word_or_macro(),
+                _ => self.pos += 1,
+            }
+        }
+        let range = self.text_start..self.end;
+        self.push_text(range);
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        let i = self.pos + offset;
+        (i < self.end).then(|| self.src[i])
+    }
+
+    fn find(&self, from: usize, pat: &[u8]) -> Option<usize> {
+        self.src[from.min(self.end)..self.end]
+            .windows(pat.len())
+            .position(|w| w == pat)
+            .map(|p| from + p)
+    }
+
+    fn starts_word(&self) -> bool {
+        self.pos == 0 || !self.src[self.pos - 1].is_ascii_alphanumeric()
+    }
+
+    fn push_text(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region {
+                kind: RegionKind::Text,
+                range,
+            });
+        }
+    }
+
+    /// Ends the current run of prose before `range` and resumes after it.
+    fn skip(&mut self, range: Range<usize>) {
+        let text = self.text_start..range.start;
+        self.push_text(text);
+        self.text_start = range.end;
+        self.pos = range.end;
+    }
+
+    /// Skips text between a pair of `delim`, or just the opening one if unpaired.
+    fn delimited(&mut self, delim: &[u8]) {
+        match self.find(self.pos + delim.len(), delim) {
+            Some(close) => self.skip(self.pos..close + delim.len()),
+            None => self.pos += delim.len(),
+        }
+    }
+
+    /// Skips `+text+`, `++text++` and `+++text+++` passthroughs.
+    fn passthrough(&mut self) {
+        let run = self.src[self.pos..self.end]
+            .iter()
+            .take_while(|&&b| b == b'+')
+            .count()
+            .min(3);
+        if self.peek(run).is_some_and(|b| !b.is_ascii_whitespace()) {
+            self.delimited(&b"+++"[..run]);
+        } else {
+            self.pos += run;
+        }
+    }
+
+    /// Skips attribute references such as `{project-name}`.
+    fn attribute_reference(&mut self) {
+        let name = self.src[self.pos + 1..self.end]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+            .count();
+        if name > 0 && self.peek(1 + name) == Some(b'}') {
+            self.skip(self.pos..self.pos + name + 2);
+        } else {
+            self.pos += 1;
+        }
+    }
+
+    /// Handles `<<id>>` and `<<id,text>>`, keeping only the text.
+    fn cross_reference(&mut self) {
+        let Some(close) = self.find(self.pos + 2, b">>") else {
+            self.pos += 2;
+            return;
+        };
+        match self.find(self.pos + 2, b",").filter(|&c| c < close) {
+            Some(comma) => {
+                self.skip(self.pos..comma + 1);
+                self.skip(close..close + 2);
+            }
+            None => self.skip(self.pos..close + 2),
+        }
+    }
+
+    /// Handles `name:target[text]` macros, or moves past a plain word.
+    fn word_or_macro(&mut self) {
+        let name_len = self.src[self.pos..self.end]
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric())
+            .count();
+        let name =
+            String::from_utf8_lossy(&self.src[self.pos..self.pos + name_len]).to_ascii_lowercase();
+        let colon = self.pos + name_len;
+        let is_macro = self.src.get(colon) == Some(&b':')
+            && (TEXT_MACROS.contains(&name.as_str()) || SKIPPED_MACROS.contains(&name.as_str()));
+        let open = is_macro
+            .then(|| {
+                self.src[colon + 1..self.end]
+                    .iter()
+                    .position(|&b| b == b'[' || b.is_ascii_whitespace())
+            })
+            .flatten()
+            .map(|p| colon + 1 + p)
+            .filter(|&p| self.src[p] == b'[');
+        let (Some(open), Some(close)) = (open, open.and_then(|o| self.find(o + 1, b"]"))) else {
+            self.pos += name_len;
+            return;
+        };
+        if SKIPPED_MACROS.contains(&name.as_str()) {
+            self.skip(self.pos..close + 1);
+            return;
+        }
+        // Keep the first positional attribute, e.g. link text or alt text.
+        let text_end = self.src[open + 1..close]
+            .iter()
+            .position(|&b| b == b',')
+            .map_or(close, |p| open + 1 + p);
+        if self.src[open + 1..text_end].contains(&b'=') {
+            self.skip(self.pos..close + 1);
+        } else {
+            self.skip(self.pos..open + 1);
+            self.skip(text_end..close + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prose(content: &str) -> String {
+        extract_regions(content)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::Text)
+            .map(|r| content[r.range].to_string())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    #[test]
+    fn test_blocks() {
+        let doc = "= Document Title\n:toc: lft\n\n// a commnt\n[source,rust]\n----\nlet wrod = 1;\n----\n\n\
+            [source]\nfn mian() {}\n\n....\nlitral\n....\n\n  indnted literal\n\n\
+            ____\nA quote.\n____\n\nimage::diagrm.png[]\n\n////\nblock commnt\n////\n\n[[anchr]]\nEnd.\n";
+        assert_eq!(prose(doc), "= Document Title|A quote.|End.");
+        let comments: Vec<&str> = extract_regions(doc)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::Comment)
+            .map(|r| &doc[r.range])
+            .collect();
+        assert_eq!(comments, vec!["// a commnt", "////\nblock commnt\n////"]);
+    }
+
+    #[test]
+    fn test_inline() {
+        let doc = "Run `cargo bild` with +passthru+ in {prjct} C++ code, see <<sectn,the section>> or <<othr>>, \
+            link:https://x.io/pth[the site], image:icn.png[Alt text,width=20] and stem:[sqrt(x)] [[anchr]].\n";
+        assert_eq!(
+            prose(doc),
+            "Run | with | in | C++ code, see |the section| or |, |the site|, |Alt text| and | |."
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Baselines of known spelling errors.
+//!
+//! A baseline records the errors present when it was created so that later
+//! checks only report new ones. Entries identify an error by file, word and a
+//! hash of the trimmed line content rather than a line number, so they keep
+//! matching when surrounding lines are added or removed.
+
+use crate::cache;
+use crate::engine::SpellError;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Bumped when the layout of the baseline file changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// The default path of the baseline file.
+pub const DEFAULT_PATH: &str = "spellcheck-baseline.json";
+
+/// One known error, or several identical ones on lines with the same content.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Entry {
+    /// The file, relative to the directory the check runs in.
+    pub file: String,
+    /// The misspelled word.
+    pub word: String,
+    /// A hash of the trimmed content of the line containing the word.
+    pub line_hash: String,
+    /// The number of occurrences.
+    pub count: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    entries: Vec<Entry>,
+}
+
+/// The key identifying an error in a baseline.
+type Key = (String, String, String);
+
+/// A set of known errors.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    entries: BTreeMap<Key, usize>,
+}
+
+impl Baseline {
+    /// Records `errors` as known.
+    pub fn from_errors<'a>(errors: impl IntoIterator<Item = &'a SpellError>) -> Self {
+        let mut entries = BTreeMap::new();
+        for error in errors {
+            *entries.entry(key(error)).or_insert(0) += 1;
+        }
+        Self { entries }
+    }
+
+    /// Loads a baseline file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read(path).with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        let file: BaselineFile = serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse baseline {}", path.display()))?;
+        if file.version != FORMAT_VERSION {
+            bail!("Unsupported baseline version {} in {}", file.version, path.display());
+        }
+        let mut entries = BTreeMap::new();
+        for entry in file.entries {
+            *entries.entry((entry.file, entry.word, entry.line_hash)).or_insert(0) += entry.count;
+        }
+        Ok(Self { entries })
+    }
+
+    /// Writes the baseline as sorted, pretty-printed JSON so changes diff well.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = BaselineFile { version: FORMAT_VERSION, entries: self.entries() };
+        let mut json = serde_json::to_string_pretty(&file)?;
+        json.push('\n');
+        std::fs::write(path, json).with_context(|| format!("Failed to write baseline {}", path.display()))
+    }
+
+    /// Returns the number of known errors.
+    pub fn len(&self) -> usize {
+        self.entries.values().sum()
+    }
+
+    /// Returns `true` if there are no known errors.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries in file order.
+    pub fn entries(&self) -> Vec<Entry> {
+        self.entries
+            .iter()
+            .map(|((file, word, line_hash), &count)| Entry {
+                file: file.clone(),
+                word: word.clone(),
+                line_hash: line_hash.clone(),
+                count,
+            })
+            .collect()
+    }
+
+    /// Removes one occurrence of `error` from the baseline.
+    ///
+    /// Returns `true` if the error was known and should not be reported.
+    pub fn suppress(&mut self, error: &SpellError) -> bool {
+        match self.entries.get_mut(&key(error)) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.entries.remove(&key(error));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the entries left after suppressing errors, limited to files
+    /// under `scope`: known errors that no longer occur.
+    pub fn remaining(&self, scope: &Path) -> Vec<Entry> {
+        let scope = normalize(scope);
+        self.entries()
+            .into_iter()
+            .filter(|entry| {
+                scope.is_empty() || entry.file == scope || entry.file.starts_with(&format!("{}/", scope))
+            })
+            .collect()
+    }
+}
+
+fn key(error: &SpellError) -> Key {
+    (normalize(&error.file), error.word.clone(), line_hash(error))
+}
+
+/// Hashes the trimmed content of the line containing an error, which
+/// identifies it independently of its line number.
+pub fn line_hash(error: &SpellError) -> String {
+    cache::hash(error.context.trim().as_bytes())
+}
+
+/// Normalizes a path for comparison: forward slashes, no leading `./`.
+fn normalize(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut path = path.as_str();
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    if path == "." { String::new() } else { path.trim_end_matches('/').to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn error(file: &str, line: usize, word: &str, context: &str) -> SpellError {
+        SpellError {
+            file: PathBuf::from(file),
+            line,
+            col: 1,
+            span: 0..word.len(),
+            word: word.to_string(),
+            context: context.to_string(),
+            suggestions: Vec::new(),
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_suppress_survives_line_shifts() {
+        let mut baseline = Baseline::from_errors(&[
+            error("./docs/a.md", 3, "teh", "  teh cat"),
+            error("./docs/a.md", 7, "teh", "teh dog"),
+        ]);
+        assert_eq!(baseline.len(), 2);
+
+        // Same lines after inserting text above, run from a different path spelling.
+        assert!(baseline.suppress(&error("docs/a.md", 5, "teh", "teh cat")));
+        assert!(!baseline.suppress(&error("docs/a.md", 6, "teh", "teh cow")));
+        assert!(!baseline.suppress(&error("docs/a.md", 9, "teh", "  teh cat")));
+
+        let remaining = baseline.remaining(Path::new("."));
+        assert_eq!(remaining.len(), 1);
+        assert_eq!((remaining[0].file.as_str(), remaining[0].word.as_str()), ("docs/a.md", "teh"));
+        assert!(baseline.remaining(Path::new("./src")).is_empty());
+        assert_eq!(baseline.remaining(Path::new("docs/")).len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(DEFAULT_PATH);
+        let errors = [error("b.md", 1, "wrod", "wrod wrod"), error("b.md", 1, "wrod", "wrod wrod"), error("a.md", 2, "teh", "teh")];
+        Baseline::from_errors(&errors).save(&path)?;
+
+        let loaded = Baseline::load(&path)?;
+        let entries = loaded.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].file.as_str(), entries[0].count), ("a.md", 1));
+        assert_eq!((entries[1].file.as_str(), entries[1].count), ("b.md", 2));
+
+        std::fs::write(&path, "{\"version\": 9, \"entries\": []}")?;
+        assert!(Baseline::load(&path).is_err());
+        Ok(())
+    }
+}
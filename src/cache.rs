@@ -0,0 +1,250 @@
+//! Persistent cache of per-file results.
+//!
+//! Each entry records the spelling errors of one file together with a hash of
+//! its content. The whole cache is tied to a fingerprint of everything else
+//! that affects results — the tool version, the bundled word lists, the
+//! configuration and the contents of configured dictionary files — and is
+//! discarded when the fingerprint changes.
+
+use crate::config_schema::Config;
+use crate::dictionary::Dictionary;
+use crate::engine::SpellError;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The file holding the cached results, inside the cache directory.
+const RESULTS_FILE: &str = "results.json";
+
+/// Bumped when the layout of `RESULTS_FILE` changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    fingerprint: String,
+    files: HashMap<String, Entry>,
+}
+
+/// The results of one file.
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry {
+    hash: String,
+    errors: Vec<SpellError>,
+}
+
+/// A result cache loaded from a directory.
+///
+/// Lookups use the results of the previous run. `save` writes the results
+/// looked up or stored during this run, so files that were deleted or
+/// renamed since drop out.
+pub struct Cache {
+    dir: PathBuf,
+    fingerprint: String,
+    entries: HashMap<String, Entry>,
+    /// The entries of this run, and whether any of them was stored anew.
+    updates: Mutex<(HashMap<String, Entry>, bool)>,
+}
+
+impl Cache {
+    /// Opens the cache in `dir` for results produced under `fingerprint`.
+    ///
+    /// A missing or unreadable cache, or one written under another
+    /// fingerprint, starts out empty.
+    pub fn open(dir: &Path, fingerprint: String) -> Self {
+        let entries = fs::read(dir.join(RESULTS_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheFile>(&bytes).ok())
+            .filter(|file| file.version == FORMAT_VERSION && file.fingerprint == fingerprint)
+            .map(|file| file.files)
+            .unwrap_or_default();
+        Self {
+            dir: dir.to_path_buf(),
+            fingerprint,
+            entries,
+            updates: Mutex::new((HashMap::new(), false)),
+        }
+    }
+
+    /// Returns the cached errors of `path` if its content still hashes to `hash`.
+    ///
+    /// A hit is kept for `save`.
+    pub fn lookup(&self, path: &str, hash: &str) -> Option<Vec<SpellError>> {
+        let entry = self.entries.get(path).filter(|entry| entry.hash == hash)?;
+        let mut updates = self.updates.lock().unwrap_or_else(|e| e.into_inner());
+        updates.0.insert(path.to_string(), entry.clone());
+        Some(entry.errors.clone())
+    }
+
+    /// Records the errors found in `path`, whose content hashes to `hash`.
+    pub fn store(&self, path: String, hash: String, errors: Vec<SpellError>) {
+        let mut updates = self.updates.lock().unwrap_or_else(|e| e.into_inner());
+        updates.0.insert(path, Entry { hash, errors });
+        updates.1 = true;
+    }
+
+    /// Writes the results looked up or stored in this run, replacing the
+    /// previous ones.
+    ///
+    /// Nothing is written if every file was a cache hit and none dropped out.
+    /// The directory gets a `.gitignore` so the cache is never committed by
+    /// accident.
+    pub fn save(&self) -> Result<()> {
+        let (files, stored) = std::mem::take(&mut *self.updates.lock().unwrap_or_else(|e| e.into_inner()));
+        if !stored && files.len() == self.entries.len() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create cache directory {}", self.dir.display()))?;
+        let gitignore = self.dir.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(&gitignore, "*\n")?;
+        }
+
+        let file = CacheFile { version: FORMAT_VERSION, fingerprint: self.fingerprint.clone(), files };
+        let path = self.dir.join(RESULTS_FILE);
+        let temp = self.dir.join(format!("{}.tmp", RESULTS_FILE));
+        fs::write(&temp, serde_json::to_vec(&file)?)
+            .with_context(|| format!("Failed to write cache file {}", temp.display()))?;
+        fs::rename(&temp, &path).with_context(|| format!("Failed to write cache file {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Removes the cache directory. Returns `false` if there was none.
+pub fn clean(dir: &Path) -> Result<bool> {
+    if !dir.exists() {
+        return Ok(false);
+    }
+    fs::remove_dir_all(dir).with_context(|| format!("Failed to remove cache directory {}", dir.display()))?;
+    Ok(true)
+}
+
+/// Fingerprints everything besides file contents that affects results.
+///
+/// The dictionary is fingerprinted as built, including word lists by
+/// content, so editing a word list or adding words in code invalidates the
+/// cache even though the configuration is unchanged.
+pub fn fingerprint(config: &Config, dictionary: &Dictionary) -> String {
+    let input = format!(
+        "{}\n{}\n{}\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        env!("SPELL_CHECK_RESOURCES_HASH"),
+        serde_json::to_string(config).unwrap_or_default(),
+        dictionary.fingerprint()
+    );
+    hash(input.as_bytes())
+}
+
+/// Returns a stable 64-bit FNV-1a hash of `bytes` as hex.
+///
+/// `std`'s default hasher is not guaranteed to be stable across releases, but
+/// hashes are compared between runs.
+pub fn hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(word: &str) -> SpellError {
+        SpellError {
+            file: PathBuf::from("./a.md"),
+            line: 1,
+            col: 1,
+            span: 0..word.len(),
+            word: word.to_string(),
+            context: word.to_string(),
+            suggestions: Vec::new(),
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = dir.path().join("cache");
+
+        let cache = Cache::open(&cache_dir, "one".to_string());
+        assert!(cache.lookup("a.md", "1").is_none());
+        cache.store("a.md".to_string(), "1".to_string(), vec![error("teh")]);
+        cache.save()?;
+        assert_eq!(fs::read_to_string(cache_dir.join(".gitignore"))?, "*\n");
+
+        let cache = Cache::open(&cache_dir, "one".to_string());
+        assert_eq!(cache.lookup("a.md", "1").map(|e| e[0].word.clone()), Some("teh".to_string()));
+        assert!(cache.lookup("a.md", "2").is_none());
+
+        // A different fingerprint discards all results.
+        let cache = Cache::open(&cache_dir, "two".to_string());
+        assert!(cache.lookup("a.md", "1").is_none());
+
+        assert!(clean(&cache_dir)?);
+        assert!(!clean(&cache_dir)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_drops_files_not_seen() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = Cache::open(dir.path(), "one".to_string());
+        cache.store("a.md".to_string(), "1".to_string(), vec![error("teh")]);
+        cache.store("b.md".to_string(), "2".to_string(), Vec::new());
+        cache.save()?;
+
+        // `b.md` was deleted: only `a.md` is looked up in the next run.
+        let cache = Cache::open(dir.path(), "one".to_string());
+        assert!(cache.lookup("a.md", "1").is_some());
+        cache.save()?;
+
+        let cache = Cache::open(dir.path(), "one".to_string());
+        assert!(cache.lookup("a.md", "1").is_some());
+        assert!(cache.lookup("b.md", "2").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_dictionary_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let words = dir.path().join("words.txt");
+        fs::write(&words, "alpha\n")?;
+        let mut config = Config::default();
+        config.dictionary.extra_dictionaries = vec![words.clone()];
+
+        let fingerprint_of = |config: &Config| -> Result<String> {
+            Ok(fingerprint(config, &Dictionary::from_config(&config.dictionary)?))
+        };
+
+        let before = fingerprint_of(&config)?;
+        assert_eq!(fingerprint_of(&config)?, before);
+        fs::write(&words, "alpha\nbeta\n")?;
+        assert_ne!(fingerprint_of(&config)?, before);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_dictionary_words() {
+        let config = Config::default();
+        let mut dict = Dictionary::new();
+        dict.add_word("alpha");
+        let before = fingerprint(&config, &dict);
+        dict.add_word("beta");
+        assert_ne!(fingerprint(&config, &dict), before);
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        assert_eq!(hash(b""), "cbf29ce484222325");
+        assert_eq!(hash(b"a"), "af63dc4c8601ec8c");
+    }
+}
@@ -1,10 +1,22 @@
 //! Command-line interface for the spell checker.
 
 use clap::{Parser, Subcommand};
-use crate::config::load_config;
-use crate::dictionary::Dictionary;
-use crate::engine::Engine;
-use std::path::PathBuf;
+use crate::baseline::{self, Baseline};
+use crate::cache;
+use crate::compiled;
+use crate::config::{ProjectConfig, load_project};
+use crate::config_schema::Config;
+use crate::engine::{Engine, SpellError};
+use crate::fix;
+use crate::git::{self, ChangedFiles};
+use crate::lsp;
+use crate::report::{self, Format, MultiReporter, Reporter, Summary};
+use crate::review;
+use crate::watch;
+use anyhow::Context;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use colored::*;
 
 /// The command-line interface structure.
@@ -28,9 +40,116 @@ pub enum Commands {
         /// Files or directories to check (defaults to current directory).
         #[arg(default_value = ".")]
         path: PathBuf,
+        /// Rewrite files, replacing misspellings that have an unambiguous correction.
+        #[arg(long)]
+        fix: bool,
+        /// With `--fix`, print a unified diff instead of writing files.
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+        /// Output format for the results.
+        #[arg(long, value_enum, default_value_t = Format::Human)]
+        format: Format,
+        /// Write the `--format` report to this file; the terminal still shows human output.
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Reuse cached results for unchanged files, even if `[cache]` does not enable it.
+        #[arg(long, conflicts_with = "no_cache")]
+        cache: bool,
+        /// Check every file instead of reusing cached results for unchanged files.
+        #[arg(long)]
+        no_cache: bool,
+        /// Only check files changed in the git working tree, including untracked files.
+        #[arg(long, group = "git_changes")]
+        changed: bool,
+        /// Only check files with changes staged for commit.
+        #[arg(long, group = "git_changes")]
+        staged: bool,
+        /// Only check files changed since the merge base of REF and HEAD.
+        #[arg(long, value_name = "REF", group = "git_changes")]
+        since: Option<String>,
+        /// With `--changed`, `--staged` or `--since`, only report errors on changed lines.
+        #[arg(long, requires = "git_changes")]
+        changed_lines: bool,
+        /// Only report errors that are not recorded in this baseline file.
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<PathBuf>,
+    },
+    /// Interactively review spelling errors and decide how to handle each one.
+    Review {
+        /// Files or directories to review (defaults to current directory).
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Check files, then re-check them whenever they change.
+    Watch {
+        /// Directory to watch (defaults to current directory).
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// How long changes must settle before re-checking, in milliseconds.
+        #[arg(long, value_name = "MS", default_value_t = 200)]
+        debounce: u64,
     },
     /// Initialize a new default `spellcheck.toml` file.
     Init,
+    /// Run a Language Server Protocol server over stdio for editor integration.
+    Lsp,
+    /// Manage dictionaries.
+    Dict {
+        #[command(subcommand)]
+        command: DictCommand,
+    },
+    /// Manage the result cache.
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    /// Manage baselines of known errors.
+    Baseline {
+        #[command(subcommand)]
+        command: BaselineCommand,
+    },
+}
+
+/// Subcommands of `baseline`.
+#[derive(Subcommand)]
+pub enum BaselineCommand {
+    /// Record every current spelling error, so `check --baseline` only reports new ones.
+    Create {
+        /// Files or directories to check (defaults to current directory).
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Path of the baseline file.
+        #[arg(short, long, value_name = "FILE", default_value = baseline::DEFAULT_PATH)]
+        output: PathBuf,
+    },
+}
+
+/// Subcommands of `cache`.
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Delete the result cache.
+    Clean {
+        /// The path whose cache to delete (defaults to current directory).
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+/// Subcommands of `dict`.
+#[derive(Subcommand)]
+pub enum DictCommand {
+    /// Compile plain-text word lists into a dictionary that loads without parsing.
+    ///
+    /// The output can be used anywhere a word list is accepted, such as
+    /// `dictionary.extra_dictionaries`.
+    Compile {
+        /// Word lists to compile, one word per line.
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// Path of the compiled dictionary.
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
 }
 
 /// Parses command-line arguments and runs the specified command.
@@ -38,84 +157,147 @@ pub async fn run() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Check { path } => {
-            let config_path = if let Some(cfg) = cli.config.as_ref() {
-                cfg.clone()
-            } else {
-                path.join("spellcheck.toml")
+        Commands::Check {
+            path,
+            fix,
+            dry_run,
+            format,
+            output,
+            cache,
+            no_cache,
+            changed,
+            staged,
+            since,
+            changed_lines,
+            baseline,
+        } => {
+            // With `--output`, the report goes to the file and the terminal gets human output.
+            let (mut reporter, format) = match output {
+                Some(file) => {
+                    if format == Format::Human {
+                        anyhow::bail!("--output requires a machine-readable --format");
+                    }
+                    let file = std::fs::File::create(&file)
+                        .with_context(|| format!("Failed to create output file {}", file.display()))?;
+                    let reporters = vec![
+                        report::reporter(Format::Human, Box::new(std::io::stdout())),
+                        report::reporter(format, Box::new(std::io::BufWriter::new(file))),
+                    ];
+                    (Box::new(MultiReporter::new(reporters)) as Box<dyn Reporter>, Format::Human)
+                }
+                None => (report::reporter(format, Box::new(std::io::stdout())), format),
             };
 
-            let config = load_config(&config_path)?;
-            
-            if config_path.exists() {
-                println!("{} Using config: {}", "info".blue(), config_path.display());
-            } else if cli.config.is_none() {
-                // Only show this if they didn't specify a config that doesn't exist
-            } else {
-                println!("{} Config not found at {:?}, using defaults.", "warn".yellow(), config_path);
-            }
-
-            let mut dictionary = Dictionary::new();
-            
-            // Load embedded dictionary
-            let default_words = include_str!("../resources/words.txt");
-            for line in default_words.lines() {
-                dictionary.add_word(line.trim());
-            }
-
-            // Load extra words from config
-            dictionary.add_words(&config.dictionary.extra_words);
-            
-            // Load extra dictionaries
-            for dict_path in &config.dictionary.extra_dictionaries {
-                dictionary.load_from_file(dict_path)?;
-            }
+            let selection = match since {
+                Some(reference) => Some(git::Selection::Since(reference)),
+                None if staged => Some(git::Selection::Staged),
+                None if changed => Some(git::Selection::WorkingTree),
+                None => None,
+            };
+            let baseline_path = baseline.clone();
+            let mut baseline = baseline.as_deref().map(Baseline::load).transpose()?;
+            // Only a full run can tell that a known error no longer occurs.
+            let full_run = selection.is_none();
+            let changes = selection
+                .map(|selection| ChangedFiles::load(&path, &selection, changed_lines))
+                .transpose()?;
 
-            let engine = Engine::try_new(config, dictionary)?;
-            let mut rx = engine.run(path);
+            let use_cache = if no_cache { Some(false) } else { cache.then_some(true) };
+            let (engine, _, _) = build_engine(cli.config.as_ref(), &path, format, use_cache)?;
+            let engine = engine.with_changes(changes).with_skipped(baseline_path);
+            let mut rx = engine.run(path.clone());
 
-            let mut count = 0;
-            let mut errors = 0;
+            let mut summary = Summary::default();
+            let mut by_file: BTreeMap<PathBuf, Vec<SpellError>> = BTreeMap::new();
+            let mut known = 0;
 
             while let Some(res) = rx.recv().await {
                 match res {
                     Ok(error) => {
-                        count += 1;
-                        println!(
-                            "{} in {}:{}:{}: {}",
-                            "Error".red().bold(),
-                            error.file.display().to_string().cyan(),
-                            error.line.to_string().yellow(),
-                            error.col.to_string().yellow(),
-                            error.word.bold()
-                        );
-                        println!("  | {}", error.context.trim());
-                        let col = error.col;
-                        if col > 0 {
-                           println!("  | {:width$}^", "", width = col - 1);
+                        if baseline.as_mut().is_some_and(|baseline| baseline.suppress(&error)) {
+                            known += 1;
+                            continue;
+                        }
+                        summary.spelling_errors += 1;
+                        reporter.spelling_error(&error)?;
+                        if fix {
+                            by_file.entry(error.file.clone()).or_default().push(error);
                         }
                     }
                     Err(e) => {
-                        errors += 1;
-                        eprintln!("{} {}", "error".red().bold(), e);
+                        summary.processing_errors += 1;
+                        reporter.processing_error(&e)?;
                     }
                 }
             }
 
-            if count == 0 {
-                if errors == 0 {
-                    println!("{}", "Perfect spelling! No errors found.".green().bold());
+            if fix {
+                let fixed = apply_fixes(&by_file, dry_run, format)?;
+                if dry_run {
+                    info(format, &format!("{} errors can be fixed automatically.", fixed));
                 } else {
-                    println!("{} Completed with {} processing errors.", "info".blue(), errors);
+                    summary.fixed = fixed;
+                    summary.spelling_errors -= fixed;
                 }
-            } else {
-                println!("\nFound {} spelling errors.", count);
-                if errors > 0 {
-                    println!("(And {} processing errors)", errors);
+            }
+
+            if let Some(baseline) = &baseline {
+                if known > 0 {
+                    info(format, &format!("{} known errors are in the baseline.", known));
                 }
+                let remaining = if full_run { baseline.remaining(&path) } else { Vec::new() };
+                if !remaining.is_empty() {
+                    info(
+                        format,
+                        &format!("{} baseline entries no longer occur and can be removed:", remaining.len()),
+                    );
+                    for entry in &remaining {
+                        let mut line = format!("  {}: {}", entry.file, entry.word);
+                        if entry.count > 1 {
+                            line.push_str(&format!(" ({} occurrences)", entry.count));
+                        }
+                        if format == Format::Human { println!("{}", line) } else { eprintln!("{}", line) }
+                    }
+                }
+            }
+
+            reporter.finish(&summary)?;
+            if summary.spelling_errors > 0 {
                 std::process::exit(1);
             }
         }
+        Commands::Review { path } => {
+            let (engine, config_path, _) = build_engine(cli.config.as_ref(), &path, Format::Human, None)?;
+            let mut rx = engine.run(path);
+
+            let mut found = Vec::new();
+            while let Some(res) = rx.recv().await {
+                match res {
+                    Ok(error) => found.push(error),
+                    Err(e) => eprintln!("{} {}", "error".red().bold(), e),
+                }
+            }
+
+            if found.is_empty() {
+                println!("{}", "Perfect spelling! No errors found.".green().bold());
+                return Ok(());
+            }
+
+            let stdin = std::io::stdin();
+            let summary = review::review(found, &config_path, stdin.lock(), std::io::stdout())?;
+            println!(
+                "\nReplaced {}, added {} to the dictionary, ignored {}, skipped {}.",
+                summary.replaced, summary.added, summary.ignored, summary.skipped
+            );
+        }
+        Commands::Watch { path, debounce } => {
+            let build = || {
+                let (engine, config_path, mut files) = build_engine(cli.config.as_ref(), &path, Format::Human, None)?;
+                files.push(config_path);
+                Ok((engine, files))
+            };
+            watch::watch(path.clone(), Duration::from_millis(debounce), build).await?;
+        }
         Commands::Init => {
             let default_config = r#"[files]
 include = ["src/**/*.rs", "*.md"]
@@ -130,7 +312,134 @@ words = []
             std::fs::write("spellcheck.toml", default_config)?;
             println!("Created spellcheck.toml");
         }
+        Commands::Dict { command: DictCommand::Compile { inputs, output } } => {
+            let mut words = Vec::new();
+            for input in &inputs {
+                let content = std::fs::read_to_string(input)
+                    .with_context(|| format!("Failed to read word list {}", input.display()))?;
+                words.extend(content.lines().map(str::to_string));
+            }
+            let bytes = compiled::encode(&words);
+            let count = compiled::CompiledWords::new(bytes.as_slice()).map_err(anyhow::Error::msg)?.len();
+            std::fs::write(&output, &bytes)
+                .with_context(|| format!("Failed to write dictionary {}", output.display()))?;
+            println!("Compiled {} words into {}", count, output.display());
+        }
+        Commands::Baseline { command: BaselineCommand::Create { path, output } } => {
+            let (engine, _, _) = build_engine(cli.config.as_ref(), &path, Format::Human, None)?;
+            // The previous baseline must not record its own words.
+            let mut rx = engine.with_skipped([output.clone()]).run(path);
+
+            let mut found = Vec::new();
+            while let Some(res) = rx.recv().await {
+                match res {
+                    Ok(error) => found.push(error),
+                    Err(e) => eprintln!("{} {}", "error".red().bold(), e),
+                }
+            }
+
+            let baseline = Baseline::from_errors(&found);
+            baseline.save(&output)?;
+            println!("Recorded {} errors in {}", baseline.len(), output.display());
+        }
+        Commands::Cache { command: CacheCommand::Clean { path } } => {
+            let project = resolve_config(cli.config.as_ref(), &path, Format::Human)?;
+            let dir = cache_dir(&path, &project.config);
+            if cache::clean(&dir)? {
+                println!("Removed cache at {}", dir.display());
+            } else {
+                println!("No cache at {}", dir.display());
+            }
+        }
+        Commands::Lsp => {
+            // stdout carries the protocol, so nothing else may be printed there.
+            lsp::serve(cli.config).await?;
+        }
     }
 
     Ok(())
 }
+
+/// Prints an informational message.
+///
+/// Machine-readable formats own stdout, so their messages go to stderr instead.
+fn info(format: Format, message: &str) {
+    if format == Format::Human {
+        println!("{} {}", "info".blue(), message);
+    } else {
+        eprintln!("{} {}", "info".blue(), message);
+    }
+}
+
+/// Resolves and loads the configuration for scanning `path`.
+fn resolve_config(config_override: Option<&PathBuf>, path: &Path, format: Format) -> anyhow::Result<ProjectConfig> {
+    let project = load_project(path, config_override.map(PathBuf::as_path))?;
+
+    for file in &project.files {
+        info(format, &format!("Using config: {}", file.display()));
+    }
+    // Only warn if they specified a config that doesn't exist
+    if let Some(cfg) = config_override
+        && !cfg.exists()
+    {
+        eprintln!("{} Config not found at {:?}, using defaults.", "warn".yellow(), cfg);
+    }
+
+    Ok(project)
+}
+
+/// Returns the directory the cache of a scan of `path` lives in.
+fn cache_dir(path: &Path, config: &Config) -> PathBuf {
+    let base = if path.is_file() { path.parent().unwrap_or(Path::new("")) } else { path };
+    base.join(&config.cache.dir)
+}
+
+/// Resolves the configuration for scanning `path` and builds an `Engine`.
+///
+/// The result cache is used as `use_cache` says, or if the configuration
+/// enables it when `use_cache` is `None`. Returns the engine together with
+/// the config file that edits go to and all config files in use.
+fn build_engine(
+    config_override: Option<&PathBuf>,
+    path: &Path,
+    format: Format,
+    use_cache: Option<bool>,
+) -> anyhow::Result<(Engine, PathBuf, Vec<PathBuf>)> {
+    let project = resolve_config(config_override, path, format)?;
+    let enabled = use_cache.unwrap_or(project.config.cache.enabled);
+    let cache_dir = enabled.then(|| cache_dir(path, &project.config));
+    let (config_path, files) = (project.path.clone(), project.files.clone());
+    Ok((Engine::for_project(project)?.with_cache(cache_dir), config_path, files))
+}
+
+/// Applies unambiguous corrections to each file, or prints them as a diff.
+///
+/// Returns the number of errors that were (or would be) fixed.
+fn apply_fixes(by_file: &BTreeMap<PathBuf, Vec<SpellError>>, dry_run: bool, format: Format) -> anyhow::Result<usize> {
+    let mut fixed = 0;
+    for (file, errors) in by_file {
+        let content = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read file {}", file.display()))?;
+        let edits = fix::plan(&content, errors);
+        if edits.is_empty() {
+            continue;
+        }
+
+        let (updated, applied) = fix::apply(&content, &edits);
+        if dry_run {
+            let name = file.to_string_lossy().replace('\\', "/");
+            let diff = fix::unified_diff(name.trim_start_matches("./"), &content, &updated);
+            // Keep stdout parseable for machine-readable formats.
+            if format == Format::Human {
+                print!("{}", diff);
+            } else {
+                eprint!("{}", diff);
+            }
+        } else {
+            std::fs::write(file, updated)
+                .with_context(|| format!("Failed to write file {}", file.display()))?;
+        }
+        fixed += applied;
+    }
+    Ok(fixed)
+}
@@ -0,0 +1,286 @@
+//! Precompiled word lists.
+//!
+//! A compiled word list stores lowercase words sorted by byte and front-coded
+//! in blocks: the first word of each block is stored in full, and every
+//! following word as the length of the prefix it shares with its predecessor
+//! plus the remaining bytes. Lookups binary-search the blocks and scan one
+//! block, directly on the bytes, so a list is usable as soon as it is loaded.
+//!
+//! Layout (integers are little-endian):
+//!
+//! ```text
+//! magic        8 bytes   b"SPCDICT\x01"
+//! word count   u32
+//! block count  u32
+//! offsets      u32 × block count, block start relative to the data
+//! data         blocks of varint-encoded lengths and word bytes
+//! ```
+//!
+//! This file has no dependencies outside `std` because the build script
+//! includes it to compile the embedded word list.
+
+use std::cmp::Ordering;
+
+/// Identifies a compiled word list and its format version.
+pub const MAGIC: &[u8; 8] = b"SPCDICT\x01";
+
+/// The number of words per front-coded block.
+const BLOCK_SIZE: usize = 16;
+
+const HEADER_LEN: usize = MAGIC.len() + 8;
+
+/// Compiles `words` into the binary format.
+///
+/// Words are lowercased, deduplicated and sorted; empty words are dropped.
+pub fn encode<I, S>(words: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut words: Vec<String> = words
+        .into_iter()
+        .map(|w| w.as_ref().trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+
+    let mut offsets = Vec::new();
+    let mut data = Vec::new();
+    for block in words.chunks(BLOCK_SIZE) {
+        offsets.push(data.len() as u32);
+        write_varint(&mut data, block[0].len());
+        data.extend_from_slice(block[0].as_bytes());
+        for pair in block.windows(2) {
+            let (prev, word) = (pair[0].as_bytes(), pair[1].as_bytes());
+            let shared = common_prefix(prev, word);
+            write_varint(&mut data, shared);
+            write_varint(&mut data, word.len() - shared);
+            data.extend_from_slice(&word[shared..]);
+        }
+    }
+
+    let mut out = Vec::with_capacity(HEADER_LEN + offsets.len() * 4 + data.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&(words.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(offsets.len() as u32).to_le_bytes());
+    for offset in offsets {
+        out.extend_from_slice(&offset.to_le_bytes());
+    }
+    out.extend_from_slice(&data);
+    out
+}
+
+/// Returns `true` if `bytes` start like a compiled word list.
+pub fn is_compiled(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// A compiled word list, queried in place.
+#[derive(Debug, Clone)]
+pub struct CompiledWords<B> {
+    bytes: B,
+    count: usize,
+    blocks: usize,
+}
+
+impl<B: AsRef<[u8]>> CompiledWords<B> {
+    /// Wraps the bytes of a compiled word list, checking its header and offsets.
+    pub fn new(bytes: B) -> Result<Self, String> {
+        let raw = bytes.as_ref();
+        if !is_compiled(raw) {
+            return Err("not a compiled word list (bad magic or unsupported version)".to_string());
+        }
+        let count = read_u32(raw, MAGIC.len()).ok_or("truncated header")? as usize;
+        let blocks = read_u32(raw, MAGIC.len() + 4).ok_or("truncated header")? as usize;
+        let data_start = HEADER_LEN + blocks * 4;
+        if raw.len() < data_start || blocks != count.div_ceil(BLOCK_SIZE) {
+            return Err("truncated block table".to_string());
+        }
+        let data_len = raw.len() - data_start;
+        let mut previous = None;
+        for i in 0..blocks {
+            let offset = read_u32(raw, HEADER_LEN + i * 4).ok_or("truncated block table")? as usize;
+            if offset >= data_len || previous.is_some_and(|p| offset <= p) {
+                return Err(format!("invalid offset for block {}", i));
+            }
+            previous = Some(offset);
+        }
+        Ok(Self { bytes, count, blocks })
+    }
+
+    /// Returns the number of words.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns `true` if the list has no words.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns `true` if the list contains `word`, which must be lowercase.
+    pub fn contains(&self, word: &str) -> bool {
+        let target = word.as_bytes();
+
+        // Find the last block whose first word is <= target.
+        let (mut low, mut high) = (0, self.blocks);
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.first_word(mid).map(|first| first.cmp(target)) {
+                Some(Ordering::Equal) => return true,
+                Some(Ordering::Less) => low = mid + 1,
+                Some(Ordering::Greater) => high = mid,
+                None => return false,
+            }
+        }
+        low > 0 && self.block_contains(low - 1, target).unwrap_or(false)
+    }
+
+    /// Returns an iterator over all words, in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = String> + '_ {
+        (0..self.blocks).flat_map(move |block| self.block_words(block))
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.bytes.as_ref()[HEADER_LEN + self.blocks * 4..]
+    }
+
+    fn block(&self, block: usize) -> &[u8] {
+        let raw = self.bytes.as_ref();
+        let start = read_u32(raw, HEADER_LEN + block * 4).unwrap_or(0) as usize;
+        let end = if block + 1 < self.blocks {
+            read_u32(raw, HEADER_LEN + (block + 1) * 4).unwrap_or(0) as usize
+        } else {
+            self.data().len()
+        };
+        self.data().get(start..end).unwrap_or(&[])
+    }
+
+    fn first_word(&self, block: usize) -> Option<&[u8]> {
+        let mut cursor = self.block(block);
+        let len = read_varint(&mut cursor)?;
+        cursor.get(..len)
+    }
+
+    /// Scans a block for `target`, knowing its first word is smaller.
+    ///
+    /// Tracks how many leading bytes of `target` the current word matches, so
+    /// no word has to be rebuilt: a word sharing more than that with its
+    /// predecessor is still smaller than `target`, one sharing less is larger.
+    fn block_contains(&self, block: usize, target: &[u8]) -> Option<bool> {
+        let mut cursor = self.block(block);
+        let len = read_varint(&mut cursor)?;
+        let first = cursor.get(..len)?;
+        cursor = &cursor[len..];
+        let mut matched = common_prefix(first, target);
+
+        while !cursor.is_empty() {
+            let shared = read_varint(&mut cursor)?;
+            let len = read_varint(&mut cursor)?;
+            let suffix = cursor.get(..len)?;
+            cursor = &cursor[len..];
+
+            match shared.cmp(&matched) {
+                Ordering::Greater => continue,
+                Ordering::Less => return Some(false),
+                Ordering::Equal => {}
+            }
+            let rest = &target[matched..];
+            match suffix.cmp(rest) {
+                Ordering::Equal => return Some(true),
+                Ordering::Greater => return Some(false),
+                Ordering::Less => matched += common_prefix(suffix, rest),
+            }
+        }
+        Some(false)
+    }
+
+    fn block_words(&self, block: usize) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut cursor = self.block(block);
+        let mut current: Vec<u8> = Vec::new();
+        let mut first = true;
+        while !cursor.is_empty() {
+            let shared = if first { 0 } else { read_varint(&mut cursor).unwrap_or(usize::MAX) };
+            first = false;
+            let Some(len) = read_varint(&mut cursor) else { break };
+            let (Some(suffix), true) = (cursor.get(..len), shared <= current.len()) else { break };
+            current.truncate(shared);
+            current.extend_from_slice(suffix);
+            cursor = &cursor[len..];
+            if let Ok(word) = std::str::from_utf8(&current) {
+                words.push(word.to_string());
+            }
+        }
+        words
+    }
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let chunk = bytes.get(at..at + 4)?;
+    Some(u32::from_le_bytes(chunk.try_into().ok()?))
+}
+
+/// Writes `value` as an unsigned LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads an unsigned LEB128 varint, advancing `cursor`.
+fn read_varint(cursor: &mut &[u8]) -> Option<usize> {
+    let mut value = 0usize;
+    for (i, &byte) in cursor.iter().enumerate().take(5) {
+        value |= usize::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *cursor = &cursor[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let words: Vec<String> = (0..100).map(|i| format!("word{i}")).chain(["Zebra".to_string(), "über".to_string()]).collect();
+        let list = CompiledWords::new(encode(&words)).unwrap();
+
+        assert_eq!(list.len(), 102);
+        for word in &words {
+            assert!(list.contains(&word.to_lowercase()), "{word}");
+        }
+        let mut expected: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+        expected.sort();
+        assert_eq!(list.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_missing_words() {
+        let list = CompiledWords::new(encode(["car", "card", "care", "cared", "cart", "dog"])).unwrap();
+        for word in ["", "a", "ca", "cab", "cards", "carf", "cas", "do", "dogs", "zoo"] {
+            assert!(!list.contains(word), "{word}");
+        }
+        assert!(list.contains("cared"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_data() {
+        assert!(CompiledWords::new(b"hello\nworld\n".as_slice()).is_err());
+        let mut bytes = encode(["alpha", "beta"]);
+        bytes.truncate(HEADER_LEN + 2);
+        assert!(CompiledWords::new(bytes).is_err());
+        assert!(CompiledWords::new(encode(Vec::<String>::new())).unwrap().is_empty());
+    }
+}
@@ -1,25 +1,332 @@
 //! Utilities for loading and managing the spell checker configuration.
+//!
+//! A scan combines several config files:
+//!
+//! - Files named `spellcheck.toml` from the scanned directory up to the
+//!   repository root, or one given with `--config`, make up the root config.
+//! - Files in subdirectories apply to their subtree, on top of the config of
+//!   the enclosing directory.
+//! - Any of them may `extends` other files, which are loaded first.
+//!
+//! Files are merged as TOML before they are parsed into a `Config`: tables
+//! are merged key by key, lists are concatenated without duplicates, and
+//! other values are replaced by the later file.
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use crate::config_schema::Config;
+use ignore::WalkBuilder;
+use serde::Deserialize;
+use toml_edit::{Array, DocumentMut, Item, Table};
 
-/// Loads a `Config` from the specified path.
+/// The file name of configuration files.
+pub const CONFIG_FILE: &str = "spellcheck.toml";
+
+/// The configuration for a scan, assembled from all applicable files.
+#[derive(Debug)]
+pub struct ProjectConfig {
+    /// The configuration for the scanned path.
+    pub config: Config,
+    /// The config file that project-wide edits, such as adding words, go
+    /// to. It may not exist yet.
+    pub path: PathBuf,
+    /// Every config file in use, root config first.
+    pub files: Vec<PathBuf>,
+    /// Configurations for subdirectories with their own config file, by
+    /// directory relative to the scanned path. Parents come before their
+    /// subdirectories.
+    pub nested: Vec<(PathBuf, Config)>,
+}
+
+/// Loads a `Config` from the specified path, including the files it extends.
 ///
 /// If the file does not exist, it returns the default configuration.
 pub fn load_config(path: &Path) -> Result<Config> {
     if !path.exists() {
         return Ok(Config::default());
     }
+    parse(load_table(path, &mut Vec::new())?)
+}
+
+/// Loads every config file that applies when scanning `root`.
+///
+/// Without `config_override`, the root config merges the `spellcheck.toml`
+/// files from the repository root (the first directory with a `.git`) or the
+/// nearest file with `root = true`, down to `root`. Config files in
+/// subdirectories of `root` are loaded as nested configurations.
+pub fn load_project(root: &Path, config_override: Option<&Path>) -> Result<ProjectConfig> {
+    let dir = if root.is_file() { root.parent().unwrap_or(Path::new(".")) } else { root };
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+
+    let (files, path) = match config_override {
+        Some(path) => {
+            let files = if path.exists() { vec![path.to_path_buf()] } else { Vec::new() };
+            (files, path.to_path_buf())
+        }
+        None => {
+            let files = discover(dir)?;
+            let path = files.last().cloned().unwrap_or_else(|| dir.join(CONFIG_FILE));
+            (files, path)
+        }
+    };
+    let mut table = toml::Table::new();
+    for file in &files {
+        merge(&mut table, load_table(file, &mut Vec::new())?);
+    }
+    let config = parse(table)?;
+
+    let mut project = ProjectConfig { config, path, files, nested: Vec::new() };
+    if root.is_dir() {
+        load_nested(root, &mut project)?;
+    }
+    Ok(project)
+}
+
+/// Finds the config files that apply to `dir`, outermost first.
+fn discover(dir: &Path) -> Result<Vec<PathBuf>> {
+    let absolute = std::path::absolute(dir).with_context(|| format!("Failed to resolve {}", dir.display()))?;
+    let mut files = Vec::new();
+    for (depth, ancestor) in absolute.ancestors().enumerate() {
+        let file = ancestor.join(CONFIG_FILE);
+        if file.is_file() {
+            // Keep the path of the scanned directory's own file as given.
+            let file = if depth == 0 { dir.join(CONFIG_FILE) } else { file };
+            let is_root = read_table(&file)?.get("root").and_then(toml::Value::as_bool).unwrap_or(false);
+            files.push(file);
+            if is_root {
+                break;
+            }
+        }
+        if ancestor.join(".git").exists() {
+            break;
+        }
+    }
+    files.reverse();
+    Ok(files)
+}
+
+/// Loads the config files in subdirectories of `root` into `project.nested`.
+///
+/// Each applies on top of the configuration of its closest parent, unless
+/// it sets `root = true`. Its glob patterns are relative to
+/// its own directory, so they are prefixed with that directory before
+/// merging.
+fn load_nested(root: &Path, project: &mut ProjectConfig) -> Result<()> {
+    let mut walker = WalkBuilder::new(root);
+    walker.add_custom_ignore_filename(".spellcheckignore");
+    let mut dirs: Vec<PathBuf> = walker
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() > 1 && entry.file_name() == CONFIG_FILE)
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .filter_map(|entry| entry.path().parent()?.strip_prefix(root).ok().map(normalize))
+        .collect();
+    dirs.sort_by_key(|dir| dir.components().count());
+
+    let mut tables: Vec<(PathBuf, toml::Table)> = Vec::new();
+    let root_table = toml::Table::try_from(&project.config).context("Failed to serialize configuration")?;
+    for dir in dirs {
+        let file = root.join(&dir).join(CONFIG_FILE);
+        let mut own = load_table(&file, &mut Vec::new())?;
+        rebase_patterns(&mut own, &dir.to_string_lossy().replace('\\', "/"));
+
+        // A nested root config is read as if it were scanned on its own.
+        let mut table = if own.get("root").and_then(toml::Value::as_bool).unwrap_or(false) {
+            toml::Table::new()
+        } else {
+            let parent = tables.iter().rev().find(|(parent, _)| dir.starts_with(parent));
+            parent.map_or_else(|| root_table.clone(), |(_, table)| table.clone())
+        };
+        merge(&mut table, own);
+        let config = parse(table.clone()).with_context(|| format!("Invalid configuration in {}", file.display()))?;
+        // Materialize defaults so nested files add to them rather than replace them.
+        let table = toml::Table::try_from(&config).context("Failed to serialize configuration")?;
+
+        project.files.push(file);
+        project.nested.push((dir.clone(), config));
+        tables.push((dir, table));
+    }
+    Ok(())
+}
 
+/// Reads and parses one config file as TOML.
+fn read_table(path: &Path) -> Result<toml::Table> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read config file at {:?}", path))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse TOML configuration in {}", path.display()))
+}
+
+/// Loads a config file merged on top of the files it extends.
+///
+/// Relative dictionary paths are resolved against the file's directory.
+/// `chain` holds the files currently being loaded, to detect cycles.
+fn load_table(path: &Path, chain: &mut Vec<PathBuf>) -> Result<toml::Table> {
+    let canonical = path.canonicalize().with_context(|| format!("Failed to read config file at {:?}", path))?;
+    if chain.contains(&canonical) {
+        bail!("Config file {} extends itself", path.display());
+    }
+    let mut table = read_table(path)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    resolve_paths(&mut table, dir);
+
+    let extends = match table.remove("extends") {
+        None => Vec::new(),
+        Some(toml::Value::String(base)) => vec![base],
+        Some(toml::Value::Array(bases)) => bases
+            .into_iter()
+            .map(|base| match base {
+                toml::Value::String(base) => Ok(base),
+                _ => bail!("`extends` in {} must list file paths", path.display()),
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => bail!("`extends` in {} must be a path or a list of paths", path.display()),
+    };
+
+    chain.push(canonical);
+    let mut merged = toml::Table::new();
+    for base in extends {
+        let mut base = load_table(&dir.join(&base), chain)
+            .with_context(|| format!("Failed to load {} extended by {}", base, path.display()))?;
+        base.remove("root");
+        merge(&mut merged, base);
+    }
+    chain.pop();
+    merge(&mut merged, table);
+    Ok(merged)
+}
+
+fn parse(table: toml::Table) -> Result<Config> {
+    Config::deserialize(table).context("Failed to parse TOML configuration")
+}
+
+/// Merges `overlay` into `base`.
+///
+/// Tables are merged recursively, lists are concatenated (`base` first)
+/// skipping values already present, and anything else is replaced.
+pub fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        let Some(existing) = base.get_mut(&key) else {
+            base.insert(key, value);
+            continue;
+        };
+        match (existing, value) {
+            (toml::Value::Table(existing), toml::Value::Table(value)) => merge(existing, value),
+            (toml::Value::Array(existing), toml::Value::Array(values)) => {
+                for value in values {
+                    if !existing.contains(&value) {
+                        existing.push(value);
+                    }
+                }
+            }
+            (existing, value) => *existing = value,
+        }
+    }
+}
+
+/// Makes the dictionary file paths in a config table relative to `dir`.
+fn resolve_paths(table: &mut toml::Table, dir: &Path) {
+    let Some(dictionary) = table.get_mut("dictionary").and_then(toml::Value::as_table_mut) else {
+        return;
+    };
+    resolve_dictionary_paths(dictionary, "extra_dictionaries", dir);
+    if let Some(locales) = dictionary.get_mut("locales").and_then(toml::Value::as_table_mut) {
+        for (_, locale) in locales.iter_mut() {
+            let Some(locale) = locale.as_table_mut() else { continue };
+            resolve_dictionary_paths(locale, "dictionaries", dir);
+        }
+    }
+}
 
-    let config: Config = toml::from_str(&content)
+/// Resolves the list of files under `key` and the `hunspell` entries of a
+/// dictionary or locale table.
+fn resolve_dictionary_paths(table: &mut toml::Table, key: &str, dir: &Path) {
+    let resolve = |value: &mut toml::Value| {
+        if let toml::Value::String(path) = value
+            && Path::new(path.as_str()).is_relative()
+        {
+            *path = dir.join(path.as_str()).to_string_lossy().into_owned();
+        }
+    };
+    if let Some(files) = table.get_mut(key).and_then(toml::Value::as_array_mut) {
+        files.iter_mut().for_each(resolve);
+    }
+    if let Some(entries) = table.get_mut("hunspell").and_then(toml::Value::as_array_mut) {
+        for entry in entries.iter_mut().filter_map(toml::Value::as_table_mut) {
+            entry.get_mut("dic").map(resolve);
+            entry.get_mut("aff").map(resolve);
+        }
+    }
+}
+
+/// Prefixes the glob patterns of a nested config table with its directory.
+fn rebase_patterns(table: &mut toml::Table, dir: &str) {
+    let rebase = |patterns: Option<&mut toml::Value>| {
+        for pattern in patterns.and_then(toml::Value::as_array_mut).into_iter().flatten() {
+            if let toml::Value::String(pattern) = pattern {
+                *pattern = format!("{}/{}", dir, pattern.trim_start_matches("./"));
+            }
+        }
+    };
+    if let Some(files) = table.get_mut("files").and_then(toml::Value::as_table_mut) {
+        rebase(files.get_mut("include"));
+        rebase(files.get_mut("exclude"));
+    }
+    let locale_overrides = table
+        .get_mut("dictionary")
+        .and_then(toml::Value::as_table_mut)
+        .and_then(|dictionary| dictionary.get_mut("locale_overrides"))
+        .and_then(toml::Value::as_array_mut);
+    for entry in locale_overrides.into_iter().flatten().filter_map(toml::Value::as_table_mut) {
+        rebase(entry.get_mut("files"));
+    }
+    let overrides = table.get_mut("overrides").and_then(toml::Value::as_array_mut);
+    for entry in overrides.into_iter().flatten().filter_map(toml::Value::as_table_mut) {
+        rebase(entry.get_mut("files"));
+    }
+}
+
+/// Drops `.` components, so `./docs` and `docs` compare equal.
+pub fn normalize(path: &Path) -> PathBuf {
+    path.components().filter(|c| *c != Component::CurDir).collect()
+}
+
+/// Adds a word to `dictionary.extra_words` in the config file at `path`.
+///
+/// The file is edited in place, preserving its formatting and comments, and is
+/// created if it does not exist. Words already listed (case-insensitively) are
+/// not added again.
+pub fn add_extra_word(path: &Path, word: &str) -> Result<()> {
+    let content = if path.exists() {
+        fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file at {:?}", path))?
+    } else {
+        String::new()
+    };
+
+    let mut doc: DocumentMut = content.parse()
         .with_context(|| "Failed to parse TOML configuration")?;
 
-    Ok(config)
+    let dictionary = doc.entry("dictionary").or_insert_with(|| Item::Table(Table::new()));
+    let words = dictionary
+        .as_table_like_mut()
+        .context("`dictionary` must be a table")?
+        .entry("extra_words")
+        .or_insert(Item::Value(Array::new().into()))
+        .as_array_mut()
+        .context("`dictionary.extra_words` must be an array")?;
+
+    let exists = words
+        .iter()
+        .any(|w| w.as_str().is_some_and(|w| w.eq_ignore_ascii_case(word)));
+    if !exists {
+        words.push(word);
+    }
+
+    fs::write(path, doc.to_string())
+        .with_context(|| format!("Failed to write config file at {:?}", path))?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -38,10 +345,110 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_add_extra_word() -> Result<()> {
+        let mut file = NamedTempFile::new()?;
+        writeln!(file, "# Project words\n[dictionary]\nextra_words = [\"custom\"]")?;
+
+        add_extra_word(file.path(), "tokio")?;
+        add_extra_word(file.path(), "Custom")?;
+
+        let content = fs::read_to_string(file.path())?;
+        assert!(content.starts_with("# Project words"));
+        let config = load_config(file.path())?;
+        assert_eq!(config.dictionary.extra_words, vec!["custom", "tokio"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extends() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let shared = dir.path().join("shared");
+        let project = dir.path().join("project");
+        fs::create_dir_all(&shared)?;
+        fs::create_dir_all(&project)?;
+        fs::write(
+            shared.join("base.toml"),
+            "root = true\n[files]\ninclude = [\"**/*.md\"]\n\n[dictionary]\nextra_words = [\"acme\"]\nextra_dictionaries = [\"words.txt\"]\nlocale = \"en-GB\"\n",
+        )?;
+        fs::write(
+            project.join(CONFIG_FILE),
+            "extends = [\"../shared/base.toml\"]\n[files]\ninclude = [\"**/*.rs\", \"**/*.md\"]\n\n[dictionary]\nextra_words = [\"tokio\", \"acme\"]\nlocale = \"en-US\"\n",
+        )?;
+
+        let config = load_config(&project.join(CONFIG_FILE))?;
+        assert_eq!(config.files.include, vec!["**/*.md", "**/*.rs"]);
+        assert_eq!(config.dictionary.extra_words, vec!["acme", "tokio"]);
+        assert_eq!(config.dictionary.extra_dictionaries, vec![project.join("../shared/words.txt")]);
+        assert_eq!(config.dictionary.locale, "en-US");
+        // `root` belongs to the file that sets it, not to files extending it.
+        assert!(!config.root);
+
+        fs::write(shared.join("base.toml"), "extends = \"../project/spellcheck.toml\"\n")?;
+        let error = load_config(&project.join(CONFIG_FILE)).unwrap_err();
+        assert!(format!("{:#}", error).contains("extends itself"), "{:#}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_project() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let repo = dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git"))?;
+        fs::create_dir_all(repo.join("docs/api"))?;
+        // Outside the repository, so it is not discovered.
+        fs::write(dir.path().join(CONFIG_FILE), "[dictionary]\nextra_words = [\"outside\"]\n")?;
+        fs::write(repo.join(CONFIG_FILE), "[dictionary]\nextra_words = [\"repo\"]\n")?;
+        fs::write(repo.join("docs").join(CONFIG_FILE), "[files]\nexclude = [\"drafts/**\"]\n\n[dictionary]\nextra_words = [\"docs\"]\n\n[[overrides]]\nfiles = [\"*.rs\"]\n")?;
+        fs::write(repo.join("docs/api").join(CONFIG_FILE), "[dictionary]\nextra_words = [\"api\"]\n")?;
+
+        let project = load_project(&repo, None)?;
+        assert_eq!(project.path, repo.join(CONFIG_FILE));
+        assert_eq!(project.config.dictionary.extra_words, vec!["repo"]);
+        let nested: Vec<(&Path, &Vec<String>)> = project
+            .nested
+            .iter()
+            .map(|(dir, config)| (dir.as_path(), &config.dictionary.extra_words))
+            .collect();
+        assert_eq!(
+            nested,
+            vec![
+                (Path::new("docs"), &vec!["repo".to_string(), "docs".to_string()]),
+                (Path::new("docs/api"), &vec!["repo".to_string(), "docs".to_string(), "api".to_string()]),
+            ]
+        );
+        // Nested patterns are relative to their directory, and defaults are kept.
+        let mut exclude = project.config.files.exclude.clone();
+        exclude.push("docs/drafts/**".to_string());
+        assert_eq!(project.nested[0].1.files.exclude, exclude);
+        assert_eq!(project.nested[0].1.files.include, project.config.files.include);
+        assert_eq!(project.nested[0].1.overrides[0].files, vec!["docs/*.rs"]);
+
+        // Scanning a subdirectory picks up the configs above it.
+        let project = load_project(&repo.join("docs"), None)?;
+        assert_eq!(project.path, repo.join("docs").join(CONFIG_FILE));
+        assert_eq!(project.config.dictionary.extra_words, vec!["repo", "docs"]);
+        assert_eq!(project.nested[0].0, Path::new("api"));
+
+        // `root = true` stops the search, and `--config` skips it.
+        fs::write(repo.join("docs").join(CONFIG_FILE), "root = true\n")?;
+        assert!(load_project(&repo.join("docs"), None)?.config.dictionary.extra_words.is_empty());
+        let project = load_project(&repo, None)?;
+        assert!(project.nested[0].1.dictionary.extra_words.is_empty());
+        assert_eq!(project.nested[1].1.dictionary.extra_words, vec!["api"]);
+        let custom = dir.path().join("custom.toml");
+        fs::write(&custom, "[dictionary]\nextra_words = [\"custom\"]\n")?;
+        let project = load_project(&repo.join("docs/api"), Some(&custom))?;
+        assert_eq!(project.config.dictionary.extra_words, vec!["custom"]);
+        assert_eq!(project.path, custom);
+        Ok(())
+    }
+
     #[test]
     fn test_default_config() -> Result<()> {
         let config = Config::default();
-        assert!(config.files.include.contains(&"**/*.{md,txt,rs,js,ts,py,c,cpp,h,hpp,go,java}".to_string()));
+        assert!(config.files.include.contains(&"**/*.{md,txt,rs,js,ts,py,c,cpp,h,hpp,go,java,html,htm,xhtml,xml,svg,rst,rest,adoc,asciidoc,tex,latex,ltx,ipynb,json,yaml,yml,toml}".to_string()));
+        assert!(config.files.exclude.contains(&"**/package-lock.json".to_string()));
         Ok(())
     }
 }
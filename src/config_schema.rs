@@ -1,11 +1,21 @@
 //! Configuration schema for the spell checker.
 
+use crate::html;
+use crate::lexer::{Language, RegionKind};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// The root configuration structure for `spell_check`.
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
+    /// Stops the search for config files in parent directories here.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub root: bool,
+    /// Config files this one builds on, relative to its directory. They are
+    /// merged in order before this file when it is loaded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<PathBuf>,
     /// File scanning configuration.
     #[serde(default)]
     pub files: FilesConfig,
@@ -15,6 +25,140 @@ pub struct Config {
     /// Custom ignore settings.
     #[serde(default)]
     pub ignore: IgnoreConfig,
+    /// Per-language settings for source files.
+    #[serde(default)]
+    pub languages: LanguagesConfig,
+    /// Result cache settings.
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// Settings for files matching glob patterns, applied in order on top of
+    /// the settings above.
+    #[serde(default)]
+    pub overrides: Vec<Override>,
+}
+
+/// Settings for the files matching `files`.
+///
+/// Words are accepted in addition to the project-wide ones; every other
+/// setting that is given replaces the project-wide value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Override {
+    /// Glob patterns, relative to the scan root.
+    pub files: Vec<String>,
+    /// Words to allow in matching files.
+    #[serde(default)]
+    pub dictionary: OverrideDictionary,
+    /// Words and patterns to ignore in matching files.
+    #[serde(default)]
+    pub ignore: OverrideIgnore,
+    /// The locale to use for matching files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Reads matching files as this language instead of detecting it from
+    /// the extension; `text` checks them in full.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<FileLanguage>,
+    /// Check regular comments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comments: Option<bool>,
+    /// Check doc comments and docstrings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_comments: Option<bool>,
+    /// Check the contents of string literals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strings: Option<bool>,
+}
+
+/// The dictionary settings of an `Override`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OverrideDictionary {
+    /// Words to allow in matching files.
+    #[serde(default)]
+    pub extra_words: Vec<String>,
+}
+
+/// The ignore settings of an `Override`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OverrideIgnore {
+    /// Words to ignore in matching files.
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Regular expressions for text not to check in matching files.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// A language name accepted by `Override::language`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileLanguage {
+    /// Prose: the whole file is checked.
+    Text,
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+    C,
+    Java,
+    Markdown,
+    Html,
+    Xml,
+    Rst,
+    AsciiDoc,
+    Latex,
+    Notebook,
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl FileLanguage {
+    /// Returns the language to lex files with, or `None` for prose.
+    pub fn language(self) -> Option<Language> {
+        match self {
+            FileLanguage::Text => None,
+            FileLanguage::Rust => Some(Language::Rust),
+            FileLanguage::Python => Some(Language::Python),
+            FileLanguage::JavaScript => Some(Language::JavaScript),
+            FileLanguage::Go => Some(Language::Go),
+            FileLanguage::C => Some(Language::C),
+            FileLanguage::Java => Some(Language::Java),
+            FileLanguage::Markdown => Some(Language::Markdown),
+            FileLanguage::Html => Some(Language::Html),
+            FileLanguage::Xml => Some(Language::Xml),
+            FileLanguage::Rst => Some(Language::Rst),
+            FileLanguage::AsciiDoc => Some(Language::AsciiDoc),
+            FileLanguage::Latex => Some(Language::Latex),
+            FileLanguage::Notebook => Some(Language::Notebook),
+            FileLanguage::Json => Some(Language::Json),
+            FileLanguage::Yaml => Some(Language::Yaml),
+            FileLanguage::Toml => Some(Language::Toml),
+        }
+    }
+}
+
+/// Configuration for the persistent result cache.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Whether unchanged files reuse the results of the previous run.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Cache directory, relative to the scanned path.
+    #[serde(default = "default_cache_dir")]
+    pub dir: PathBuf,
+}
+
+fn default_cache_dir() -> PathBuf {
+    PathBuf::from(".spellcheck-cache")
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: default_cache_dir(),
+        }
+    }
 }
 
 /// Configuration for controlling which files are scanned.
@@ -24,25 +168,33 @@ pub struct FilesConfig {
     #[serde(default = "default_include")]
     pub include: Vec<String>,
     /// List of glob patterns to exclude from the scan.
-    #[serde(default)]
+    #[serde(default = "default_exclude")]
     pub exclude: Vec<String>,
 }
 
 fn default_include() -> Vec<String> {
-    vec!["**/*.{md,txt,rs,js,ts,py,c,cpp,h,hpp,go,java}".to_string()]
+    vec!["**/*.{md,txt,rs,js,ts,py,c,cpp,h,hpp,go,java,html,htm,xhtml,xml,svg,rst,rest,adoc,asciidoc,tex,latex,ltx,ipynb,json,yaml,yml,toml}".to_string()]
+}
+
+/// Lockfiles, whose strings are package names and hashes rather than prose.
+fn default_exclude() -> Vec<String> {
+    ["**/*.lock", "**/package-lock.json", "**/npm-shrinkwrap.json", "**/pnpm-lock.yaml"]
+        .into_iter()
+        .map(String::from)
+        .collect()
 }
 
 impl Default for FilesConfig {
     fn default() -> Self {
         Self {
             include: default_include(),
-            exclude: Vec::new(),
+            exclude: default_exclude(),
         }
     }
 }
 
 /// Configuration for the dictionary and custom word lists.
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DictionaryConfig {
     /// List of additional words to allow globally.
     #[serde(default)]
@@ -50,6 +202,81 @@ pub struct DictionaryConfig {
     /// Paths to external line-separated dictionary files.
     #[serde(default)]
     pub extra_dictionaries: Vec<PathBuf>,
+    /// Hunspell dictionaries, each a `.dic` word list with its `.aff` rules.
+    #[serde(default)]
+    pub hunspell: Vec<HunspellConfig>,
+    /// The locale whose words are accepted: `en` (US and UK spellings),
+    /// `en-US`, `en-GB`, or a name defined in `locales`.
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Additional word sets, by locale name. Settings for a bundled locale
+    /// extend it.
+    #[serde(default)]
+    pub locales: BTreeMap<String, LocaleConfig>,
+    /// Locales for files matching glob patterns; the last matching entry wins.
+    #[serde(default)]
+    pub locale_overrides: Vec<LocaleOverride>,
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+impl Default for DictionaryConfig {
+    fn default() -> Self {
+        Self {
+            extra_words: Vec::new(),
+            extra_dictionaries: Vec::new(),
+            hunspell: Vec::new(),
+            locale: default_locale(),
+            locales: BTreeMap::new(),
+            locale_overrides: Vec::new(),
+        }
+    }
+}
+
+/// A named word set, such as `de-DE` or a regional variant.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LocaleConfig {
+    /// Another locale whose words are accepted as well, e.g. `en` to keep
+    /// English words in code comments.
+    #[serde(default)]
+    pub extends: Option<String>,
+    /// Words to allow in this locale.
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Paths to line-separated dictionary files for this locale.
+    #[serde(default)]
+    pub dictionaries: Vec<PathBuf>,
+    /// Hunspell dictionaries for this locale.
+    #[serde(default)]
+    pub hunspell: Vec<HunspellConfig>,
+}
+
+/// Selects a locale for the files matching `files`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocaleOverride {
+    /// Glob patterns, relative to the scan root.
+    pub files: Vec<String>,
+    /// The locale to use for matching files.
+    pub locale: String,
+}
+
+/// A Hunspell dictionary given as a `.dic`/`.aff` pair.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HunspellConfig {
+    /// Path to the `.dic` file.
+    pub dic: PathBuf,
+    /// Path to the `.aff` file. Defaults to the `.dic` path with an `.aff` extension.
+    #[serde(default)]
+    pub aff: Option<PathBuf>,
+}
+
+impl HunspellConfig {
+    /// Returns the path of the `.aff` file.
+    pub fn aff_path(&self) -> PathBuf {
+        self.aff.clone().unwrap_or_else(|| self.dic.with_extension("aff"))
+    }
 }
 
 /// Configuration for words to ignore during spelling checks.
@@ -58,4 +285,198 @@ pub struct IgnoreConfig {
     /// List of specific words to ignore (case-insensitive).
     #[serde(default)]
     pub words: Vec<String>,
+    /// Regular expressions; text matching any of them is not checked.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Built-in patterns for common kinds of noise.
+    #[serde(default)]
+    pub builtin: BuiltinPatterns,
+}
+
+/// Toggles for the built-in ignore patterns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuiltinPatterns {
+    /// URLs such as `https://example.com/path` and `www.example.com`.
+    #[serde(default = "default_true")]
+    pub urls: bool,
+    /// Email addresses.
+    #[serde(default = "default_true")]
+    pub emails: bool,
+    /// Hexadecimal hashes and UUIDs.
+    #[serde(default = "default_true")]
+    pub hashes: bool,
+    /// Long base64 strings.
+    #[serde(default = "default_true")]
+    pub base64: bool,
+    /// File paths such as `/usr/lib`, `./src/main.rs` and `C:\Windows`.
+    #[serde(default = "default_true")]
+    pub paths: bool,
+}
+
+impl Default for BuiltinPatterns {
+    fn default() -> Self {
+        Self {
+            urls: true,
+            emails: true,
+            hashes: true,
+            base64: true,
+            paths: true,
+        }
+    }
+}
+
+/// Per-language scanning settings, keyed by language name.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LanguagesConfig {
+    /// Settings for Rust (`.rs`) files.
+    #[serde(default)]
+    pub rust: LanguageConfig,
+    /// Settings for Python (`.py`) files.
+    #[serde(default)]
+    pub python: LanguageConfig,
+    /// Settings for JavaScript and TypeScript files.
+    #[serde(default)]
+    pub javascript: LanguageConfig,
+    /// Settings for Go (`.go`) files.
+    #[serde(default)]
+    pub go: LanguageConfig,
+    /// Settings for C and C++ sources and headers.
+    #[serde(default)]
+    pub c: LanguageConfig,
+    /// Settings for Java (`.java`) files.
+    #[serde(default)]
+    pub java: LanguageConfig,
+    /// Settings for Markdown files; `comments` covers HTML comments.
+    #[serde(default)]
+    pub markdown: LanguageConfig,
+    /// Settings for HTML files.
+    #[serde(default)]
+    pub html: MarkupConfig,
+    /// Settings for XML and SVG files.
+    #[serde(default)]
+    pub xml: MarkupConfig,
+    /// Settings for reStructuredText files; `comments` covers `..` comments.
+    #[serde(default)]
+    pub rst: LanguageConfig,
+    /// Settings for AsciiDoc files; `comments` covers `//` and `////` comments.
+    #[serde(default)]
+    pub asciidoc: LanguageConfig,
+    /// Settings for LaTeX files; `comments` covers `%` comments.
+    #[serde(default)]
+    pub latex: LanguageConfig,
+    /// Settings for Jupyter notebooks, covering both Markdown and code cells.
+    #[serde(default)]
+    pub notebook: LanguageConfig,
+    /// Settings for JSON files.
+    #[serde(default)]
+    pub json: DataConfig,
+    /// Settings for YAML files.
+    #[serde(default)]
+    pub yaml: DataConfig,
+    /// Settings for TOML files.
+    #[serde(default)]
+    pub toml: DataConfig,
+}
+
+impl LanguagesConfig {
+    /// Returns the settings for the given language.
+    pub fn get(&self, language: Language) -> &LanguageConfig {
+        match language {
+            Language::Rust => &self.rust,
+            Language::Python => &self.python,
+            Language::JavaScript => &self.javascript,
+            Language::Go => &self.go,
+            Language::C => &self.c,
+            Language::Java => &self.java,
+            Language::Markdown => &self.markdown,
+            Language::Html => &self.html.regions,
+            Language::Xml => &self.xml.regions,
+            Language::Rst => &self.rst,
+            Language::AsciiDoc => &self.asciidoc,
+            Language::Latex => &self.latex,
+            Language::Notebook => &self.notebook,
+            Language::Json => &self.json.regions,
+            Language::Yaml => &self.yaml.regions,
+            Language::Toml => &self.toml.regions,
+        }
+    }
+}
+
+/// Controls which regions of a source file are spell-checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageConfig {
+    /// Check regular line and block comments.
+    #[serde(default = "default_true")]
+    pub comments: bool,
+    /// Check doc comments and docstrings.
+    #[serde(default = "default_true")]
+    pub doc_comments: bool,
+    /// Check the contents of string literals.
+    #[serde(default = "default_true")]
+    pub strings: bool,
+}
+
+/// Settings for HTML and XML documents, where `strings` covers the values of
+/// `attributes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkupConfig {
+    #[serde(flatten)]
+    pub regions: LanguageConfig,
+    /// Attributes whose values are checked.
+    #[serde(default = "default_attributes")]
+    pub attributes: Vec<String>,
+}
+
+fn default_attributes() -> Vec<String> {
+    html::DEFAULT_ATTRIBUTES.iter().map(|a| a.to_string()).collect()
+}
+
+impl Default for MarkupConfig {
+    fn default() -> Self {
+        Self {
+            regions: LanguageConfig::default(),
+            attributes: default_attributes(),
+        }
+    }
+}
+
+/// Settings for JSON, YAML and TOML files, where `strings` covers string
+/// values and `comments` YAML and TOML comments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataConfig {
+    #[serde(flatten)]
+    pub regions: LanguageConfig,
+    /// Key path patterns whose values are checked, such as `**.description`.
+    /// Empty checks every string value.
+    #[serde(default)]
+    pub include_keys: Vec<String>,
+    /// Key path patterns whose values are not checked.
+    #[serde(default)]
+    pub exclude_keys: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl LanguageConfig {
+    /// Returns `true` if regions of the given kind should be checked.
+    pub fn checks(&self, kind: RegionKind) -> bool {
+        match kind {
+            RegionKind::Comment => self.comments,
+            RegionKind::DocComment => self.doc_comments,
+            RegionKind::String => self.strings,
+            RegionKind::Text => true,
+        }
+    }
+}
+
+impl Default for LanguageConfig {
+    fn default() -> Self {
+        Self {
+            comments: true,
+            doc_comments: true,
+            strings: true,
+        }
+    }
 }
@@ -0,0 +1,292 @@
+//! JSON string values.
+//!
+//! A small JSON scanner that keeps the byte offsets of string literals, so
+//! values can be checked in place. Notebooks are parsed with it too.
+
+use super::KeyFilter;
+use crate::lexer::{Region, RegionKind};
+use std::ops::Range;
+
+/// Nesting deeper than this is not parsed further.
+const MAX_DEPTH: usize = 64;
+
+/// Extracts the string values of a JSON document whose key paths `filter`
+/// allows. Keys are never checked.
+///
+/// Returns no regions if `content` is not valid JSON.
+pub fn extract_regions(content: &str, filter: &KeyFilter) -> Vec<Region> {
+    let mut regions = Vec::new();
+    if let Some(root) = parse(content) {
+        visit(&root, &mut Vec::new(), filter, &mut regions);
+    }
+    regions
+}
+
+fn visit(value: &Value, path: &mut Vec<String>, filter: &KeyFilter, regions: &mut Vec<Region>) {
+    match value {
+        Value::String(raw) if !raw.is_empty() && filter.allows(path) => {
+            regions.push(Region { kind: RegionKind::String, range: raw.clone() });
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push(i.to_string());
+                visit(item, path, filter, regions);
+                path.pop();
+            }
+        }
+        Value::Object(members) => {
+            for (key, member) in members {
+                path.push(key.clone());
+                visit(member, path, filter, regions);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parses a JSON document, or returns `None` if it is not valid JSON.
+pub(crate) fn parse(content: &str) -> Option<Value> {
+    let mut parser = Parser { content, src: content.as_bytes(), pos: 0 };
+    let root = parser.value(0)?;
+    parser.skip_whitespace();
+    (parser.pos == content.len()).then_some(root)
+}
+
+/// Finds the escape sequences in the checked text of a JSON document,
+/// returning each with its decoded text.
+///
+/// Only string contents are left in checked text, so every backslash starts
+/// an escape sequence. Control characters decode to a space.
+pub fn escapes(text: &str) -> Vec<(Range<usize>, String)> {
+    let bytes = text.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            i += 1;
+            continue;
+        }
+        let (c, len) = escape(text, i);
+        let decoded = if c.is_control() { " ".to_string() } else { c.to_string() };
+        found.push((i..i + len, decoded));
+        i += len;
+    }
+    found
+}
+
+/// Returns the decoded contents of a string literal, without its quotes.
+fn unescape(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len());
+    let mut i = 0;
+    while let Some(offset) = raw[i..].find('\\') {
+        text.push_str(&raw[i..i + offset]);
+        let (c, len) = escape(raw, i + offset);
+        text.push(c);
+        i += offset + len;
+    }
+    text.push_str(&raw[i.min(raw.len())..]);
+    text
+}
+
+/// A JSON value, with strings kept as ranges into the JSON text.
+pub(crate) enum Value {
+    /// The range of a string literal between its quotes.
+    String(Range<usize>),
+    Array(Vec<Value>),
+    /// Members with their decoded keys.
+    Object(Vec<(String, Value)>),
+    /// A number, boolean or null.
+    Scalar,
+}
+
+impl Value {
+    /// Returns the member of an object with the given key.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        let Value::Object(members) = self else { return None };
+        members.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Returns the decoded contents of a string.
+    pub(crate) fn as_string(&self, content: &str) -> Option<String> {
+        let Value::String(raw) = self else { return None };
+        Some(unescape(&content[raw.clone()]))
+    }
+}
+
+/// A minimal JSON parser that keeps the byte offsets of strings.
+struct Parser<'a> {
+    content: &'a str,
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.src.get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match *self.src.get(self.pos)? {
+            b'"' => self.string().map(Value::String),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.src.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Some(Value::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.src.get(self.pos)? {
+                        b',' => self.pos += 1,
+                        b']' => {
+                            self.pos += 1;
+                            return Some(Value::Array(items));
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.src.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Some(Value::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.src.get(self.pos) != Some(&b'"') {
+                        return None;
+                    }
+                    let key = unescape(&self.content[self.string()?]);
+                    self.skip_whitespace();
+                    if self.src.get(self.pos) != Some(&b':') {
+                        return None;
+                    }
+                    self.pos += 1;
+                    members.push((key, self.value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.src.get(self.pos)? {
+                        b',' => self.pos += 1,
+                        b'}' => {
+                            self.pos += 1;
+                            return Some(Value::Object(members));
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            b'-' | b'0'..=b'9' | b't' | b'f' | b'n' => {
+                let len = self.src[self.pos..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.'))
+                    .count();
+                self.pos += len;
+                Some(Value::Scalar)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses a string literal, returning the range between its quotes.
+    fn string(&mut self) -> Option<Range<usize>> {
+        let start = self.pos + 1;
+        let mut i = start;
+        loop {
+            match *self.src.get(i)? {
+                b'"' => break,
+                b'\\' => i += 2,
+                _ => i += 1,
+            }
+        }
+        self.pos = i + 1;
+        Some(start..i)
+    }
+}
+
+/// Decodes the escape sequence at `start`, returning the character and the
+/// length of the sequence.
+pub(crate) fn escape(text: &str, start: usize) -> (char, usize) {
+    let bytes = text.as_bytes();
+    let Some(&kind) = bytes.get(start + 1) else { return ('\\', 1) };
+    let c = match kind {
+        b'n' => '\n',
+        b't' => '\t',
+        b'r' => '\r',
+        b'b' => '\u{8}',
+        b'f' => '\u{c}',
+        b'u' => {
+            let unit = |at: usize| text.get(at..at + 4).and_then(|hex| u32::from_str_radix(hex, 16).ok());
+            let Some(high) = unit(start + 2) else { return ('\u{fffd}', 2) };
+            if (0xd800..0xdc00).contains(&high)
+                && text.get(start + 6..start + 8) == Some("\\u")
+                && let Some(low) = unit(start + 8).filter(|low| (0xdc00..0xe000).contains(low))
+            {
+                let c = char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00));
+                return (c.unwrap_or('\u{fffd}'), 12);
+            }
+            return (char::from_u32(high).unwrap_or('\u{fffd}'), 6);
+        }
+        _ => {
+            // `\"`, `\\` and `\/`; anything else is invalid and kept as is.
+            let c = text[start + 1..].chars().next().unwrap_or('\\');
+            return (c, 1 + c.len_utf8());
+        }
+    };
+    (c, 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_schema::DataConfig;
+
+    fn values<'a>(content: &'a str, include_keys: &[&str]) -> Vec<&'a str> {
+        let filter = KeyFilter::new(&DataConfig {
+            include_keys: include_keys.iter().map(|k| k.to_string()).collect(),
+            ..DataConfig::default()
+        });
+        extract_regions(content, &filter).into_iter().map(|r| &content[r.range]).collect()
+    }
+
+    #[test]
+    fn test_values() {
+        let content = r#"{
+  "name": "spell-chekcer",
+  "version": "1.0.0",
+  "private": true,
+  "description": "Finds \"tpyos\" in caf\u00e9 menus",
+  "keywords": ["speling", ""],
+  "scripts": {"build": "tsc -p .", "description": "nested"}
+}
+"#;
+        assert_eq!(
+            values(content, &[]),
+            vec!["spell-chekcer", "1.0.0", "Finds \\\"tpyos\\\" in caf\\u00e9 menus", "speling", "tsc -p .", "nested"]
+        );
+        assert_eq!(values(content, &["**.description"]), vec!["Finds \\\"tpyos\\\" in caf\\u00e9 menus", "nested"]);
+        assert_eq!(values(content, &["keywords.*"]), vec!["speling"]);
+        assert!(values("{\"a\": ", &[]).is_empty());
+        assert!(values("{} trailing", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_escapes() {
+        let found = escapes("caf\\u00e9 \\ud83d\\ude00 \\\"a\\\" line\\nbreak");
+        let decoded: Vec<&str> = found.iter().map(|(_, d)| d.as_str()).collect();
+        assert_eq!(decoded, vec!["é", "😀", "\"", "\"", " "]);
+        assert_eq!(found[1].0, 10..22);
+        assert_eq!(unescape("a\\tb\\u0041"), "a\tbA");
+    }
+}
@@ -0,0 +1,110 @@
+//! Extraction of string values from structured data files.
+//!
+//! Config and translation files mix user-facing text with keys, versions,
+//! paths and identifiers. The extractors in this module visit the string
+//! values of JSON, YAML and TOML documents and return them as
+//! `RegionKind::String`, leaving keys and other scalars out. Each value has a
+//! key path, its keys and array indices joined with `.` (`package.description`,
+//! `keywords.0`), which `KeyFilter` matches against `include_keys` and
+//! `exclude_keys`.
+
+pub mod json;
+pub mod toml;
+pub mod yaml;
+
+use crate::config_schema::DataConfig;
+
+/// Decides which key paths have their values checked.
+#[derive(Debug, Default)]
+pub struct KeyFilter {
+    include: Vec<Vec<String>>,
+    exclude: Vec<Vec<String>>,
+}
+
+impl KeyFilter {
+    /// Creates a filter from the `include_keys` and `exclude_keys` patterns
+    /// of a data language.
+    ///
+    /// In a pattern, `*` matches any part of a key and `**` any number of
+    /// keys, so `**.description` matches `description` at every level.
+    pub fn new(config: &DataConfig) -> Self {
+        let split = |patterns: &[String]| -> Vec<Vec<String>> {
+            patterns.iter().map(|p| p.split('.').map(str::to_string).collect()).collect()
+        };
+        Self { include: split(&config.include_keys), exclude: split(&config.exclude_keys) }
+    }
+
+    /// Returns `true` if the value at `path` is checked: it matches an
+    /// include pattern, or there are none, and no exclude pattern.
+    pub fn allows(&self, path: &[String]) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| matches(p, path)))
+            && !self.exclude.iter().any(|p| matches(p, path))
+    }
+}
+
+/// Matches a key path against a split pattern.
+fn matches(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| matches(rest, &path[skip..])),
+        Some((first, rest)) => {
+            path.split_first().is_some_and(|(key, path)| wildcard(first, key) && matches(rest, path))
+        }
+    }
+}
+
+/// Matches a single key against a pattern in which `*` matches any text.
+fn wildcard(pattern: &str, key: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else { return pattern == key };
+    let Some(key) = key.strip_prefix(prefix) else { return false };
+    (0..=key.len()).filter(|&i| key.is_char_boundary(i)).any(|i| wildcard(rest, &key[i..]))
+}
+
+/// Returns `true` if an unquoted scalar is a number, boolean, null, date or
+/// version rather than text.
+pub(crate) fn is_plain_value(scalar: &str) -> bool {
+    let lower = scalar.to_ascii_lowercase();
+    matches!(lower.as_str(), "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~" | ".nan" | ".inf" | "-.inf")
+        || lower.parse::<f64>().is_ok()
+        || ["0x", "0o", "0b"].iter().any(|prefix| {
+            lower.strip_prefix(prefix).is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_hexdigit() || c == '_'))
+        })
+        || lower.starts_with(|c: char| c.is_ascii_digit()) && lower.chars().all(|c| c.is_ascii_digit() || "-:.tz+_ ".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_key_filter() {
+        let filter = KeyFilter::new(&DataConfig {
+            include_keys: vec!["**.description".into(), "messages.*".into(), "package.key*".into()],
+            exclude_keys: vec!["messages.internal_*".into()],
+            ..DataConfig::default()
+        });
+        assert!(filter.allows(&path("description")));
+        assert!(filter.allows(&path("package.description")));
+        assert!(filter.allows(&path("a.b.c.description")));
+        assert!(filter.allows(&path("messages.greeting")));
+        assert!(filter.allows(&path("package.keywords")));
+        assert!(!filter.allows(&path("messages.internal_error")));
+        assert!(!filter.allows(&path("messages.greeting.long")));
+        assert!(!filter.allows(&path("package.name")));
+        assert!(KeyFilter::default().allows(&path("anything.at.all")));
+    }
+
+    #[test]
+    fn test_plain_values() {
+        for value in ["true", "Null", "~", "42", "-3.5e10", "0x1F", "1979-05-27T07:32:00Z", "1.2.3"] {
+            assert!(is_plain_value(value), "{value}");
+        }
+        for value in ["hello", "yesterday", "-not-a-number", "3 apples"] {
+            assert!(!is_plain_value(value), "{value}");
+        }
+    }
+}
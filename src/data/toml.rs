@@ -0,0 +1,320 @@
+//! TOML string values.
+//!
+//! Reads tables, arrays of tables, dotted keys, arrays and inline tables to
+//! find the key path of every string. Keys, numbers, booleans and dates are
+//! left out; comments are returned as comments.
+
+use super::KeyFilter;
+use crate::lexer::{Region, RegionKind};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Extracts the string values of a TOML document whose key paths `filter`
+/// allows, and its comments. Keys are never checked.
+pub fn extract_regions(content: &str, filter: &KeyFilter) -> Vec<Region> {
+    let mut scanner = Scanner { content, src: content.as_bytes(), pos: 0, filter, arrays: HashMap::new(), regions: Vec::new() };
+    scanner.run();
+    scanner.regions
+}
+
+struct Scanner<'a> {
+    content: &'a str,
+    src: &'a [u8],
+    pos: usize,
+    filter: &'a KeyFilter,
+    /// The number of elements of each array of tables so far, by its keys.
+    arrays: HashMap<Vec<String>, usize>,
+    regions: Vec<Region>,
+}
+
+impl Scanner<'_> {
+    fn run(&mut self) {
+        let mut table = Vec::new();
+        loop {
+            self.skip_blank();
+            match self.src.get(self.pos) {
+                None => break,
+                Some(b'[') => table = self.header(),
+                Some(_) => {
+                    let mut path = table.clone();
+                    if self.keys(&mut path) && self.src.get(self.pos) == Some(&b'=') {
+                        self.pos += 1;
+                        self.value(&mut path);
+                    } else {
+                        self.skip_line();
+                    }
+                }
+            }
+        }
+    }
+
+    fn peek_is(&self, pattern: &[u8]) -> bool {
+        self.src[self.pos..].starts_with(pattern)
+    }
+
+    fn push(&mut self, kind: RegionKind, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region { kind, range });
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.src.get(self.pos), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_line(&mut self) {
+        self.pos = self.content[self.pos..].find('\n').map_or(self.src.len(), |i| self.pos + i + 1);
+    }
+
+    /// Skips whitespace, line breaks and comments.
+    fn skip_blank(&mut self) {
+        while let Some(&b) = self.src.get(self.pos) {
+            match b {
+                b' ' | b'\t' | b'\r' | b'\n' => self.pos += 1,
+                b'#' => {
+                    let end = self.content[self.pos..].find('\n').map_or(self.src.len(), |i| self.pos + i);
+                    self.push(RegionKind::Comment, self.pos..end);
+                    self.pos = end;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Reads a `[table]` or `[[array]]` header, returning the path of its keys.
+    fn header(&mut self) -> Vec<String> {
+        let array = self.peek_is(b"[[");
+        self.pos += if array { 2 } else { 1 };
+        let mut keys = Vec::new();
+        self.keys(&mut keys);
+        if array {
+            *self.arrays.entry(keys.clone()).or_insert(0) += 1;
+        }
+        self.skip_line_rest();
+        // Tables inside an array of tables belong to its last element.
+        let mut path = Vec::new();
+        for i in 0..keys.len() {
+            path.push(keys[i].clone());
+            if let Some(count) = self.arrays.get(&keys[..=i]) {
+                path.push((count - 1).to_string());
+            }
+        }
+        path
+    }
+
+    /// Skips to the end of the line, keeping a comment.
+    fn skip_line_rest(&mut self) {
+        let end = self.content[self.pos..].find('\n').map_or(self.src.len(), |i| self.pos + i);
+        if let Some(hash) = self.content[self.pos..end].find('#') {
+            self.push(RegionKind::Comment, self.pos + hash..end);
+        }
+        self.pos = end;
+    }
+
+    /// Reads a dotted key such as `a."b c".d` onto `path`, leaving the
+    /// position at the next non-space character. Returns `false` if there is
+    /// no key.
+    fn keys(&mut self, path: &mut Vec<String>) -> bool {
+        loop {
+            self.skip_spaces();
+            let key = match self.src.get(self.pos) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let start = self.pos + 1;
+                    let end = self.content[start..].find([quote as char, '\n']).map_or(self.src.len(), |i| start + i);
+                    self.pos = (end + 1).min(self.src.len());
+                    self.content[start..end].to_string()
+                }
+                _ => {
+                    let len = self.src[self.pos..]
+                        .iter()
+                        .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+                        .count();
+                    if len == 0 {
+                        return false;
+                    }
+                    self.pos += len;
+                    self.content[self.pos - len..self.pos].to_string()
+                }
+            };
+            path.push(key);
+            self.skip_spaces();
+            if self.src.get(self.pos) != Some(&b'.') {
+                return true;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Reads the value at the current position.
+    fn value(&mut self, path: &mut Vec<String>) {
+        self.skip_spaces();
+        let allowed = self.filter.allows(path);
+        match self.src.get(self.pos) {
+            None => {}
+            Some(b'"') => {
+                let multiline = self.peek_is(b"\"\"\"");
+                self.pos += if multiline { 3 } else { 1 };
+                self.basic_string(multiline, allowed);
+            }
+            Some(b'\'') => {
+                let multiline = self.peek_is(b"'''");
+                self.pos += if multiline { 3 } else { 1 };
+                let start = self.pos;
+                let close = if multiline { "'''" } else { "'" };
+                let end = if multiline { self.content[start..].find(close) } else { self.content[start..].find(['\'', '\n']) };
+                let end = end.map_or(self.src.len(), |i| start + i);
+                // A closing delimiter may be preceded by up to two quotes of content.
+                let extra = match self.src.get(end + 3..) {
+                    Some(after) if multiline => after.iter().take(2).take_while(|&&b| b == b'\'').count(),
+                    _ => 0,
+                };
+                if allowed {
+                    self.push(RegionKind::String, start..end + extra);
+                }
+                self.pos = (end + extra + close.len()).min(self.src.len());
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut index = 0;
+                loop {
+                    self.skip_blank();
+                    match self.src.get(self.pos) {
+                        None => return,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return;
+                        }
+                        Some(b',') => self.pos += 1,
+                        Some(_) => {
+                            path.push(index.to_string());
+                            let before = self.pos;
+                            self.value(path);
+                            path.pop();
+                            index += 1;
+                            if self.pos == before {
+                                self.pos += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                loop {
+                    self.skip_blank();
+                    match self.src.get(self.pos) {
+                        None => return,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return;
+                        }
+                        Some(b',') => self.pos += 1,
+                        Some(_) => {
+                            let mut member = path.clone();
+                            if !self.keys(&mut member) || self.src.get(self.pos) != Some(&b'=') {
+                                self.pos += 1;
+                                continue;
+                            }
+                            self.pos += 1;
+                            self.value(&mut member);
+                        }
+                    }
+                }
+            }
+            // Numbers, booleans and dates.
+            Some(_) => {
+                while self.src.get(self.pos).is_some_and(|b| !matches!(b, b',' | b']' | b'}' | b'#' | b'\n' | b'\r')) {
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Reads a basic string whose content starts at the current position,
+    /// splitting it around escape sequences.
+    fn basic_string(&mut self, multiline: bool, allowed: bool) {
+        let mut text_start = self.pos;
+        while let Some(&b) = self.src.get(self.pos) {
+            let closes = if multiline { self.peek_is(b"\"\"\"") } else { b == b'"' || b == b'\n' };
+            if closes {
+                let extra = if multiline { self.src[self.pos + 3..].iter().take(2).take_while(|&&b| b == b'"').count() } else { 0 };
+                if allowed {
+                    self.push(RegionKind::String, text_start..self.pos + extra);
+                }
+                self.pos += extra + if multiline { 3 } else { 1 };
+                return;
+            }
+            if b == b'\\' {
+                if allowed {
+                    self.push(RegionKind::String, text_start..self.pos);
+                }
+                let len = match self.src.get(self.pos + 1) {
+                    Some(b'u') => 6,
+                    Some(b'U') => 10,
+                    _ => 2,
+                };
+                self.pos = (self.pos + len).min(self.src.len());
+                text_start = self.pos;
+            } else {
+                self.pos += 1;
+            }
+        }
+        if allowed {
+            self.push(RegionKind::String, text_start..self.pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_schema::DataConfig;
+
+    fn strings<'a>(content: &'a str, include_keys: &[&str]) -> Vec<&'a str> {
+        let filter = KeyFilter::new(&DataConfig {
+            include_keys: include_keys.iter().map(|k| k.to_string()).collect(),
+            ..DataConfig::default()
+        });
+        extract_regions(content, &filter)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::String)
+            .map(|r| &content[r.range])
+            .collect()
+    }
+
+    #[test]
+    fn test_values() {
+        let content = "# Cargo manifst\n[package]\nname = \"spell_chekc\"\nversion = \"0.1.0\"  # bumped\nedition = 2024\n\
+            description = \"Finds \\\"tpyos\\\"\\tfast\"\nkeywords = [\"speling\", 'literl',\n  \"checkr\", # trailing\n]\n\
+            [dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n\n[[bin]]\nname = \"frist\"\n\n[[bin]]\nname = \"secnd\"\n\
+            doc.summary = '''\nMulti-line literl\n'''\n[bin.metadata]\nnote = \"\"\"\nMulti-line basc \\\n  text\"\"\"\n";
+        assert_eq!(
+            strings(content, &[]),
+            vec![
+                "spell_chekc",
+                "0.1.0",
+                "Finds ",
+                "tpyos",
+                "fast",
+                "speling",
+                "literl",
+                "checkr",
+                "1",
+                "derive",
+                "frist",
+                "secnd",
+                "\nMulti-line literl\n",
+                "\nMulti-line basc ",
+                "  text"
+            ]
+        );
+        assert_eq!(strings(content, &["**.description", "package.keywords.2"]), vec!["Finds ", "tpyos", "fast", "checkr"]);
+        assert_eq!(
+            strings(content, &["bin.1.*", "bin.*.doc.summary", "dependencies.serde.features.*"]),
+            vec!["derive", "secnd", "\nMulti-line literl\n"]
+        );
+        assert_eq!(strings(content, &["bin.1.metadata.note"]), vec!["\nMulti-line basc ", "  text"]);
+    }
+}
@@ -0,0 +1,523 @@
+//! YAML string values.
+//!
+//! A line-based reader for the YAML used in config and translation files:
+//! block mappings and sequences by indentation, plain, quoted and block
+//! scalars, flow collections, anchors, tags and comments. Keys, numbers,
+//! booleans and nulls are left out, as are aliases and directives.
+
+use super::{KeyFilter, is_plain_value};
+use crate::lexer::{self, Region, RegionKind};
+use std::ops::Range;
+
+/// Extracts the string values of a YAML document whose key paths `filter`
+/// allows, and its comments. Keys are never checked.
+pub fn extract_regions(content: &str, filter: &KeyFilter) -> Vec<Region> {
+    let mut reader = Reader {
+        content,
+        src: content.as_bytes(),
+        lines: lexer::lines(content),
+        line: 0,
+        filter,
+        stack: Vec::new(),
+        regions: Vec::new(),
+    };
+    reader.run();
+    reader.regions
+}
+
+/// A mapping key or sequence item whose value is being read.
+struct Frame {
+    /// The column of the key or of the item's `-`.
+    indent: usize,
+    /// The key, or the index of the item.
+    key: String,
+    /// For sequence items, the next item's index is one more.
+    item: bool,
+    /// Whether the value continues on the following lines.
+    open: bool,
+}
+
+struct Reader<'a> {
+    content: &'a str,
+    src: &'a [u8],
+    lines: Vec<Range<usize>>,
+    /// The current line.
+    line: usize,
+    filter: &'a KeyFilter,
+    stack: Vec<Frame>,
+    regions: Vec<Region>,
+}
+
+/// Returns the offset of a comment in `text`: a `#` at its start or after
+/// whitespace.
+fn comment_start(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    (0..bytes.len()).find(|&i| bytes[i] == b'#' && (i == 0 || bytes[i - 1] == b' ' || bytes[i - 1] == b'\t'))
+}
+
+/// Returns `true` if `text` starts a block sequence item.
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ") || text.starts_with("-\t")
+}
+
+impl Reader<'_> {
+    fn run(&mut self) {
+        while self.line < self.lines.len() {
+            let range = self.lines[self.line].clone();
+            let text = &self.content[range.clone()];
+            let trimmed = text.trim_start_matches(' ');
+            let indent = text.len() - trimmed.len();
+            let code = trimmed.trim_end();
+            if trimmed.starts_with('#') {
+                self.push(RegionKind::Comment, range.start + indent..range.end);
+            } else if indent == 0 && (code == "---" || code == "..." || code.starts_with("--- ") || code.starts_with('%')) {
+                // A new document or a directive.
+                self.stack.clear();
+            } else if !code.is_empty() {
+                let item = is_item(trimmed);
+                while let Some(top) = self.stack.last() {
+                    let keep = top.indent < indent || (top.indent == indent && item && (top.item || top.open));
+                    if keep {
+                        break;
+                    }
+                    self.stack.pop();
+                }
+                self.node(range.start + indent, indent);
+            }
+            self.line += 1;
+        }
+    }
+
+    fn line_end(&self) -> usize {
+        self.lines[self.line].end
+    }
+
+    fn push(&mut self, kind: RegionKind, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region { kind, range });
+        }
+    }
+
+    fn path(&self) -> Vec<String> {
+        self.stack.iter().map(|f| f.key.clone()).collect()
+    }
+
+    /// Records the comment in `from..line end`, if any.
+    fn trailing_comment(&mut self, from: usize) {
+        let end = self.line_end();
+        if from < end
+            && let Some(start) = comment_start(&self.content[from..end])
+        {
+            self.push(RegionKind::Comment, from + start..end);
+        }
+    }
+
+    /// Reads the node starting at `at`, in column `col` of the current line.
+    fn node(&mut self, at: usize, col: usize) {
+        let rest = &self.content[at..self.line_end()];
+        if is_item(rest) {
+            let index = match self.stack.last() {
+                Some(top) if top.item && top.indent == col => {
+                    let next = top.key.parse::<usize>().map_or(0, |i| i + 1);
+                    self.stack.pop();
+                    next
+                }
+                _ => 0,
+            };
+            self.stack.push(Frame { indent: col, key: index.to_string(), item: true, open: true });
+            let spaces = rest[1..].len() - rest[1..].trim_start().len();
+            if rest[1 + spaces..].is_empty() || rest[1 + spaces..].starts_with('#') {
+                self.trailing_comment(at + 1);
+            } else {
+                self.node(at + 1 + spaces, col + 1 + spaces);
+            }
+        } else if rest.starts_with("? ") || rest == "?" {
+            // Complex keys are not supported.
+        } else if let Some((key, value)) = mapping_key(rest) {
+            let open = value.is_none();
+            self.stack.push(Frame { indent: col, key, item: false, open });
+            match value {
+                Some(offset) => self.value(at + offset),
+                None => self.trailing_comment(at),
+            }
+        } else {
+            self.value(at);
+        }
+    }
+
+    /// Reads the value starting at `at` on the current line.
+    fn value(&mut self, mut at: usize) {
+        let end = self.line_end();
+        // Anchors and tags come before the value they apply to.
+        while matches!(self.src.get(at), Some(b'&' | b'!')) && at < end {
+            let token = self.content[at..end].find([' ', '\t']).unwrap_or(end - at);
+            let rest = &self.content[at + token..end];
+            at = end - rest.trim_start().len();
+        }
+        let rest = &self.content[at..end];
+        if rest.is_empty() || rest.starts_with('#') {
+            if let Some(top) = self.stack.last_mut() {
+                top.open = true;
+            }
+            self.trailing_comment(at);
+            return;
+        }
+        let path = self.path();
+        let allowed = self.filter.allows(&path);
+        match rest.as_bytes()[0] {
+            b'*' => self.trailing_comment(at),
+            b'|' | b'>' => {
+                self.trailing_comment(at);
+                self.block_scalar(allowed);
+            }
+            b'"' => {
+                let close = self.double_quoted(at + 1, allowed);
+                self.finish_at(close);
+            }
+            b'\'' => {
+                let close = self.single_quoted(at + 1, allowed);
+                self.finish_at(close);
+            }
+            b'[' | b'{' => {
+                let close = self.flow(at, &mut path.clone());
+                self.finish_at(close);
+            }
+            _ => self.plain(at, allowed),
+        }
+    }
+
+    /// Moves to the line containing `pos`, which ends a value, and records a
+    /// comment after it.
+    fn finish_at(&mut self, pos: usize) {
+        self.line = self.lines.partition_point(|l| l.start <= pos).saturating_sub(1).max(self.line);
+        self.trailing_comment(pos.min(self.line_end()));
+    }
+
+    /// The indentation that continuation lines must exceed, or `None` at the top level.
+    fn parent_indent(&self) -> Option<usize> {
+        self.stack.last().map(|f| f.indent)
+    }
+
+    fn indentation(&self, line: usize) -> usize {
+        let text = &self.content[self.lines[line].clone()];
+        text.len() - text.trim_start_matches(' ').len()
+    }
+
+    fn is_blank(&self, line: usize) -> bool {
+        self.content[self.lines[line].clone()].trim().is_empty()
+    }
+
+    fn plain(&mut self, at: usize, allowed: bool) {
+        let end = self.line_end();
+        let text = &self.content[at..end];
+        let code = text[..comment_start(text).unwrap_or(text.len())].trim_end();
+        let check = allowed && !is_plain_value(code);
+        if check {
+            self.push(RegionKind::String, at..at + code.len());
+        }
+        self.trailing_comment(at + code.len());
+        // A plain scalar continues on more indented lines.
+        let parent = self.parent_indent();
+        while self.line + 1 < self.lines.len()
+            && !self.is_blank(self.line + 1)
+            && parent.is_none_or(|p| self.indentation(self.line + 1) > p)
+        {
+            self.line += 1;
+            let range = self.lines[self.line].clone();
+            let text = &self.content[range.clone()];
+            let start = range.start + self.indentation(self.line);
+            if text.trim_start().starts_with('#') {
+                self.push(RegionKind::Comment, start..range.end);
+                break;
+            }
+            let code = text[..comment_start(text).unwrap_or(text.len())].trim_end();
+            if check {
+                self.push(RegionKind::String, start..range.start + code.len());
+            }
+            self.trailing_comment(range.start + code.len());
+        }
+    }
+
+    /// Reads the content lines of a `|` or `>` block scalar.
+    fn block_scalar(&mut self, allowed: bool) {
+        let parent = self.parent_indent();
+        let mut last = None;
+        let mut next = self.line + 1;
+        while next < self.lines.len()
+            && (self.is_blank(next) || parent.is_none_or(|p| self.indentation(next) > p))
+        {
+            if !self.is_blank(next) {
+                if allowed {
+                    let range = self.lines[next].clone();
+                    self.push(RegionKind::String, range.start + self.indentation(next)..range.end);
+                }
+                last = Some(next);
+            }
+            next += 1;
+        }
+        if let Some(last) = last {
+            self.line = last;
+        }
+    }
+
+    /// Reads a double-quoted scalar whose content starts at `start`, returning
+    /// the position after its closing quote. The content is split around
+    /// escape sequences.
+    fn double_quoted(&mut self, start: usize, allowed: bool) -> usize {
+        let mut text_start = start;
+        let mut i = start;
+        while i < self.src.len() {
+            match self.src[i] {
+                b'"' => {
+                    if allowed {
+                        self.push(RegionKind::String, text_start..i);
+                    }
+                    return i + 1;
+                }
+                b'\\' => {
+                    if allowed {
+                        self.push(RegionKind::String, text_start..i);
+                    }
+                    let len = match self.src.get(i + 1) {
+                        Some(b'x') => 4,
+                        Some(b'u') => 6,
+                        Some(b'U') => 10,
+                        _ => 2,
+                    };
+                    i = (i + len).min(self.src.len());
+                    text_start = i;
+                }
+                _ => i += 1,
+            }
+        }
+        if allowed {
+            self.push(RegionKind::String, text_start..i);
+        }
+        i
+    }
+
+    /// Reads a single-quoted scalar whose content starts at `start`, returning
+    /// the position after its closing quote. The content is split at `''`.
+    fn single_quoted(&mut self, start: usize, allowed: bool) -> usize {
+        let mut text_start = start;
+        let mut i = start;
+        while i < self.src.len() {
+            if self.src[i] == b'\'' {
+                if self.src.get(i + 1) != Some(&b'\'') {
+                    break;
+                }
+                if allowed {
+                    self.push(RegionKind::String, text_start..i + 1);
+                }
+                i += 2;
+                text_start = i;
+            } else {
+                i += 1;
+            }
+        }
+        if allowed {
+            self.push(RegionKind::String, text_start..i);
+        }
+        (i + 1).min(self.src.len())
+    }
+
+    /// Skips whitespace, line breaks and comments inside a flow collection.
+    fn skip_flow_space(&mut self, mut pos: usize) -> usize {
+        while pos < self.src.len() {
+            match self.src[pos] {
+                b' ' | b'\t' | b'\r' | b'\n' => pos += 1,
+                b'#' => {
+                    let end = self.content[pos..].find('\n').map_or(self.src.len(), |i| pos + i);
+                    self.push(RegionKind::Comment, pos..end);
+                    pos = end;
+                }
+                _ => break,
+            }
+        }
+        pos
+    }
+
+    /// Reads a flow sequence or mapping at `pos`, returning the position
+    /// after its closing bracket.
+    fn flow(&mut self, mut pos: usize, path: &mut Vec<String>) -> usize {
+        let mapping = self.src[pos] == b'{';
+        let close = if mapping { b'}' } else { b']' };
+        pos += 1;
+        let mut index = 0;
+        loop {
+            pos = self.skip_flow_space(pos);
+            match self.src.get(pos) {
+                None => return pos,
+                Some(&b) if b == close => return pos + 1,
+                Some(b',') => {
+                    pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            if mapping {
+                let (key, end) = self.flow_key(pos);
+                pos = self.skip_flow_space(end);
+                if self.src.get(pos) == Some(&b':') {
+                    path.push(key);
+                    pos = self.flow_value(pos + 1, path);
+                    path.pop();
+                }
+            } else {
+                path.push(index.to_string());
+                pos = self.flow_value(pos, path);
+                path.pop();
+                index += 1;
+            }
+        }
+    }
+
+    /// Reads a key in a flow mapping, returning it and the position after it.
+    fn flow_key(&self, pos: usize) -> (String, usize) {
+        let quote = self.src[pos];
+        if quote == b'"' || quote == b'\'' {
+            let end = self.content[pos + 1..].find(quote as char).map_or(self.src.len(), |i| pos + 1 + i);
+            return (self.content[pos + 1..end].to_string(), (end + 1).min(self.src.len()));
+        }
+        let len = self.content[pos..].find([':', ',', '}', '\n']).unwrap_or(self.src.len() - pos).max(1);
+        (self.content[pos..pos + len].trim().to_string(), pos + len)
+    }
+
+    /// Reads a value in a flow collection, returning the position after it.
+    fn flow_value(&mut self, pos: usize, path: &mut Vec<String>) -> usize {
+        let mut pos = self.skip_flow_space(pos);
+        while matches!(self.src.get(pos), Some(b'&' | b'!')) {
+            pos += self.content[pos..].find([' ', ',', ']', '}', '\n']).unwrap_or(self.src.len() - pos);
+            pos = self.skip_flow_space(pos);
+        }
+        let allowed = self.filter.allows(path);
+        match self.src.get(pos) {
+            None | Some(b',' | b']' | b'}') => pos,
+            Some(b'[' | b'{') => self.flow(pos, path),
+            Some(b'"') => self.double_quoted(pos + 1, allowed),
+            Some(b'\'') => self.single_quoted(pos + 1, allowed),
+            Some(_) => {
+                let bytes = self.src;
+                let mut end = pos + 1;
+                // A flow scalar ends at an indicator, `: ` or ` #`.
+                while let Some(&b) = bytes.get(end) {
+                    let ends = match b {
+                        b',' | b']' | b'}' | b'\n' => true,
+                        b':' => matches!(bytes.get(end + 1), Some(b' ' | b'\n')),
+                        b'#' => bytes[end - 1] == b' ',
+                        _ => false,
+                    };
+                    if ends {
+                        break;
+                    }
+                    end += 1;
+                }
+                let text = self.content[pos..end].trim_end();
+                if allowed && !text.starts_with('*') && !is_plain_value(text) {
+                    self.push(RegionKind::String, pos..pos + text.len());
+                }
+                end
+            }
+        }
+    }
+}
+
+/// Splits `key: value` in a block mapping, returning the key and the offset
+/// of the value, if it is on the same line.
+fn mapping_key(rest: &str) -> Option<(String, Option<usize>)> {
+    let (key, after) = match rest.as_bytes()[0] {
+        quote @ (b'"' | b'\'') => {
+            let close = rest[1..].find(quote as char)? + 1;
+            let after = close + 1 + rest[close + 1..].len() - rest[close + 1..].trim_start().len();
+            if !rest[after..].starts_with(':') {
+                return None;
+            }
+            (rest[1..close].to_string(), after + 1)
+        }
+        b'[' | b'{' => return None,
+        _ => {
+            let code = &rest[..comment_start(rest).unwrap_or(rest.len())];
+            let colon = code.find(": ").or_else(|| code.find(":\t")).or_else(|| {
+                let trimmed = code.trim_end();
+                trimmed.ends_with(':').then(|| trimmed.len() - 1)
+            })?;
+            (code[..colon].trim_end().to_string(), colon + 1)
+        }
+    };
+    let value = &rest[after..];
+    let spaces = value.len() - value.trim_start().len();
+    let value_start = after + spaces;
+    let has_value = value_start < rest.len() && !rest[value_start..].starts_with('#');
+    Some((key, has_value.then_some(value_start)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_schema::DataConfig;
+
+    fn regions<'a>(content: &'a str, include_keys: &[&str]) -> Vec<(RegionKind, &'a str)> {
+        let filter = KeyFilter::new(&DataConfig {
+            include_keys: include_keys.iter().map(|k| k.to_string()).collect(),
+            ..DataConfig::default()
+        });
+        extract_regions(content, &filter).into_iter().map(|r| (r.kind, &content[r.range])).collect()
+    }
+
+    fn strings<'a>(content: &'a str, include_keys: &[&str]) -> Vec<&'a str> {
+        regions(content, include_keys).into_iter().filter(|(k, _)| *k == RegionKind::String).map(|(_, s)| s).collect()
+    }
+
+    #[test]
+    fn test_mappings_and_sequences() {
+        let content = "# Site settngs\ntitle: My Blgo  # the title\nversion: 1.2\nenabled: true\nempty:\n\
+            nested:\n  description: Somethng nice\n  \"quoted key\": 'It''s fine'\n\
+            items:\n- first itme\n- name: secnd\n  description: \"a \\\"quoted\\\" tpyo\\n\"\n- &anchor !tag tagged vaule\n- *alias\n\
+            ---\nnext: documnt\n";
+        assert_eq!(
+            strings(content, &[]),
+            vec![
+                "My Blgo",
+                "Somethng nice",
+                "It'",
+                "s fine",
+                "first itme",
+                "secnd",
+                "a ",
+                "quoted",
+                " tpyo",
+                "tagged vaule",
+                "documnt"
+            ]
+        );
+        assert_eq!(strings(content, &["**.description"]), vec!["Somethng nice", "a ", "quoted", " tpyo"]);
+        assert_eq!(strings(content, &["items.1.name", "nested.quoted key"]), vec!["It'", "s fine", "secnd"]);
+        let comments: Vec<&str> =
+            regions(content, &[]).into_iter().filter(|(k, _)| *k == RegionKind::Comment).map(|(_, s)| s).collect();
+        assert_eq!(comments, vec!["# Site settngs", "# the title"]);
+    }
+
+    #[test]
+    fn test_scalars() {
+        let content = "summary: >-\n  Folded txt\n  over lines.\n\n  More.\nafter: |\n    Literl\nplain: first\n  continud\n\
+            flow: [one, \"two\", {key: thre, n: 4}]\nmap: {a: b c, 'd': e}\nurl: https://example.com/a#b\n";
+        assert_eq!(
+            strings(content, &[]),
+            vec![
+                "Folded txt",
+                "over lines.",
+                "More.",
+                "Literl",
+                "first",
+                "continud",
+                "one",
+                "two",
+                "thre",
+                "b c",
+                "e",
+                "https://example.com/a#b"
+            ]
+        );
+        assert_eq!(strings(content, &["flow.2.key", "map.d"]), vec!["thre", "e"]);
+    }
+}
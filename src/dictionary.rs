@@ -1,28 +1,223 @@
 //! Dictionary implementation for fast word lookups.
+//!
+//! Words are kept in named word sets, one per locale. The bundled `en` set is
+//! the embedded word list; `en-US` and `en-GB` extend it and reject the other
+//! variety's spellings. Words added through the configuration's top-level
+//! `extra_words`, `extra_dictionaries` and `hunspell` keys are accepted in
+//! every locale.
 
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
-use anyhow::{Context, Result};
+use std::sync::OnceLock;
+use anyhow::{Context, Result, anyhow, bail};
+use crate::cache;
+use crate::compiled::{self, CompiledWords};
+use crate::config_schema::DictionaryConfig;
+use crate::hunspell::Hunspell;
+use crate::suggest::{self, SuggestionIndex};
+
+/// The embedded default English word list, compiled from
+/// `resources/words.txt` by the build script.
+const EMBEDDED_WORDS: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/words.dict"));
+
+/// Spellings that are only correct in American English, one per line.
+const EN_US_ONLY: &str = include_str!("../resources/locales/en-US.txt");
+
+/// Spellings that are only correct in British English, one per line.
+const EN_GB_ONLY: &str = include_str!("../resources/locales/en-GB.txt");
+
+/// A set of words, optionally extending the set of another locale.
+#[derive(Default)]
+struct WordSet {
+    words: HashSet<String>,
+    /// Compiled word lists, queried in place.
+    compiled: Vec<CompiledWords<Cow<'static, [u8]>>>,
+    /// Hunspell dictionaries, checked when a word is not in `words`.
+    hunspell: Vec<Hunspell>,
+    /// Words rejected even if an extended locale accepts them.
+    rejected: HashSet<String>,
+    /// The locale whose words this set accepts as well.
+    extends: Option<String>,
+    /// Suggestion index, built lazily on the first call to `suggest`.
+    index: OnceLock<SuggestionIndex>,
+    /// Content hashes of the files loaded into this set, for `fingerprint`.
+    sources: Vec<String>,
+}
+
+impl WordSet {
+    fn from_lines(text: &str) -> HashSet<String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    /// Loads a plain-text word list, or a list compiled by `dict compile`.
+    fn load_from_file(&mut self, path: &Path) -> Result<()> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("Failed to read dictionary file at {:?}", path))?;
+        self.sources.push(cache::hash(&bytes));
+        if compiled::is_compiled(&bytes) {
+            let list = CompiledWords::new(Cow::Owned(bytes))
+                .map_err(|e| anyhow!("Invalid compiled dictionary at {:?}: {}", path, e))?;
+            self.compiled.push(list);
+        } else {
+            let content = String::from_utf8(bytes)
+                .with_context(|| format!("Dictionary file at {:?} is not valid UTF-8", path))?;
+            self.words.extend(Self::from_lines(&content));
+        }
+        Ok(())
+    }
+
+    /// Loads a Hunspell dictionary from its `.aff` and `.dic` files.
+    fn load_hunspell(&mut self, aff_path: &Path, dic_path: &Path) -> Result<()> {
+        self.hunspell.push(Hunspell::load(aff_path, dic_path)?);
+        for path in [aff_path, dic_path] {
+            self.sources.push(fs::read(path).map(|bytes| cache::hash(&bytes)).unwrap_or_default());
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if this set itself (not an extended one) has the
+    /// lowercase `word`.
+    fn accepts(&self, word: &str) -> bool {
+        self.words.contains(word)
+            || self.compiled.iter().any(|c| c.contains(word))
+            || self.hunspell.iter().any(|h| h.contains(word))
+    }
+
+    /// Returns the words of the compiled lists and those derived from the
+    /// Hunspell dictionaries.
+    fn derived_words(&self) -> impl Iterator<Item = String> + '_ {
+        self.compiled
+            .iter()
+            .flat_map(|c| c.iter())
+            .chain(self.hunspell.iter().flat_map(Hunspell::words))
+    }
+
+    fn count(&self) -> usize {
+        self.words.len() + self.compiled.iter().map(CompiledWords::len).sum::<usize>()
+    }
+}
 
 /// A thread-safe, case-insensitive dictionary used for word lookups.
 pub struct Dictionary {
-    words: HashSet<String>,
+    /// Words accepted in every locale.
+    common: WordSet,
+    /// Word sets by locale name.
+    locales: HashMap<String, WordSet>,
+    /// The locale used by `contains` and `suggest`.
+    locale: String,
 }
 
 impl Dictionary {
     /// Creates a new, empty `Dictionary`.
     pub fn new() -> Self {
         Self {
-            words: HashSet::new(),
+            common: WordSet::default(),
+            locales: HashMap::new(),
+            locale: "en".to_string(),
+        }
+    }
+
+    /// Creates a dictionary from the bundled locales plus the extra words,
+    /// dictionary files, Hunspell dictionaries and locales listed in the
+    /// configuration.
+    ///
+    /// Fails if the configuration uses a locale that is not defined, or if
+    /// locales extend each other in a cycle.
+    pub fn from_config(config: &DictionaryConfig) -> Result<Self> {
+        let mut dictionary = Self::new();
+
+        let embedded = CompiledWords::new(Cow::Borrowed(EMBEDDED_WORDS)).map_err(anyhow::Error::msg)?;
+        let en = WordSet {
+            compiled: vec![embedded],
+            sources: vec![env!("SPELL_CHECK_RESOURCES_HASH").to_string()],
+            ..Default::default()
+        };
+        let en_us = WordSet {
+            rejected: WordSet::from_lines(EN_GB_ONLY),
+            extends: Some("en".to_string()),
+            ..Default::default()
+        };
+        let en_gb = WordSet {
+            rejected: WordSet::from_lines(EN_US_ONLY),
+            extends: Some("en".to_string()),
+            ..Default::default()
+        };
+        dictionary.locales.insert("en".to_string(), en);
+        dictionary.locales.insert("en-US".to_string(), en_us);
+        dictionary.locales.insert("en-GB".to_string(), en_gb);
+
+        dictionary.add_words(&config.extra_words);
+
+        for dict_path in &config.extra_dictionaries {
+            dictionary.load_from_file(dict_path)?;
+        }
+
+        for hunspell in &config.hunspell {
+            dictionary.load_hunspell(&hunspell.aff_path(), &hunspell.dic)?;
         }
+
+        for (name, locale) in &config.locales {
+            let set = dictionary.locales.entry(name.clone()).or_default();
+            if locale.extends.is_some() {
+                set.extends = locale.extends.clone();
+            }
+            set.words.extend(locale.words.iter().map(|w| w.to_lowercase()));
+            for dict_path in &locale.dictionaries {
+                set.load_from_file(dict_path)?;
+            }
+            for hunspell in &locale.hunspell {
+                set.load_hunspell(&hunspell.aff_path(), &hunspell.dic)?;
+            }
+        }
+
+        let used = std::iter::once(&config.locale)
+            .chain(config.locale_overrides.iter().map(|o| &o.locale))
+            .chain(config.locales.keys());
+        for name in used {
+            dictionary.validate_locale(name)?;
+        }
+        dictionary.locale = config.locale.clone();
+
+        Ok(dictionary)
     }
 
-    /// Adds a single word to the dictionary.
+    /// Checks that `name` and every locale it extends are defined, without cycles.
+    fn validate_locale(&self, name: &str) -> Result<()> {
+        let mut seen = HashSet::new();
+        let mut next = Some(name);
+        while let Some(current) = next {
+            if !seen.insert(current) {
+                bail!("Locale '{}' extends itself", name);
+            }
+            let Some(set) = self.locales.get(current) else {
+                bail!("Unknown locale '{}'; define it under [dictionary.locales]", current);
+            };
+            next = set.extends.as_deref();
+        }
+        Ok(())
+    }
+
+    /// Drops suggestion indexes after the words changed.
+    fn invalidate(&mut self) {
+        self.common.index.take();
+        for set in self.locales.values_mut() {
+            set.index.take();
+        }
+    }
+
+    /// Adds a single word to the dictionary, for every locale.
     ///
     /// The word is normalized to lowercase before storage.
     pub fn add_word(&mut self, word: &str) {
-        self.words.insert(word.to_lowercase());
+        if self.common.words.insert(word.to_lowercase()) {
+            self.invalidate();
+        }
     }
 
     /// Adds multiple words to the dictionary from an iterator.
@@ -38,29 +233,124 @@ impl Dictionary {
 
     /// Loads words from a plain-text file, one word per line.
     pub fn load_from_file(&mut self, path: &Path) -> Result<()> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read dictionary file at {:?}", path))?;
+        self.common.load_from_file(path)?;
+        self.invalidate();
+        Ok(())
+    }
 
-        for line in content.lines() {
-            let word = line.trim();
-            if !word.is_empty() {
-                self.add_word(word);
-            }
-        }
+    /// Loads a Hunspell dictionary from its `.aff` and `.dic` files.
+    ///
+    /// Words derived from its stems through affixes or compounding are
+    /// accepted by `contains`.
+    pub fn load_hunspell(&mut self, aff_path: &Path, dic_path: &Path) -> Result<()> {
+        self.common.load_hunspell(aff_path, dic_path)?;
+        self.invalidate();
         Ok(())
     }
 
-    /// Checks if a word exists in the dictionary.
+    /// Checks if a word exists in the dictionary's default locale.
     ///
     /// This lookup is case-insensitive.
     pub fn contains(&self, word: &str) -> bool {
-        let word_lower = word.to_lowercase();
-        self.words.contains(&word_lower)
+        self.contains_in(&self.locale, word)
     }
 
-    /// Returns the total number of words in the dictionary.
+    /// Returns `true` if `name` is a bundled or configured locale.
+    pub fn has_locale(&self, name: &str) -> bool {
+        self.locales.contains_key(name)
+    }
+
+    /// Checks if a word is correct in `locale`.
+    ///
+    /// A locale accepts its own words and those of the locales it extends,
+    /// except for words it rejects. Unknown locales accept only the words
+    /// common to all locales.
+    pub fn contains_in(&self, locale: &str, word: &str) -> bool {
+        let word = word.to_lowercase();
+        if self.common.accepts(&word) {
+            return true;
+        }
+        let mut next = Some(locale);
+        while let Some(set) = next.and_then(|name| self.locales.get(name)) {
+            if set.rejected.contains(&word) {
+                return false;
+            }
+            if set.accepts(&word) {
+                return true;
+            }
+            next = set.extends.as_deref();
+        }
+        false
+    }
+
+    /// Returns up to `limit` suggested corrections for a misspelled word in
+    /// the default locale.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        self.suggest_in(&self.locale, word, limit)
+    }
+
+    /// Returns up to `limit` suggested corrections for a misspelled word in `locale`.
+    ///
+    /// Suggestions are lowercase and ranked by edit distance. Each locale's
+    /// suggestion index is built on first use, so runs without errors never
+    /// pay for it. Hunspell dictionaries contribute their stems and singly
+    /// affixed forms.
+    pub fn suggest_in(&self, locale: &str, word: &str, limit: usize) -> Vec<String> {
+        let set = self.locales.get(locale).unwrap_or(&self.common);
+        let index = set.index.get_or_init(|| self.build_index(locale));
+        suggest::rank(index, &word.to_lowercase(), limit)
+    }
+
+    /// Builds the suggestion index of every word `locale` accepts.
+    fn build_index(&self, locale: &str) -> SuggestionIndex {
+        let mut chain = Vec::new();
+        let mut next = Some(locale);
+        while let Some(set) = next.and_then(|name| self.locales.get(name)) {
+            chain.push(set);
+            next = set.extends.as_deref();
+        }
+        // A set's words count unless it, or a locale extending it, rejects them.
+        let allowed = |i: usize, word: &str| !chain[..=i].iter().any(|s| s.rejected.contains(word));
+
+        let mut derived: HashSet<String> = self.common.derived_words().collect();
+        for (i, set) in chain.iter().enumerate() {
+            derived.extend(set.derived_words().filter(|w| allowed(i, w)));
+        }
+
+        let mut words: HashSet<&str> = self.common.words.iter().map(String::as_str).collect();
+        for (i, set) in chain.iter().enumerate() {
+            words.extend(set.words.iter().map(String::as_str).filter(|w| allowed(i, w)));
+        }
+        words.extend(derived.iter().map(String::as_str));
+        SuggestionIndex::new(words)
+    }
+
+    /// Fingerprints everything this dictionary accepts and rejects, for the
+    /// result cache.
+    ///
+    /// Word lists loaded from files are represented by a hash of their
+    /// content, and words added in code are listed, so two dictionaries with
+    /// the same fingerprint give the same results.
+    pub fn fingerprint(&self) -> String {
+        let mut locales: Vec<(&String, &WordSet)> = self.locales.iter().collect();
+        locales.sort_by_key(|(name, _)| *name);
+        let common = String::new();
+        let mut input = format!("locale={}\n", self.locale);
+        for (name, set) in std::iter::once((&common, &self.common)).chain(locales) {
+            input.push_str(&format!("[{}] extends={:?} sources={}\n", name, set.extends, set.sources.join(",")));
+            for words in [&set.words, &set.rejected] {
+                let mut words: Vec<&str> = words.iter().map(String::as_str).collect();
+                words.sort_unstable();
+                input.push_str(&words.join(" "));
+                input.push('\n');
+            }
+        }
+        cache::hash(input.as_bytes())
+    }
+
+    /// Returns the total number of words in the dictionary, across locales.
     pub fn count(&self) -> usize {
-        self.words.len()
+        self.common.count() + self.locales.values().map(WordSet::count).sum::<usize>()
     }
 }
 
@@ -93,4 +383,104 @@ mod tests {
         assert_eq!(dict.count(), 3);
         assert!(dict.contains("TWO"));
     }
+
+    #[test]
+    fn test_suggest() {
+        let mut dict = Dictionary::new();
+        dict.add_words(["occurrence", "reference"]);
+        assert_eq!(dict.suggest("Occurance", 3), vec!["occurrence"]);
+
+        // Adding words invalidates the index.
+        dict.add_word("occurrences");
+        assert_eq!(dict.suggest("occurences", 3), vec!["occurrences", "occurrence"]);
+    }
+
+    #[test]
+    fn test_suggest_embedded() -> Result<()> {
+        let dict = Dictionary::from_config(&DictionaryConfig::default())?;
+        for (typo, expected) in [
+            ("teh", "the"),
+            ("wrold", "world"),
+            ("recieve", "receive"),
+            ("seperate", "separate"),
+            ("occurence", "occurrence"),
+            ("definately", "definitely"),
+            ("adress", "address"),
+            ("becuase", "because"),
+        ] {
+            assert_eq!(dict.suggest(typo, 5).first().map(String::as_str), Some(expected), "{typo}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hunspell() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dic = dir.path().join("en_GB.dic");
+        fs::write(dir.path().join("en_GB.aff"), "SET UTF-8\nSFX S Y 1\nSFX S 0 s .\n")?;
+        fs::write(&dic, "2\ncolour/S\nrecognise/S\n")?;
+
+        let config = crate::config_schema::HunspellConfig { dic, aff: None };
+        let mut dict = Dictionary::new();
+        dict.load_hunspell(&config.aff_path(), &config.dic)?;
+        assert!(dict.contains("Colours"));
+        assert!(dict.contains("recognises"));
+        assert!(!dict.contains("recognised"));
+        assert_eq!(dict.suggest("recognizes", 3), vec!["recognises", "recognise"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compiled_dictionary_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("words.dict");
+        fs::write(&path, compiled::encode(["Kubernetes", "tokio"]))?;
+
+        let mut dict = Dictionary::new();
+        dict.load_from_file(&path)?;
+        assert!(dict.contains("kubernetes"));
+        assert!(!dict.contains("kube"));
+        assert_eq!(dict.count(), 2);
+        assert_eq!(dict.suggest("kubernets", 1), vec!["kubernetes"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_locales() -> Result<()> {
+        let mut config = DictionaryConfig { locale: "en-GB".to_string(), ..Default::default() };
+        config.extra_words = vec!["color".to_string()];
+        config.locales.insert(
+            "de-DE".to_string(),
+            crate::config_schema::LocaleConfig { words: vec!["Farbe".to_string()], ..Default::default() },
+        );
+        let dict = Dictionary::from_config(&config)?;
+
+        assert!(dict.contains("colour"));
+        assert!(!dict.contains("organize"));
+        // Words added for all locales win over the locale's rejections.
+        assert!(dict.contains("color"));
+        assert!(dict.contains_in("en-US", "organize"));
+        assert!(!dict.contains_in("en-US", "organise"));
+        assert!(dict.contains_in("en", "organise"));
+        assert!(dict.contains_in("de-DE", "farbe"));
+        assert!(!dict.contains_in("de-DE", "colour"));
+
+        assert_eq!(dict.suggest_in("en-US", "organixe", 1), vec!["organize"]);
+        assert_eq!(dict.suggest_in("en-GB", "organixe", 1), vec!["organise"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_locale() {
+        let config = DictionaryConfig { locale: "fr".to_string(), ..Default::default() };
+        let error = Dictionary::from_config(&config).err().unwrap();
+        assert!(error.to_string().contains("Unknown locale 'fr'"));
+
+        let mut config = DictionaryConfig::default();
+        config.locales.insert(
+            "en-AU".to_string(),
+            crate::config_schema::LocaleConfig { extends: Some("en-AU".to_string()), ..Default::default() },
+        );
+        assert!(Dictionary::from_config(&config).is_err());
+    }
 }
@@ -1,20 +1,47 @@
 //! The core spell checking engine.
 
-use crate::config_schema::Config;
+use crate::cache::{self, Cache};
+use crate::config::{ProjectConfig, normalize};
+use crate::config_schema::{Config, LanguageConfig, Override};
+use crate::data::json;
 use crate::dictionary::Dictionary;
-use anyhow::{Context, Result};
+use crate::git::{Changed, ChangedFiles};
+use crate::html;
+use crate::lexer::{self, Language};
+use crate::notebook::{self, CellPosition, Notebook};
+use crate::patterns::{self, IgnorePatterns};
+use crate::tokenizer;
+use anyhow::{Context, Result, bail};
 use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use tokio::task::JoinSet;
 
+/// The maximum number of suggestions attached to each `SpellError`.
+const MAX_SUGGESTIONS: usize = 5;
+
 /// The main spell-checking engine.
 ///
 /// It coordinates file walking, word extraction, and dictionary lookups.
+/// Cloning is cheap: clones share the same configuration and dictionary.
+#[derive(Clone)]
 pub struct Engine {
+    /// Settings for the scan root.
     inner: Arc<EngineInner>,
+    /// Settings for subdirectories with their own config, by directory
+    /// relative to the scan root, parents first.
+    scopes: Arc<Vec<(PathBuf, Arc<EngineInner>)>>,
+    /// Directory of the result cache, if enabled.
+    cache_dir: Option<PathBuf>,
+    /// If set, only these files (and lines) are checked.
+    changes: Option<Arc<ChangedFiles>>,
+    /// Canonical paths of files that are never checked.
+    skipped: Arc<Vec<PathBuf>>,
 }
 
 struct EngineInner {
@@ -22,10 +49,36 @@ struct EngineInner {
     dictionary: Arc<Dictionary>,
     include_set: GlobSet,
     exclude_set: GlobSet,
+    /// `dictionary.locale_overrides`, in order, with their compiled globs.
+    locale_overrides: Vec<(GlobSet, String)>,
+    /// `ignore.patterns` and the enabled built-in patterns.
+    patterns: IgnorePatterns,
+    /// `overrides`, in order.
+    overrides: Vec<CompiledOverride>,
+}
+
+/// An `[[overrides]]` entry with its compiled globs.
+struct CompiledOverride {
+    files: GlobSet,
+    /// Its `dictionary.extra_words` and `ignore.words`, lowercased.
+    words: HashSet<String>,
+    patterns: IgnorePatterns,
+    settings: Override,
+}
+
+/// The settings that apply to one file.
+struct FileSettings {
+    locale: String,
+    /// The language to lex the file with, or `None` to check it in full.
+    language: Option<Language>,
+    /// Which regions to check when the file is lexed.
+    regions: LanguageConfig,
+    /// Indices of the matching `overrides`.
+    overrides: Vec<usize>,
 }
 
 /// Represents a spelling error found in a file.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpellError {
     /// The path to the file containing the error.
     pub file: PathBuf,
@@ -33,10 +86,28 @@ pub struct SpellError {
     pub line: usize,
     /// The 1-based column number where the word starts.
     pub col: usize,
+    /// The byte range of the word within the file.
+    pub span: Range<usize>,
     /// The misspelled word.
     pub word: String,
     /// The full content of the line containing the error.
     pub context: String,
+    /// Suggested corrections, best first. May be empty.
+    pub suggestions: Vec<String>,
+    /// For notebooks, the position of the word within its cell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<CellPosition>,
+}
+
+impl SpellError {
+    /// Returns the word as written in `context`: the text of `span`, which is
+    /// longer than `word` when the word was decoded from character references
+    /// or escapes (`caf&eacute;`, `caf\u00e9`).
+    pub fn source(&self) -> &str {
+        let start = self.col.saturating_sub(1);
+        let end = (start + self.span.len()).min(self.context.len());
+        self.context.get(start..end).unwrap_or(&self.word)
+    }
 }
 
 impl Engine {
@@ -44,28 +115,88 @@ impl Engine {
     ///
     /// Validates the include/exclude glob patterns provided in the config.
     pub fn try_new(config: Config, dictionary: Dictionary) -> Result<Self> {
-        let mut include_builder = GlobSetBuilder::new();
-        for pattern in &config.files.include {
-            let glob = Glob::new(pattern).with_context(|| format!("Invalid include glob pattern: {}", pattern))?;
-            include_builder.add(glob);
+        Ok(Self {
+            inner: Arc::new(EngineInner::try_new(config, dictionary)?),
+            scopes: Arc::new(Vec::new()),
+            cache_dir: None,
+            changes: None,
+            skipped: Arc::new(Vec::new()),
+        })
+    }
+
+    /// Creates an engine for all configurations of a project, building a
+    /// dictionary for each.
+    pub fn for_project(project: ProjectConfig) -> Result<Self> {
+        let dictionary = Dictionary::from_config(&project.config.dictionary)?;
+        let mut engine = Self::try_new(project.config, dictionary)?;
+        for (dir, config) in project.nested {
+            let dictionary = Dictionary::from_config(&config.dictionary)
+                .with_context(|| format!("Invalid dictionary settings for {}", dir.display()))?;
+            engine.add_scope(dir, config, dictionary)?;
         }
-        let include_set = include_builder.build().context("Failed to build include glob set")?;
+        Ok(engine)
+    }
 
-        let mut exclude_builder = GlobSetBuilder::new();
-        for pattern in &config.files.exclude {
-            let glob = Glob::new(pattern).with_context(|| format!("Invalid exclude glob pattern: {}", pattern))?;
-            exclude_builder.add(glob);
+    /// Uses `config` and `dictionary` for files under `dir`, relative to the
+    /// scan root, instead of the settings of the enclosing directory.
+    ///
+    /// Subdirectories must be added after their parents.
+    pub fn add_scope(&mut self, dir: PathBuf, config: Config, dictionary: Dictionary) -> Result<()> {
+        let scope = Arc::new(EngineInner::try_new(config, dictionary)?);
+        Arc::make_mut(&mut self.scopes).push((normalize(&dir), scope));
+        Ok(())
+    }
+
+    /// Returns the settings for a path relative to the scan root: those of
+    /// the deepest directory with its own config containing it.
+    fn scope_for(&self, path: &Path) -> &Arc<EngineInner> {
+        let path = normalize(path);
+        self.scopes
+            .iter()
+            .rev()
+            .find(|(dir, _)| path.starts_with(dir))
+            .map_or(&self.inner, |(_, scope)| scope)
+    }
+
+    /// Fingerprints the settings of every scope for the result cache.
+    fn fingerprint(&self) -> String {
+        let mut input = cache::fingerprint(&self.inner.config, &self.inner.dictionary);
+        for (dir, scope) in self.scopes.iter() {
+            input.push_str(&format!("\n{}={}", dir.display(), cache::fingerprint(&scope.config, &scope.dictionary)));
         }
-        let exclude_set = exclude_builder.build().context("Failed to build exclude glob set")?;
+        cache::hash(input.as_bytes())
+    }
 
-        Ok(Self {
-            inner: Arc::new(EngineInner {
-                config: Arc::new(config),
-                dictionary: Arc::new(dictionary),
-                include_set,
-                exclude_set,
-            }),
-        })
+    /// Enables the result cache in `dir`, or disables it with `None`.
+    ///
+    /// With a cache, `run` replays the previous results of files whose
+    /// content, configuration and dictionaries are unchanged instead of
+    /// checking them again.
+    pub fn with_cache(mut self, dir: Option<PathBuf>) -> Self {
+        self.cache_dir = dir;
+        self
+    }
+
+    /// Limits `run` to the given changed files, or checks everything with `None`.
+    ///
+    /// Files that changed only in some lines report errors on those lines only.
+    pub fn with_changes(mut self, changes: Option<ChangedFiles>) -> Self {
+        self.changes = changes.map(Arc::new);
+        self
+    }
+
+    /// Never checks the files at `paths`, whatever the globs say, such as the
+    /// baseline being read or written. The files need not exist yet.
+    pub fn with_skipped(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.skipped = Arc::new(paths.into_iter().map(|path| canonical(&path)).collect());
+        self
+    }
+
+    /// Returns `true` if `path` is one of the files passed to `with_skipped`.
+    fn is_skipped(&self, path: &Path) -> bool {
+        // Only resolve paths whose file name matches one of the skipped files.
+        self.skipped.iter().any(|skipped| skipped.file_name() == path.file_name())
+            && self.skipped.contains(&canonical(path))
     }
 
     /// Starts the spell-checking process for the given path.
@@ -74,12 +205,33 @@ impl Engine {
     /// as strings as they are discovered.
     pub fn run(&self, path: PathBuf) -> mpsc::Receiver<Result<SpellError, String>> {
         let (tx, rx) = mpsc::channel(100);
-        let inner = self.inner.clone();
+        let engine = self.clone();
         let scan_root = path.clone();
+        let cache = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| Arc::new(Cache::open(dir, self.fingerprint())));
+
+        // Changed files are keyed by canonical path, so walked paths are
+        // resolved against the canonical scan root.
+        let changes = self
+            .changes
+            .clone()
+            .map(|changes| (changes, scan_root.canonicalize().unwrap_or_else(|_| scan_root.clone())));
 
         tokio::spawn(async move {
-            let mut walker = WalkBuilder::new(&scan_root);
-            walker.add_custom_ignore_filename(".spellcheckignore");
+            let mut walker = walker(&scan_root);
+            if let Some((changes, canonical_root)) = changes.clone() {
+                let scan_root = scan_root.clone();
+                walker.filter_entry(move |entry| {
+                    entry.depth() == 0
+                        || !entry.file_type().is_some_and(|ft| ft.is_dir())
+                        || entry
+                            .path()
+                            .strip_prefix(&scan_root)
+                            .is_ok_and(|relative| changes.contains_dir(&canonical_root.join(relative)))
+                });
+            }
             let walker = walker.build();
 
             let mut set = JoinSet::new();
@@ -93,8 +245,27 @@ impl Engine {
                             // Make path relative to scan root for glob matching
                             let relative_path = entry_path.strip_prefix(&scan_root).expect("Failed to get relative path");
                             
-                            if inner.should_check(relative_path) {
+                            let changed = match &changes {
+                                Some((changes, canonical_root)) => {
+                                    let path = if relative_path.as_os_str().is_empty() {
+                                        canonical_root.clone()
+                                    } else {
+                                        canonical_root.join(relative_path)
+                                    };
+                                    match changes.get(&path) {
+                                        Some(changed) => Some(changed.clone()),
+                                        None => continue,
+                                    }
+                                }
+                                None => None,
+                            };
+
+                            let inner = engine.scope_for(relative_path);
+                            if inner.should_check(relative_path) && !engine.is_skipped(&entry_path) {
                                 let tx = tx.clone();
+                                let settings = inner.settings_for(relative_path);
+                                let key = cache_key(relative_path, &entry_path);
+                                let cache = cache.clone();
                                 let inner = inner.clone();
                                 let entry_path = entry_path.clone();
                                 
@@ -104,7 +275,7 @@ impl Engine {
                                 }
                                 
                                 set.spawn(async move {
-                                    if let Err(e) = Self::check_file(&entry_path, &inner, tx).await {
+                                    if let Err(e) = Self::check_file(&entry_path, &key, &settings, &inner, cache.as_deref(), changed.as_ref(), tx).await {
                                         // Errors are handled inside check_file or reported back if critical
                                         return Err(format!("Error checking {}: {}", entry_path.display(), e));
                                     }
@@ -124,19 +295,312 @@ impl Engine {
                     let _ = tx.send(Err(e)).await;
                 }
             }
+
+            if let Some(cache) = cache
+                && let Err(e) = cache.save()
+            {
+                let _ = tx.send(Err(format!("Failed to update cache: {:#}", e))).await;
+            }
         });
 
         rx
     }
 
-    async fn check_file(path: &Path, inner: &EngineInner, tx: mpsc::Sender<Result<SpellError, String>>) -> Result<()> {
-        let content = tokio::fs::read_to_string(path).await
+    /// Checks one file, or replays its cached results if it is unchanged.
+    ///
+    /// `key` identifies the file in the cache. With `changed`, only errors on
+    /// changed lines are reported; the cache still holds all of them.
+    async fn check_file(
+        path: &Path,
+        key: &str,
+        settings: &FileSettings,
+        inner: &EngineInner,
+        cache: Option<&Cache>,
+        changed: Option<&Changed>,
+        tx: mpsc::Sender<Result<SpellError, String>>,
+    ) -> Result<()> {
+        let in_change = |error: &SpellError| changed.is_none_or(|changed| changed.contains_line(error.line));
+
+        let bytes = tokio::fs::read(path).await
             .with_context(|| format!("Failed to read file {}", path.display()))?;
-        
+
+        let hash = cache.map(|_| cache::hash(&bytes));
+        if let (Some(cache), Some(hash)) = (cache, &hash)
+            && let Some(errors) = cache.lookup(key, hash)
+        {
+            for mut error in errors.into_iter().filter(in_change) {
+                error.file = path.to_path_buf();
+                let _ = tx.send(Ok(error)).await;
+            }
+            return Ok(());
+        }
+
+        let content = String::from_utf8(bytes)
+            .with_context(|| format!("Failed to read file {}", path.display()))?;
+        let errors = inner.check_text(path, settings, &content);
+        if let (Some(cache), Some(hash)) = (cache, hash) {
+            cache.store(key.to_string(), hash, errors.clone());
+        }
+
+        for error in errors.into_iter().filter(in_change) {
+            let _ = tx.send(Ok(error)).await;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the files under `root` that `run` would check, in walk order.
+    pub fn files(&self, root: &Path) -> Vec<PathBuf> {
+        walker(root)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .map(|entry| entry.into_path())
+            .filter(|path| path.strip_prefix(root).is_ok_and(|relative| self.should_check(relative)))
+            .filter(|path| !self.is_skipped(path))
+            .collect()
+    }
+
+    /// Checks the file at `path`, found under `root`, without using the cache.
+    ///
+    /// Errors record `path` as given, like the ones from `run`.
+    pub fn check_path(&self, root: &Path, path: &Path) -> Result<Vec<SpellError>> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file {}", path.display()))?;
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let inner = self.scope_for(relative);
+        Ok(inner.check_text(path, &inner.settings_for(relative), &content))
+    }
+
+    /// Checks in-memory `content` as if it were the contents of `path`.
+    ///
+    /// The path, relative to the scan root, selects the language lexer and the
+    /// locale and is recorded in each error; the file itself is not read. Used
+    /// by editors for unsaved buffers.
+    pub fn check_text(&self, path: &Path, content: &str) -> Vec<SpellError> {
+        let inner = self.scope_for(path);
+        inner.check_text(path, &inner.settings_for(path), content)
+    }
+
+    /// Returns `true` if a path, relative to the scan root, matches the
+    /// include globs and none of the exclude globs.
+    pub fn should_check(&self, path: &Path) -> bool {
+        self.scope_for(path).should_check(path)
+    }
+
+    /// Extracts potential words from a string slice.
+    ///
+    /// Returns a list of (byte_offset, token) pairs. Tokens may be whole
+    /// identifiers such as `parseConfigFile`; use `tokenizer::split_identifier`
+    /// to break them into the sub-words that are looked up.
+    fn extract_words(content: &str) -> Vec<(usize, &str)> {
+        tokenizer::extract_tokens(content)
+    }
+}
+
+/// Checked text with character references replaced by what they stand for.
+struct Decoded {
+    text: String,
+    /// The decoded start, decoded length, original start and original length
+    /// of each replacement, in order.
+    replacements: Vec<(usize, usize, usize, usize)>,
+}
+
+impl Decoded {
+    fn new(checked: String, references: Vec<(Range<usize>, String)>) -> Self {
+        if references.is_empty() {
+            return Self { text: checked, replacements: Vec::new() };
+        }
+        let mut text = String::with_capacity(checked.len());
+        let mut replacements = Vec::with_capacity(references.len());
+        let mut copied = 0;
+        for (range, decoded) in references {
+            text.push_str(&checked[copied..range.start]);
+            replacements.push((text.len(), decoded.len(), range.start, range.len()));
+            text.push_str(&decoded);
+            copied = range.end;
+        }
+        text.push_str(&checked[copied..]);
+        Self { text, replacements }
+    }
+
+    /// Maps an offset in the original content to one in `text`.
+    fn to_decoded(&self, original: usize) -> usize {
+        let i = self.replacements.partition_point(|r| r.2 <= original);
+        match i.checked_sub(1).map(|i| self.replacements[i]) {
+            Some((start, _, orig_start, orig_len)) if original < orig_start + orig_len => start,
+            Some((start, len, orig_start, orig_len)) => start + len + (original - orig_start - orig_len),
+            None => original,
+        }
+    }
+
+    /// Maps an offset in `text` to one in the original content. Offsets
+    /// inside a replacement map to the start of the reference.
+    fn to_original(&self, decoded: usize) -> usize {
+        let i = self.replacements.partition_point(|r| r.0 <= decoded);
+        match i.checked_sub(1).map(|i| self.replacements[i]) {
+            Some((start, len, orig_start, _)) if decoded < start + len => orig_start,
+            Some((start, len, orig_start, orig_len)) => orig_start + orig_len + (decoded - start - len),
+            None => decoded,
+        }
+    }
+}
+
+/// Creates the walker over `root` shared by `run` and `files`.
+/// Resolves `path` to its canonical form. For a file that does not exist
+/// yet, its directory is resolved instead.
+fn canonical(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    match (dir.canonicalize(), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn walker(root: &Path) -> WalkBuilder {
+    let mut walker = WalkBuilder::new(root);
+    walker.add_custom_ignore_filename(".spellcheckignore");
+    walker
+}
+
+/// Returns the cache key of a file: its path relative to the scan root, or
+/// the path as given when a single file is scanned.
+fn cache_key(relative: &Path, path: &Path) -> String {
+    let key = if relative.as_os_str().is_empty() { path } else { relative };
+    key.to_string_lossy().replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+impl EngineInner {
+    /// Compiles the glob patterns of `config`.
+    fn try_new(config: Config, dictionary: Dictionary) -> Result<Self> {
+        let mut include_builder = GlobSetBuilder::new();
+        for pattern in &config.files.include {
+            let glob = Glob::new(pattern).with_context(|| format!("Invalid include glob pattern: {}", pattern))?;
+            include_builder.add(glob);
+        }
+        let include_set = include_builder.build().context("Failed to build include glob set")?;
+
+        let mut exclude_builder = GlobSetBuilder::new();
+        for pattern in &config.files.exclude {
+            let glob = Glob::new(pattern).with_context(|| format!("Invalid exclude glob pattern: {}", pattern))?;
+            exclude_builder.add(glob);
+        }
+        let exclude_set = exclude_builder.build().context("Failed to build exclude glob set")?;
+
+        let mut locale_overrides = Vec::new();
+        for locale_override in &config.dictionary.locale_overrides {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &locale_override.files {
+                let glob = Glob::new(pattern).with_context(|| format!("Invalid locale override glob pattern: {}", pattern))?;
+                builder.add(glob);
+            }
+            let set = builder.build().context("Failed to build locale override glob set")?;
+            locale_overrides.push((set, locale_override.locale.clone()));
+        }
+
+        let mut overrides = Vec::new();
+        for settings in &config.overrides {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &settings.files {
+                let glob = Glob::new(pattern).with_context(|| format!("Invalid override glob pattern: {}", pattern))?;
+                builder.add(glob);
+            }
+            let files = builder.build().context("Failed to build override glob set")?;
+            if let Some(locale) = &settings.locale
+                && !dictionary.has_locale(locale)
+            {
+                bail!("Unknown locale '{}' in overrides; define it under [dictionary.locales]", locale);
+            }
+            let words = settings
+                .dictionary
+                .extra_words
+                .iter()
+                .chain(&settings.ignore.words)
+                .map(|w| w.to_lowercase())
+                .collect();
+            let patterns = IgnorePatterns::new(&settings.ignore.patterns)?;
+            overrides.push(CompiledOverride { files, words, patterns, settings: settings.clone() });
+        }
+
+        let patterns = IgnorePatterns::from_config(&config.ignore)?;
+
+        Ok(Self {
+            config: Arc::new(config),
+            dictionary: Arc::new(dictionary),
+            include_set,
+            exclude_set,
+            locale_overrides,
+            patterns,
+            overrides,
+        })
+    }
+
+    /// Checks file content with the given settings, returning every spelling
+    /// error found.
+    fn check_text(&self, path: &Path, settings: &FileSettings, content: &str) -> Vec<SpellError> {
+        let mut errors = Vec::new();
+        let locale = settings.locale.as_str();
+
+        // Source files are only checked inside their comments and literals;
+        // everything else is masked out so offsets stay valid.
+        let checked = match settings.language {
+            Some(language) => {
+                let regions = lexer::extract_regions(language, content, &self.config.languages);
+                lexer::mask(
+                    content,
+                    regions.iter().filter(|r| settings.regions.checks(r.kind)).map(|r| &r.range),
+                )
+            }
+            None => content.to_string(),
+        };
+
+        // URLs, hashes and other matches of the ignore patterns are blanked
+        // out the same way.
+        let mut ignored = self.patterns.find(&checked);
+        for &i in &settings.overrides {
+            ignored.extend(self.overrides[i].patterns.find(&checked));
+        }
+        let checked = if ignored.is_empty() { checked } else { patterns::blank(&checked, &ignored) };
+
+        // Character references in markup are checked as the text they stand
+        // for, so `caf&eacute;` is one word.
+        let references = match settings.language {
+            Some(language) if language.is_markup() => html::entities(&checked),
+            Some(Language::Notebook) => notebook::references(&checked),
+            Some(Language::Json) => json::escapes(&checked),
+            _ => Vec::new(),
+        };
+        let checked = Decoded::new(checked, references);
+
+        // Errors in notebooks also report their position within the cell.
+        let notebook = match settings.language {
+            Some(Language::Notebook) => Notebook::parse(content),
+            _ => None,
+        };
+
         let mut disabled = false;
 
-        for (line_num, line_content) in content.lines().enumerate() {
+        let mut next_line_start = 0;
+
+        // Repeated misspellings share their suggestions.
+        let mut suggestions: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (line_num, raw_line) in content.split_inclusive('\n').enumerate() {
             let line_num = line_num + 1;
+            let line_start = next_line_start;
+            next_line_start += raw_line.len();
+
+            let line_content = raw_line
+                .strip_suffix('\n')
+                .map_or(raw_line, |l| l.strip_suffix('\r').unwrap_or(l));
+            let decoded_start = checked.to_decoded(line_start);
+            let checked_line = &checked.text[decoded_start..checked.to_decoded(line_start + line_content.len())];
 
             if line_content.contains("spellcheck-disable") {
                 disabled = true;
@@ -150,66 +614,99 @@ impl Engine {
                 continue;
             }
 
-            let words = Self::extract_words(line_content);
-            for (col, word) in words {
-                if !inner.dictionary.contains(word) {
-                    // Check if word is in ignore list
-                    if inner.config.ignore.words.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+            for (offset, token) in Engine::extract_words(checked_line) {
+                // Whole identifiers may be allowed explicitly (e.g. `GitHub`).
+                if self.is_known(settings, token) {
+                    continue;
+                }
+
+                for (sub_offset, word) in tokenizer::split_identifier(token) {
+                    if self.is_known(settings, word) {
                         continue;
                     }
 
-                    let _ = tx.send(Ok(SpellError {
+                    let decoded = decoded_start + offset + sub_offset;
+                    let start = checked.to_original(decoded);
+                    errors.push(SpellError {
                         file: path.to_path_buf(),
                         line: line_num,
-                        col,
+                        col: start - line_start + 1,
+                        span: start..checked.to_original(decoded + word.len()),
                         word: word.to_string(),
                         context: line_content.to_string(),
-                    })).await;
+                        suggestions: suggestions
+                            .entry(word.to_lowercase())
+                            .or_insert_with(|| self.dictionary.suggest_in(locale, word, MAX_SUGGESTIONS))
+                            .clone(),
+                        cell: notebook.as_ref().and_then(|n| n.position(start)),
+                    });
                 }
             }
         }
 
-        Ok(())
+        errors
     }
 
-    /// Extracts potential words from a string slice.
-    ///
-    /// Returns a list of (start_column, word) pairs. Handles apostrophes correctly
-    /// and ignores alphanumeric strings that contain numbers.
-    fn extract_words(content: &str) -> Vec<(usize, &str)> {
-        let mut words = Vec::new();
-        let mut start = None;
+    /// Returns `true` if the word is in the dictionary for the file's locale,
+    /// the ignore list, or the words of a matching override.
+    fn is_known(&self, settings: &FileSettings, word: &str) -> bool {
+        self.dictionary.contains_in(&settings.locale, word)
+            || self.config.ignore.words.iter().any(|w| w.eq_ignore_ascii_case(word))
+            || (!settings.overrides.is_empty() && {
+                let word = word.to_lowercase();
+                settings.overrides.iter().any(|&i| self.overrides[i].words.contains(&word))
+            })
+    }
 
-        for (i, c) in content.char_indices() {
-            if c.is_alphabetic() || c == '\'' {
-                if start.is_none() {
-                    start = Some(i);
-                }
-            } else {
-                if let Some(s) = start {
-                    let mut word = &content[s..i];
-                    word = word.trim_matches('\'');
-                    if word.len() > 1 && !word.chars().any(char::is_numeric) {
-                        words.push((s + 1, word));
-                    }
-                    start = None;
-                }
+    /// Returns the settings for a path relative to the scan root, applying
+    /// the matching `overrides` in order.
+    fn settings_for(&self, path: &Path) -> FileSettings {
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        let normalized = path_str.trim_start_matches("./");
+        let mut settings = FileSettings {
+            locale: self.locale_for(path).to_string(),
+            language: Language::from_path(path),
+            regions: LanguageConfig::default(),
+            overrides: Vec::new(),
+        };
+        for (i, entry) in self.overrides.iter().enumerate() {
+            if !entry.files.is_match(normalized) {
+                continue;
+            }
+            if let Some(locale) = &entry.settings.locale {
+                settings.locale = locale.clone();
             }
+            if let Some(language) = entry.settings.language {
+                settings.language = language.language();
+            }
+            settings.overrides.push(i);
         }
 
-        if let Some(s) = start {
-            let mut word = &content[s..];
-            word = word.trim_matches('\'');
-            if word.len() > 1 && !word.chars().any(char::is_numeric) {
-                words.push((s + 1, word));
-            }
+        if let Some(language) = settings.language {
+            settings.regions = self.config.languages.get(language).clone();
         }
+        for &i in &settings.overrides {
+            let entry = &self.overrides[i].settings;
+            let regions = &mut settings.regions;
+            regions.comments = entry.comments.unwrap_or(regions.comments);
+            regions.doc_comments = entry.doc_comments.unwrap_or(regions.doc_comments);
+            regions.strings = entry.strings.unwrap_or(regions.strings);
+        }
+        settings
+    }
 
-        words
+    /// Returns the locale for a path relative to the scan root: that of the
+    /// last matching `locale_overrides` entry, or `dictionary.locale`.
+    fn locale_for(&self, path: &Path) -> &str {
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        let normalized = path_str.trim_start_matches("./");
+        self.locale_overrides
+            .iter()
+            .rev()
+            .find(|(set, _)| set.is_match(normalized))
+            .map_or(&self.config.dictionary.locale, |(_, locale)| locale)
     }
-}
 
-impl EngineInner {
     fn should_check(&self, path: &Path) -> bool {
         // Normalize path to forward slashes for globset
         let path_str = path.to_string_lossy().replace('\\', "/");
@@ -267,4 +764,275 @@ mod tests {
         assert!(!engine.inner.should_check(Path::new("src/temp.rs")));
         Ok(())
     }
+
+    #[test]
+    fn test_locale_overrides() -> anyhow::Result<()> {
+        let mut config = Config::default();
+        config.dictionary.locale = "en-US".to_string();
+        config.dictionary.locale_overrides = vec![
+            crate::config_schema::LocaleOverride { files: vec!["docs/**".to_string()], locale: "en-GB".to_string() },
+            crate::config_schema::LocaleOverride { files: vec!["docs/us/**".to_string()], locale: "en-US".to_string() },
+        ];
+        let dict = Dictionary::from_config(&config.dictionary)?;
+        let engine = Engine::try_new(config, dict)?;
+
+        let words = |path: &str| -> Vec<String> {
+            engine.check_text(Path::new(path), "The colour and the color.\n").into_iter().map(|e| e.word).collect()
+        };
+        assert_eq!(words("src/lib.txt"), vec!["colour"]);
+        assert_eq!(words("./docs/guide.md"), vec!["color"]);
+        assert_eq!(words("docs/us/guide.md"), vec!["colour"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overrides() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [[overrides]]
+            files = ["migrations/**"]
+            dictionary.extra_words = ["upsert"]
+            ignore.words = ["ALTER"]
+            ignore.patterns = ['V\d+__\w+']
+            strings = false
+
+            [[overrides]]
+            files = ["scripts/*.run"]
+            language = "python"
+
+            [[overrides]]
+            files = ["src/**"]
+            language = "text"
+            "#,
+        )?;
+        let mut dict = Dictionary::new();
+        dict.add_words(["the", "a", "table", "fn", "main", "let", "x"]);
+        let engine = Engine::try_new(config, dict)?;
+
+        let words = |path: &str, content: &str| -> Vec<String> {
+            engine.check_text(Path::new(path), content).into_iter().map(|e| e.word).collect()
+        };
+        let sql = "// upsert the table\nlet x = \"alter teh table\";\n";
+        assert_eq!(words("src/upsert.rs", "fn main() {}\n// upsert\n"), vec!["upsert"]);
+        assert!(words("migrations/001.rs", sql).is_empty());
+        assert_eq!(words("other/001.rs", sql), vec!["upsert", "alter", "teh"]);
+        assert!(words("migrations/002.sql", "the V2__add_tabel\n").is_empty());
+        assert_eq!(words("other/002.sql", "the V2__add_tabel\n"), vec!["add", "tabel"]);
+        assert_eq!(words("scripts/build.run", "teh = 1  # a tabel\n"), vec!["tabel"]);
+
+        let invalid: Config = toml::from_str("[[overrides]]\nfiles = [\"*\"]\nlocale = \"fr\"\n")?;
+        assert!(Engine::try_new(invalid, Dictionary::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_markup_positions() -> anyhow::Result<()> {
+        let mut dict = Dictionary::new();
+        dict.add_words(["café", "menu"]);
+        let engine = Engine::try_new(Config::default(), dict)?;
+
+        let content = "<ul class=\"menu\">\n  <li title=\"Caf&eacute; mnu\">caf&eacute; &amp; wrold&shy;s</li>\n</ul>\n";
+        let errors = engine.check_text(Path::new("site/index.html"), content);
+        let found: Vec<(&str, usize, usize, &str)> =
+            errors.iter().map(|e| (e.word.as_str(), e.line, e.col, &content[e.span.clone()])).collect();
+        assert_eq!(found, vec![("mnu", 2, 26, "mnu"), ("wrolds", 2, 49, "wrold&shy;s")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_document_formats() -> anyhow::Result<()> {
+        let mut dict = Dictionary::new();
+        dict.add_words(["see", "and", "for", "details", "use", "section"]);
+        let engine = Engine::try_new(Config::default(), dict)?;
+        let found = |path: &str, content: &str| -> Vec<(String, usize, usize)> {
+            engine.check_text(Path::new(path), content).into_iter().map(|e| (e.word, e.line, e.col)).collect()
+        };
+
+        let tex = "\\section{Usge}\nSee \\ref{fig:xyzzy} and $\\alpha$ for detials.\n";
+        assert_eq!(found("paper.tex", tex), vec![("Usge".into(), 1, 10), ("detials".into(), 2, 38)]);
+        let rst = "Use :func:`frobnicate` for ``clientt`` detials.\n";
+        assert_eq!(found("docs/index.rst", rst), vec![("detials".into(), 1, 40)]);
+        let adoc = "= Section\n:toc: lft\n\nUse `clientt` for detials.\n";
+        assert_eq!(found("guide.adoc", adoc), vec![("detials".into(), 4, 19)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_notebook_positions() -> anyhow::Result<()> {
+        let mut dict = Dictionary::new();
+        dict.add_words(["a", "title", "print", "hello"]);
+        let engine = Engine::try_new(Config::default(), dict)?;
+
+        let content = "{\"cells\": [\n {\"cell_type\": \"markdown\", \"source\": [\"# A title\\n\", \"Tpyo\"]},\n \
+            {\"cell_type\": \"code\", \"outputs\": [\"helo\"], \"source\": \"print(\\\"hello wrold\\\")\"}\n]}\n";
+        let errors = engine.check_text(Path::new("analysis.ipynb"), content);
+        let found: Vec<(&str, usize, usize)> = errors.iter().map(|e| (e.word.as_str(), e.line, e.col)).collect();
+        assert_eq!(found, vec![("Tpyo", 2, 55), ("wrold", 3, 70)]);
+        let cells: Vec<(usize, usize, usize)> =
+            errors.iter().filter_map(|e| e.cell.as_ref()).map(|c| (c.index, c.line, c.col)).collect();
+        assert_eq!(cells, vec![(1, 2, 1), (2, 1, 14)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_files() -> anyhow::Result<()> {
+        let mut dict = Dictionary::new();
+        dict.add_words(["a", "fast", "spell", "checker", "tab", "here"]);
+        let mut config = Config::default();
+        config.languages.toml.include_keys = vec!["package.description".into()];
+        let engine = Engine::try_new(config, dict)?;
+        let found = |path: &str, content: &str| -> Vec<(String, usize, usize)> {
+            engine.check_text(Path::new(path), content).into_iter().map(|e| (e.word, e.line, e.col)).collect()
+        };
+
+        let cargo = "[package]\nname = \"spelchek\"\ndescription = \"A fast spell chekcer\"\n";
+        assert_eq!(found("Cargo.toml", cargo), vec![("chekcer".into(), 3, 29)]);
+        let package = "{\n  \"name\": \"spelchek\",\n  \"description\": \"A\\tfast chekcer\"\n}\n";
+        assert_eq!(found("package.json", package), vec![("spelchek".into(), 2, 12), ("chekcer".into(), 3, 27)]);
+        let yaml = "spelchek:\n  - tab here  # a commnt\n  - \"vaule\"\n";
+        assert_eq!(found("config.yml", yaml), vec![("commnt".into(), 2, 19), ("vaule".into(), 3, 6)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_scopes() -> anyhow::Result<()> {
+        let engine_for = |words: &[&str], exclude: &[&str]| -> anyhow::Result<(Config, Dictionary)> {
+            let mut config = Config::default();
+            config.files.exclude = exclude.iter().map(|p| p.to_string()).collect();
+            let mut dict = Dictionary::new();
+            dict.add_words(words.iter().copied());
+            Ok((config, dict))
+        };
+        let (config, dict) = engine_for(&["the"], &[])?;
+        let mut engine = Engine::try_new(config, dict)?;
+        let (config, dict) = engine_for(&["the", "kubectl"], &["docs/drafts/**"])?;
+        engine.add_scope(PathBuf::from("docs"), config, dict)?;
+
+        let words = |path: &str| -> Vec<String> {
+            engine.check_text(Path::new(path), "the kubectl\n").into_iter().map(|e| e.word).collect()
+        };
+        assert_eq!(words("README.md"), vec!["kubectl"]);
+        assert!(words("./docs/guide.md").is_empty());
+        assert_eq!(words("docsite/guide.md"), vec!["kubectl"]);
+        assert!(engine.should_check(Path::new("drafts/a.md")));
+        assert!(!engine.should_check(Path::new("docs/drafts/a.md")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_default_config_checks_document_formats() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let files = [
+            ("index.html", "<p title=\"Hello\">Hello wrold</p>\n"),
+            ("logo.svg", "<svg><title>Logo tpyo</title></svg>\n"),
+            ("guide.rst", "Use ``clientt`` for detials.\n"),
+            ("guide.adoc", "= Guide\n\nUse `clientt` for detials.\n"),
+            ("paper.tex", "\\section{Usge}\n"),
+            ("analysis.ipynb", "{\"cells\": [{\"cell_type\": \"markdown\", \"source\": [\"Notebok\"]}]}\n"),
+            ("package.json", "{\"description\": \"Hello jsn\"}\n"),
+            ("package-lock.json", "{\"lockfileVersion\": \"lokc\"}\n"),
+            ("config.yml", "title: Hello yml\n"),
+            ("Cargo.toml", "[package]\ndescription = \"Hello tmol\"\n"),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content)?;
+        }
+
+        let mut dict = Dictionary::new();
+        dict.add_words(["hello", "logo", "use", "for", "guide"]);
+        let engine = Engine::try_new(Config::default(), dict)?;
+        let mut rx = engine.run(dir.path().to_path_buf());
+        let mut found = Vec::new();
+        while let Some(res) = rx.recv().await {
+            found.push(res.map_err(anyhow::Error::msg)?.word);
+        }
+        found.sort();
+        assert_eq!(found, vec!["Notebok", "Usge", "detials", "detials", "jsn", "tmol", "tpyo", "wrold", "yml"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_replays_unchanged_files() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().join("project");
+        std::fs::create_dir(&root)?;
+        std::fs::write(root.join("a.txt"), "teh cat\n")?;
+        let cache_dir = dir.path().join("cache");
+
+        async fn words(engine: &Engine, root: &Path) -> anyhow::Result<Vec<String>> {
+            let mut rx = engine.run(root.to_path_buf());
+            let mut found = Vec::new();
+            while let Some(res) = rx.recv().await {
+                found.push(res.map_err(anyhow::Error::msg)?.word);
+            }
+            Ok(found)
+        }
+
+        let mut dict = Dictionary::new();
+        dict.add_word("cat");
+        let engine = Engine::try_new(Config::default(), dict)?.with_cache(Some(cache_dir.clone()));
+        assert_eq!(words(&engine, &root).await?, vec!["teh"]);
+
+        // The same settings replay the cached result, so an edit to it shows.
+        let results = cache_dir.join("results.json");
+        std::fs::write(&results, std::fs::read_to_string(&results)?.replace("\"teh\"", "\"tea\""))?;
+        let mut dict = Dictionary::new();
+        dict.add_word("cat");
+        let engine = Engine::try_new(Config::default(), dict)?.with_cache(Some(cache_dir.clone()));
+        assert_eq!(words(&engine, &root).await?, vec!["tea"]);
+
+        // A dictionary that knows the word misses the cache.
+        let mut dict = Dictionary::new();
+        dict.add_words(["cat", "teh"]);
+        let engine = Engine::try_new(Config::default(), dict)?.with_cache(Some(cache_dir.clone()));
+        assert!(words(&engine, &root).await?.is_empty());
+
+        // Changing the file invalidates its entry.
+        std::fs::write(root.join("a.txt"), "teh cat tehh\n")?;
+        assert_eq!(words(&engine, &root).await?, vec!["tehh"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identifier_subword_columns() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("notes.txt"), "fn recieveMessage(max_retry_count: u32) {}\n")?;
+
+        let mut dict = Dictionary::new();
+        dict.add_words(["fn", "message", "max", "retry", "count"]);
+        let engine = Engine::try_new(Config::default(), dict)?;
+
+        let mut rx = engine.run(dir.path().to_path_buf());
+        let mut found = Vec::new();
+        while let Some(res) = rx.recv().await {
+            let error = res.map_err(anyhow::Error::msg)?;
+            found.push((error.col, error.span, error.word));
+        }
+        assert_eq!(found, vec![(4, 3..10, "recieve".to_string())]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_source_files_check_only_comments_and_strings() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("main.rs"),
+            "/// Entry pointt.\nfn main() {\n    let naem = \"helo\"; // fine\n}\n",
+        )?;
+
+        let mut dict = Dictionary::new();
+        dict.add_words(["entry", "fine"]);
+        let mut config = Config::default();
+        config.languages.rust.doc_comments = false;
+        let engine = Engine::try_new(config, dict)?;
+
+        let mut rx = engine.run(dir.path().to_path_buf());
+        let mut found = Vec::new();
+        while let Some(res) = rx.recv().await {
+            let error = res.map_err(anyhow::Error::msg)?;
+            found.push((error.line, error.col, error.word));
+        }
+        assert_eq!(found, vec![(3, 17, "helo".to_string())]);
+        Ok(())
+    }
 }
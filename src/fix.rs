@@ -0,0 +1,223 @@
+//! Automatic correction of spelling errors.
+//!
+//! A `SpellError` is only fixed when its correction is unambiguous: exactly one
+//! suggestion has the best `suggest::score`, which weighs edit distance,
+//! transpositions and word frequency. Replacements keep the casing
+//! of the original word and are applied by byte span, so several errors on the
+//! same line can be fixed in one pass.
+
+use crate::engine::SpellError;
+use crate::suggest;
+use std::ops::Range;
+
+/// A single replacement to apply to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// The byte range to replace.
+    pub span: Range<usize>,
+    /// The text that the range is replaced with.
+    pub replacement: String,
+}
+
+/// Returns the correction for an error, if one is unambiguous.
+///
+/// The correction is cased like the original word (`Referance` → `Reference`).
+pub fn correction(error: &SpellError) -> Option<String> {
+    let word = error.word.to_lowercase();
+    let mut scores: Vec<_> = error.suggestions.iter().map(|s| (suggest::score(&word, s), s)).collect();
+    scores.sort();
+
+    match scores.as_slice() {
+        [(_, best)] => Some(match_case(&error.word, best)),
+        [(best_score, best), (next_score, _), ..] if best_score < next_score => Some(match_case(&error.word, best)),
+        _ => None,
+    }
+}
+
+/// Plans the edits that fix the given errors in `content`.
+///
+/// Errors without an unambiguous correction are left alone, as are errors whose
+/// span no longer holds the reported word (e.g. the file changed since the check).
+pub fn plan(content: &str, errors: &[SpellError]) -> Vec<Edit> {
+    errors
+        .iter()
+        .filter(|e| content.get(e.span.clone()) == Some(e.word.as_str()))
+        .filter_map(|e| {
+            correction(e).map(|replacement| Edit { span: e.span.clone(), replacement })
+        })
+        .collect()
+}
+
+/// Re-cases `replacement` to follow the casing pattern of `original`.
+///
+/// All-uppercase words stay uppercase, capitalized words stay capitalized and
+/// anything else becomes lowercase.
+pub fn match_case(original: &str, replacement: &str) -> String {
+    let mut letters = original.chars().filter(|c| c.is_alphabetic());
+    let first_upper = letters.next().is_some_and(char::is_uppercase);
+    let rest: Vec<char> = letters.collect();
+
+    if first_upper && !rest.is_empty() && rest.iter().all(|c| c.is_uppercase()) {
+        replacement.to_uppercase()
+    } else if first_upper {
+        let mut chars = replacement.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    } else {
+        replacement.to_lowercase()
+    }
+}
+
+/// Applies edits to `content`, returning the new text and the number of
+/// edits applied.
+///
+/// Edits are applied by byte span in order; an edit overlapping an earlier one,
+/// or whose span does not fall on character boundaries, is skipped.
+pub fn apply(content: &str, edits: &[Edit]) -> (String, usize) {
+    let mut edits: Vec<&Edit> = edits.iter().collect();
+    edits.sort_by_key(|e| e.span.start);
+
+    let mut result = String::with_capacity(content.len());
+    let mut pos = 0;
+    let mut applied = 0;
+    for edit in edits {
+        let Range { start, end } = edit.span;
+        if start < pos || content.get(start..end).is_none() {
+            continue;
+        }
+        result.push_str(&content[pos..start]);
+        result.push_str(&edit.replacement);
+        pos = end;
+        applied += 1;
+    }
+    result.push_str(&content[pos..]);
+    (result, applied)
+}
+
+/// Renders a unified diff between two versions of a file.
+///
+/// Fixes never add or remove lines, so lines are compared pairwise and each
+/// changed line is shown with up to three lines of context.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    const CONTEXT: usize = 3;
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let changed: Vec<usize> = (0..old_lines.len().max(new_lines.len()))
+        .filter(|&i| old_lines.get(i) != new_lines.get(i))
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Group changed lines whose context windows touch into hunks.
+    let mut hunks: Vec<Range<usize>> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(old_lines.len().max(new_lines.len()));
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+
+    let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+    for hunk in hunks {
+        let len = hunk.len();
+        out.push_str(&format!("@@ -{},{len} +{},{len} @@\n", hunk.start + 1, hunk.start + 1));
+        for i in hunk {
+            match (old_lines.get(i), new_lines.get(i)) {
+                (Some(a), Some(b)) if a == b => out.push_str(&format!(" {a}\n")),
+                (a, b) => {
+                    if let Some(a) = a {
+                        out.push_str(&format!("-{a}\n"));
+                    }
+                    if let Some(b) = b {
+                        out.push_str(&format!("+{b}\n"));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn error(word: &str, suggestions: &[&str]) -> SpellError {
+        SpellError {
+            file: PathBuf::from("a.md"),
+            line: 1,
+            col: 1,
+            span: 0..word.len(),
+            word: word.to_string(),
+            context: word.to_string(),
+            suggestions: suggestions.iter().map(|s| s.to_string()).collect(),
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_correction() {
+        assert_eq!(correction(&error("Referance", &["reference", "refinance"])), Some("Reference".to_string()));
+        assert_eq!(correction(&error("recieve", &["receive", "relieve"])), Some("receive".to_string()));
+        assert_eq!(correction(&error("Teh", &["tch", "tea", "the"])), Some("The".to_string()));
+        assert_eq!(correction(&error("frobnacate", &["frobnicate", "frobnecate"])), None);
+        assert_eq!(correction(&error("xyzzy", &[])), None);
+    }
+
+    #[test]
+    fn test_correction_with_embedded_dictionary() -> anyhow::Result<()> {
+        let dict = crate::dictionary::Dictionary::from_config(&crate::config_schema::DictionaryConfig::default())?;
+        let fixed = |word: &str| correction(&error(word, &dict.suggest(word, 5).iter().map(String::as_str).collect::<Vec<_>>()));
+        assert_eq!(fixed("teh"), Some("the".to_string()));
+        assert_eq!(fixed("Recieve"), Some("Receive".to_string()));
+        assert_eq!(fixed("wrold"), Some("world".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_skips_stale_spans() {
+        let content = "Teh end";
+        let stale = SpellError { span: 4..7, ..error("teh", &["the"]) };
+        let edits = plan(content, &[error("Teh", &["the"]), stale]);
+        assert_eq!(edits, vec![Edit { span: 0..3, replacement: "The".to_string() }]);
+    }
+
+    #[test]
+    fn test_match_case() {
+        assert_eq!(match_case("Teh", "the"), "The");
+        assert_eq!(match_case("TEH", "the"), "THE");
+        assert_eq!(match_case("teh", "the"), "the");
+    }
+
+    #[test]
+    fn test_apply_multiple_edits_on_one_line() {
+        let content = "teh cat and teh dog";
+        let edits = vec![
+            Edit { span: 12..15, replacement: "the".to_string() },
+            Edit { span: 0..3, replacement: "The".to_string() },
+        ];
+        assert_eq!(apply(content, &edits), ("The cat and the dog".to_string(), 2));
+    }
+
+    #[test]
+    fn test_apply_counts_skipped_edits() {
+        let edits = vec![
+            Edit { span: 0..3, replacement: "The".to_string() },
+            Edit { span: 1..3, replacement: "he".to_string() },
+        ];
+        assert_eq!(apply("teh cat", &edits), ("The cat".to_string(), 1));
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = unified_diff("a.md", "one\nteh\nthree\n", "one\nthe\nthree\n");
+        assert_eq!(diff, "--- a/a.md\n+++ b/a.md\n@@ -1,3 +1,3 @@\n one\n-teh\n+the\n three\n");
+    }
+}
@@ -0,0 +1,314 @@
+//! Selecting files and lines changed in a git repository.
+//!
+//! Changes are read from the local repository by running `git`; nothing is
+//! fetched. Paths are resolved against the top level of the work tree.
+
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The object name of the empty tree, used as the base before the first commit.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Which changes to select.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// Staged and unstaged changes against `HEAD`, plus untracked files.
+    WorkingTree,
+    /// Changes staged in the index.
+    Staged,
+    /// Changes since the merge base of a ref and `HEAD`, including uncommitted ones.
+    Since(String),
+}
+
+/// The part of a changed file to check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changed {
+    /// Every line, e.g. for untracked files.
+    All,
+    /// Only these 1-based line ranges of the new content.
+    Lines(Vec<Range<usize>>),
+}
+
+impl Changed {
+    /// Returns `true` if the 1-based `line` is part of the change.
+    pub fn contains_line(&self, line: usize) -> bool {
+        match self {
+            Changed::All => true,
+            Changed::Lines(ranges) => ranges.iter().any(|range| range.contains(&line)),
+        }
+    }
+}
+
+/// The set of changed files, keyed by canonical absolute path.
+#[derive(Debug, Default)]
+pub struct ChangedFiles {
+    files: HashMap<PathBuf, Changed>,
+    /// Every directory containing a changed file, so walks can skip the rest.
+    dirs: HashSet<PathBuf>,
+}
+
+impl ChangedFiles {
+    /// Reads the changes selected by `selection` from the repository containing `path`.
+    ///
+    /// With `lines_only`, files are limited to the lines added or modified by
+    /// the diff; otherwise changed files are checked in full.
+    pub fn load(path: &Path, selection: &Selection, lines_only: bool) -> Result<Self> {
+        let dir = if path.is_dir() { path } else { path.parent().unwrap_or(Path::new(".")) };
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        let toplevel = git(dir, &["rev-parse", "--show-toplevel"])
+            .with_context(|| format!("{} is not inside a git repository", path.display()))?;
+        let root = PathBuf::from(String::from_utf8_lossy(&toplevel).trim_end());
+        let root = root.canonicalize().with_context(|| format!("Failed to resolve {}", root.display()))?;
+
+        let base = match selection {
+            Selection::WorkingTree => {
+                let head = git(&root, &["rev-parse", "--verify", "--quiet", "HEAD"]);
+                Some(head.map_or(EMPTY_TREE.to_string(), |_| "HEAD".to_string()))
+            }
+            Selection::Staged => None,
+            Selection::Since(reference) => {
+                let base = git(&root, &["merge-base", reference, "HEAD"])
+                    .with_context(|| format!("Failed to find the merge base of '{}' and HEAD", reference))?;
+                Some(String::from_utf8_lossy(&base).trim_end().to_string())
+            }
+        };
+        let mut diff_args = vec!["-c", "core.quotePath=false", "diff", "--no-ext-diff", "--no-color"];
+        match &base {
+            Some(base) => diff_args.push(base),
+            None => diff_args.push("--cached"),
+        }
+
+        let mut changes = Self::default();
+        if lines_only {
+            diff_args.extend(["-U0", "--src-prefix=a/", "--dst-prefix=b/"]);
+            for (file, lines) in parse_hunks(&String::from_utf8_lossy(&git(&root, &diff_args)?)) {
+                changes.insert(root.join(file), Changed::Lines(lines));
+            }
+        } else {
+            diff_args.extend(["--name-only", "-z", "--diff-filter=d"]);
+            for file in parse_name_list(&git(&root, &diff_args)?) {
+                changes.insert(root.join(file), Changed::All);
+            }
+        }
+        if *selection == Selection::WorkingTree {
+            let untracked = git(&root, &["ls-files", "--others", "--exclude-standard", "-z"])?;
+            for file in parse_name_list(&untracked) {
+                changes.insert(root.join(file), Changed::All);
+            }
+        }
+        Ok(changes)
+    }
+
+    fn insert(&mut self, path: PathBuf, changed: Changed) {
+        let mut dir = path.parent();
+        while let Some(d) = dir {
+            if !self.dirs.insert(d.to_path_buf()) {
+                break;
+            }
+            dir = d.parent();
+        }
+        self.files.insert(path, changed);
+    }
+
+    /// Returns the number of changed files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns what changed in the file at the canonical `path`, if anything.
+    pub fn get(&self, path: &Path) -> Option<&Changed> {
+        self.files.get(path)
+    }
+
+    /// Returns `true` if the directory at the canonical `path` contains a changed file.
+    pub fn contains_dir(&self, path: &Path) -> bool {
+        self.dirs.contains(path)
+    }
+}
+
+/// Runs `git` in `dir` and returns its standard output.
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(output.stdout)
+}
+
+/// Splits NUL-separated `git` output into paths.
+fn parse_name_list(output: &[u8]) -> Vec<PathBuf> {
+    output
+        .split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| PathBuf::from(String::from_utf8_lossy(name).into_owned()))
+        .collect()
+}
+
+/// Collects the added line ranges of each file from a `git diff -U0` patch.
+///
+/// Deleted files are skipped; files with only deletions get no ranges.
+fn parse_hunks(patch: &str) -> Vec<(PathBuf, Vec<Range<usize>>)> {
+    let mut files: Vec<(PathBuf, Vec<Range<usize>>)> = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut lines = patch.lines();
+    while let Some(line) = lines.next() {
+        if let Some(name) = line.strip_prefix("+++ ") {
+            current = unquote(name).strip_prefix("b/").map(|name| {
+                files.push((PathBuf::from(name), Vec::new()));
+                files.len() - 1
+            });
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            let Some((old, new)) = parse_hunk_header(header) else { continue };
+            if let Some(index) = current
+                && new.1 > 0
+            {
+                files[index].1.push(new.0..new.0 + new.1);
+            }
+            // Skip the hunk body so added lines starting with "+++" are not
+            // mistaken for file headers.
+            let mut remaining = old + new.1;
+            while remaining > 0 {
+                match lines.next() {
+                    Some(body) if body.starts_with('\\') => {}
+                    Some(_) => remaining -= 1,
+                    None => break,
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Parses `-a,b +c,d @@`, returning the old line count and the new start and count.
+fn parse_hunk_header(header: &str) -> Option<(usize, (usize, usize))> {
+    let mut parts = header.split(' ');
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let range = |spec: &str| -> Option<(usize, usize)> {
+        match spec.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((spec.parse().ok()?, 1)),
+        }
+    };
+    Some((range(old)?.1, range(new)?))
+}
+
+/// Undoes the C-style quoting git applies to unusual file names.
+fn unquote(name: &str) -> String {
+    let Some(inner) = name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) else {
+        return name.to_string();
+    };
+    let mut bytes = Vec::new();
+    let mut chars = inner.bytes().peekable();
+    while let Some(b) = chars.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(d @ b'0'..=b'7') => {
+                let mut value = u32::from(d - b'0');
+                for _ in 0..2 {
+                    if let Some(&d @ b'0'..=b'7') = chars.peek() {
+                        value = value * 8 + u32::from(d - b'0');
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_hunks() {
+        let patch = "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -3 +3,2 @@ fn main() {
+-old
++++ new line that looks like a header
++another
+@@ -10,2 +11,0 @@
+-gone
+-gone too
+diff --git a/removed.md b/removed.md
+--- a/removed.md
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git \"a/caf\\303\\251.md\" \"b/caf\\303\\251.md\"
+--- /dev/null
++++ \"b/caf\\303\\251.md\"
+@@ -0,0 +1 @@
++hello
+\\ No newline at end of file
+";
+        assert_eq!(
+            parse_hunks(patch),
+            vec![
+                (PathBuf::from("src/a.rs"), vec![3..5]),
+                (PathBuf::from("café.md"), vec![1..2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_from_repository() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path();
+        let run = |args: &[&str]| git(root, args).map(|_| ());
+        run(&["init", "-q"])?;
+        fs::create_dir(root.join("docs"))?;
+        fs::write(root.join("docs/guide.md"), "one\ntwo\nthree\n")?;
+        fs::write(root.join("old.md"), "old\n")?;
+        run(&["add", "."])?;
+        run(&["-c", "user.name=Test", "-c", "user.email=test@example.com", "commit", "-q", "-m", "init"])?;
+
+        fs::write(root.join("docs/guide.md"), "one\n2\nthree\n")?;
+        fs::write(root.join("new.md"), "new\n")?;
+        let canonical = root.canonicalize()?;
+
+        let changes = ChangedFiles::load(root, &Selection::WorkingTree, true)?;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes.get(&canonical.join("docs/guide.md")), Some(&Changed::Lines(vec![2..3])));
+        assert_eq!(changes.get(&canonical.join("new.md")), Some(&Changed::All));
+        assert!(changes.get(&canonical.join("old.md")).is_none());
+        assert!(changes.contains_dir(&canonical.join("docs")));
+
+        assert!(ChangedFiles::load(root, &Selection::Staged, false)?.is_empty());
+        run(&["add", "docs"])?;
+        let staged = ChangedFiles::load(root, &Selection::Staged, false)?;
+        assert_eq!(staged.get(&canonical.join("docs/guide.md")), Some(&Changed::All));
+        assert_eq!(staged.len(), 1);
+
+        let since = ChangedFiles::load(root, &Selection::Since("HEAD".to_string()), false)?;
+        assert_eq!(since.len(), 1);
+        Ok(())
+    }
+}
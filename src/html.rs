@@ -0,0 +1,343 @@
+//! HTML and XML text extraction.
+//!
+//! Tag names, attribute names and markup are not prose. This module finds the
+//! text nodes of an HTML, XML or SVG document and the values of the attributes
+//! people read, such as `alt` and `title`. The contents of `<script>`,
+//! `<style>`, `<code>` and `<pre>` elements are skipped. Character references
+//! are left in place and decoded with `entities` after masking.
+
+use crate::lexer::{Region, RegionKind};
+use std::ops::Range;
+
+/// Elements whose content is never checked.
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "code", "pre"];
+
+/// The attributes checked by default.
+pub const DEFAULT_ATTRIBUTES: &[&str] = &["alt", "title", "aria-label", "placeholder"];
+
+/// Named character references, besides the accented Latin letters.
+const NAMED: &[(&str, &str)] = &[
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", "\u{a0}"),
+    ("shy", ""),
+    ("zwnj", ""),
+    ("zwj", ""),
+    ("ensp", "\u{2002}"),
+    ("emsp", "\u{2003}"),
+    ("thinsp", "\u{2009}"),
+    ("ndash", "–"),
+    ("mdash", "—"),
+    ("lsquo", "‘"),
+    ("rsquo", "’"),
+    ("sbquo", "‚"),
+    ("ldquo", "“"),
+    ("rdquo", "”"),
+    ("bdquo", "„"),
+    ("laquo", "«"),
+    ("raquo", "»"),
+    ("lsaquo", "‹"),
+    ("rsaquo", "›"),
+    ("hellip", "…"),
+    ("bull", "•"),
+    ("middot", "·"),
+    ("copy", "©"),
+    ("reg", "®"),
+    ("trade", "™"),
+    ("deg", "°"),
+    ("times", "×"),
+    ("divide", "÷"),
+    ("plusmn", "±"),
+    ("para", "¶"),
+    ("sect", "§"),
+    ("euro", "€"),
+    ("pound", "£"),
+    ("yen", "¥"),
+    ("cent", "¢"),
+    ("dagger", "†"),
+    ("Dagger", "‡"),
+    ("larr", "←"),
+    ("rarr", "→"),
+    ("iexcl", "¡"),
+    ("iquest", "¿"),
+    ("szlig", "ß"),
+    ("AElig", "Æ"),
+    ("aelig", "æ"),
+    ("OElig", "Œ"),
+    ("oelig", "œ"),
+    ("Oslash", "Ø"),
+    ("oslash", "ø"),
+];
+
+/// Accented letters as (suffix, base letters, composed letters), e.g. `eacute`.
+const ACCENTS: &[(&str, &str, &str)] = &[
+    ("grave", "AEIOUaeiou", "ÀÈÌÒÙàèìòù"),
+    ("acute", "AEIOUYaeiouy", "ÁÉÍÓÚÝáéíóúý"),
+    ("circ", "AEIOUaeiou", "ÂÊÎÔÛâêîôû"),
+    ("tilde", "ANOano", "ÃÑÕãñõ"),
+    ("uml", "AEIOUaeiouy", "ÄËÏÖÜäëïöüÿ"),
+    ("ring", "Aa", "Åå"),
+    ("cedil", "Cc", "Çç"),
+];
+
+/// Extracts the text nodes, comments and checked attribute values of an HTML
+/// or XML document.
+///
+/// Text and CDATA sections become `Text` regions, attribute values `String`
+/// regions and comments `Comment` regions. HTML element and attribute names
+/// are compared case-insensitively; XML ones exactly, ignoring any namespace
+/// prefix on attributes.
+pub fn extract_regions(content: &str, xml: bool, attributes: &[String]) -> Vec<Region> {
+    Scanner { src: content.as_bytes(), pos: 0, xml, attributes, regions: Vec::new() }.run()
+}
+
+struct Scanner<'a> {
+    src: &'a [u8],
+    pos: usize,
+    xml: bool,
+    attributes: &'a [String],
+    regions: Vec<Region>,
+}
+
+impl Scanner<'_> {
+    fn run(mut self) -> Vec<Region> {
+        let mut text_start = 0;
+        while self.pos < self.src.len() {
+            if self.src[self.pos] != b'<' || !self.markup_starts() {
+                self.pos += 1;
+                continue;
+            }
+            self.push(RegionKind::Text, text_start..self.pos);
+            self.markup();
+            text_start = self.pos;
+        }
+        self.push(RegionKind::Text, text_start..self.src.len());
+        self.regions
+    }
+
+    fn starts_with(&self, pat: &str) -> bool {
+        self.src[self.pos..].starts_with(pat.as_bytes())
+    }
+
+    /// Returns the position of the next `pat` at or after `from`.
+    fn find(&self, from: usize, pat: &str) -> Option<usize> {
+        let from = from.min(self.src.len());
+        self.src[from..].windows(pat.len()).position(|w| w == pat.as_bytes()).map(|p| from + p)
+    }
+
+    fn push(&mut self, kind: RegionKind, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region { kind, range });
+        }
+    }
+
+    /// Returns `true` if the `<` at the current position starts markup rather
+    /// than being a literal character, as in `a < b`.
+    fn markup_starts(&self) -> bool {
+        self.src.get(self.pos + 1).is_some_and(|&b| b.is_ascii_alphabetic() || matches!(b, b'/' | b'!' | b'?'))
+    }
+
+    fn markup(&mut self) {
+        if self.starts_with("<!--") {
+            let end = self.find(self.pos + 4, "-->").map_or(self.src.len(), |c| c + 3);
+            self.push(RegionKind::Comment, self.pos..end);
+            self.pos = end;
+        } else if self.starts_with("<![CDATA[") {
+            let start = self.pos + 9;
+            let end = self.find(start, "]]>").unwrap_or(self.src.len());
+            self.push(RegionKind::Text, start..end);
+            self.pos = (end + 3).min(self.src.len());
+        } else if self.starts_with("<?") {
+            self.pos = self.find(self.pos + 2, "?>").map_or(self.src.len(), |c| c + 2);
+        } else if self.starts_with("<!") || self.starts_with("</") {
+            self.pos = self.find(self.pos, ">").map_or(self.src.len(), |c| c + 1);
+        } else {
+            self.tag();
+        }
+    }
+
+    fn name_end(&self, from: usize) -> usize {
+        from + self.src[from..].iter().take_while(|&&b| !(b.is_ascii_whitespace() || matches!(b, b'>' | b'/' | b'=' | b'<'))).count()
+    }
+
+    /// Consumes a start tag, recording checked attribute values and skipping
+    /// the content of skipped elements.
+    fn tag(&mut self) {
+        let name_start = self.pos + 1;
+        let name_end = self.name_end(name_start);
+        let name = String::from_utf8_lossy(&self.src[name_start..name_end]).into_owned();
+        self.pos = name_end;
+
+        let mut self_closing = false;
+        while self.pos < self.src.len() {
+            match self.src[self.pos] {
+                b'>' => {
+                    self.pos += 1;
+                    break;
+                }
+                b'/' => {
+                    self_closing = true;
+                    self.pos += 1;
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => {
+                    self_closing = false;
+                    self.attribute();
+                }
+            }
+        }
+
+        let skipped = SKIPPED_ELEMENTS.iter().any(|e| self.names_match(e, &name));
+        if skipped && !self_closing {
+            let close = format!("</{}", name);
+            self.pos = if self.xml {
+                self.find(self.pos, &close)
+            } else {
+                let lower = close.to_ascii_lowercase();
+                let rest = self.src[self.pos..].to_ascii_lowercase();
+                rest.windows(lower.len()).position(|w| w == lower.as_bytes()).map(|p| self.pos + p)
+            }
+            .unwrap_or(self.src.len());
+        }
+    }
+
+    /// Consumes one attribute, recording its value if it is checked.
+    fn attribute(&mut self) {
+        let name_start = self.pos;
+        let name_end = self.name_end(name_start).max(name_start + 1);
+        let name = String::from_utf8_lossy(&self.src[name_start..name_end]).into_owned();
+        self.pos = name_end;
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+        if self.src.get(self.pos) != Some(&b'=') {
+            return;
+        }
+        self.pos += 1;
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+        let value = match self.src.get(self.pos) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let start = self.pos + 1;
+                let end = self.find(start, if quote == b'"' { "\"" } else { "'" }).unwrap_or(self.src.len());
+                self.pos = (end + 1).min(self.src.len());
+                start..end
+            }
+            _ => {
+                let start = self.pos;
+                self.pos += self.src[start..].iter().take_while(|&&b| !(b.is_ascii_whitespace() || b == b'>')).count();
+                start..self.pos
+            }
+        };
+        let local = name.rsplit(':').next().unwrap_or(&name);
+        if self.attributes.iter().any(|a| self.names_match(a, &name) || self.names_match(a, local)) {
+            self.push(RegionKind::String, value);
+        }
+    }
+
+    fn names_match(&self, a: &str, b: &str) -> bool {
+        if self.xml { a == b } else { a.eq_ignore_ascii_case(b) }
+    }
+}
+
+/// Finds the character references in `text`, returning each with its decoded
+/// text. Unknown named references decode to a space so their names are not
+/// checked as words.
+pub fn entities(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut found = Vec::new();
+    let bytes = text.as_bytes();
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find('&') {
+        let start = pos + offset;
+        pos = start + 1;
+        let name_len = bytes[pos..].iter().take_while(|b| b.is_ascii_alphanumeric() || **b == b'#').count();
+        if name_len == 0 || bytes.get(pos + name_len) != Some(&b';') {
+            continue;
+        }
+        let name = &text[pos..pos + name_len];
+        let decoded = match name.strip_prefix('#') {
+            Some(number) => {
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                };
+                code.and_then(char::from_u32).map(String::from)
+            }
+            None => decode_named(name),
+        };
+        pos += name_len + 1;
+        found.push((start..pos, decoded.unwrap_or_else(|| " ".to_string())));
+    }
+    found
+}
+
+fn decode_named(name: &str) -> Option<String> {
+    if let Some((_, decoded)) = NAMED.iter().find(|(n, _)| *n == name) {
+        return Some(decoded.to_string());
+    }
+    ACCENTS.iter().find_map(|(suffix, bases, composed)| {
+        let base = name.strip_suffix(suffix).filter(|b| b.len() == 1)?;
+        let index = bases.find(base)?;
+        composed.chars().nth(index).map(String::from)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(content: &str, xml: bool) -> Vec<(RegionKind, &str)> {
+        let attributes: Vec<String> = DEFAULT_ATTRIBUTES.iter().map(|a| a.to_string()).collect();
+        extract_regions(content, xml, &attributes).into_iter().map(|r| (r.kind, &content[r.range])).collect()
+    }
+
+    #[test]
+    fn test_html_regions() {
+        let html = "<!DOCTYPE html>\n<p class=\"intro\" title='Greeting'>Hello <b>wrld</b> a < b</p>\
+            <img src=x.png ALT=Picture/><!-- note --><SCRIPT>let teh = 1;</script>\
+            <pre><b>x</b> codde</pre><input placeholder=\"Your name\" disabled>";
+        assert_eq!(
+            texts(html, false),
+            vec![
+                (RegionKind::Text, "\n"),
+                (RegionKind::String, "Greeting"),
+                (RegionKind::Text, "Hello "),
+                (RegionKind::Text, "wrld"),
+                (RegionKind::Text, " a < b"),
+                (RegionKind::String, "Picture/"),
+                (RegionKind::Comment, "<!-- note -->"),
+                (RegionKind::String, "Your name"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xml_regions() {
+        let svg = "<?xml version=\"1.0\"?>\n<svg xmlns:xlink=\"http://www.w3.org/1999/xlink\"><title>Diagrm</title>\
+            <a xlink:title=\"Link\" Title=\"no\"><text>Label</text></a><style>.cls { fill: red }</style>\
+            <desc><![CDATA[Raw & text]]></desc></svg>";
+        assert_eq!(
+            texts(svg, true),
+            vec![
+                (RegionKind::Text, "\n"),
+                (RegionKind::Text, "Diagrm"),
+                (RegionKind::String, "Link"),
+                (RegionKind::Text, "Label"),
+                (RegionKind::Text, "Raw & text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entities() {
+        let text = "caf&eacute; &amp; na&#239;ve&#x2019;s &shy;&bogus; & AT&T;";
+        let decoded: Vec<(&str, String)> =
+            entities(text).into_iter().map(|(range, decoded)| (&text[range], decoded)).collect();
+        let expected = [("&eacute;", "é"), ("&amp;", "&"), ("&#239;", "ï"), ("&#x2019;", "’"), ("&shy;", ""), ("&bogus;", " "), ("&T;", " ")];
+        assert_eq!(decoded, expected.map(|(reference, text)| (reference, text.to_string())));
+    }
+}
@@ -0,0 +1,727 @@
+//! Hunspell dictionary support.
+//!
+//! A Hunspell dictionary is a pair of files: the `.dic` file lists stems with
+//! the flags they accept, and the `.aff` file defines what those flags mean —
+//! prefixes and suffixes that may be attached, and which stems may be joined
+//! into compounds. `Hunspell::contains` strips affixes and splits compounds to
+//! find a stem that allows the word, like Hunspell's own checker.
+//!
+//! Only the parts of the format that decide whether a word is correct are
+//! supported. Suggestion tables (`REP`, `KEY`, `TRY`, ...), morphology and
+//! compound restrictions such as `CHECKCOMPOUNDDUP` are ignored. Everything
+//! is lowercased on load, since lookups are case-insensitive.
+
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// A flag as declared by `FLAG`: one character, two characters or a number.
+type Flag = u32;
+
+/// How flags are written in the `.aff` and `.dic` files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum FlagMode {
+    /// One character per flag (the default, and `FLAG UTF-8`).
+    #[default]
+    Char,
+    /// Two characters per flag (`FLAG long`).
+    Long,
+    /// Comma-separated numbers (`FLAG num`).
+    Num,
+}
+
+impl FlagMode {
+    fn parse(self, text: &str) -> Vec<Flag> {
+        match self {
+            Self::Char => text.chars().map(u32::from).collect(),
+            Self::Long => {
+                let chars: Vec<char> = text.chars().collect();
+                chars
+                    .chunks(2)
+                    .map(|pair| pair.iter().fold(0, |flag, &c| (flag << 16) | (u32::from(c) & 0xffff)))
+                    .collect()
+            }
+            Self::Num => text.split(',').filter_map(|n| n.trim().parse().ok()).collect(),
+        }
+    }
+}
+
+/// One element of an affix condition.
+#[derive(Debug, Clone)]
+enum CondChar {
+    /// `.` matches any character.
+    Any,
+    /// A character, or a `[...]` / `[^...]` set of characters.
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl CondChar {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// The condition a stem must meet for an affix to apply, such as `[^aeiou]y`.
+///
+/// Suffix conditions are matched against the end of the stem, prefix
+/// conditions against its start.
+#[derive(Debug, Clone, Default)]
+struct Condition(Vec<CondChar>);
+
+impl Condition {
+    fn parse(text: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => parts.push(CondChar::Any),
+                '[' => {
+                    let mut set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                    let negated = set.first() == Some(&'^');
+                    if negated {
+                        set.remove(0);
+                    }
+                    parts.push(CondChar::Set { chars: set, negated });
+                }
+                c => parts.push(CondChar::Set { chars: vec![c], negated: false }),
+            }
+        }
+        Self(parts)
+    }
+
+    fn matches_start(&self, stem: &str) -> bool {
+        let mut chars = stem.chars();
+        self.0.iter().all(|part| chars.next().is_some_and(|c| part.matches(c)))
+    }
+
+    fn matches_end(&self, stem: &str) -> bool {
+        let mut chars = stem.chars().rev();
+        self.0.iter().rev().all(|part| chars.next().is_some_and(|c| part.matches(c)))
+    }
+}
+
+/// A single `PFX` or `SFX` rule.
+#[derive(Debug, Clone)]
+struct Affix {
+    flag: Flag,
+    /// Whether the affix combines with affixes of the other kind.
+    cross_product: bool,
+    /// Text removed from the stem before `add` is attached.
+    strip: String,
+    add: String,
+    condition: Condition,
+    /// Flags of the affixed word, e.g. a further suffix that may follow.
+    continuation: Vec<Flag>,
+}
+
+impl Affix {
+    /// For a suffix, returns the stem `word` was derived from.
+    fn unsuffix(&self, word: &str) -> Option<String> {
+        let rest = word.strip_suffix(self.add.as_str())?;
+        if rest.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let stem = format!("{rest}{}", self.strip);
+        self.condition.matches_end(&stem).then_some(stem)
+    }
+
+    /// For a prefix, returns the stem `word` was derived from.
+    fn unprefix(&self, word: &str) -> Option<String> {
+        let rest = word.strip_prefix(self.add.as_str())?;
+        if rest.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{rest}", self.strip);
+        self.condition.matches_start(&stem).then_some(stem)
+    }
+
+    /// For a suffix, returns `stem` with the suffix attached, if it applies.
+    fn suffix(&self, stem: &str) -> Option<String> {
+        let rest = stem.strip_suffix(self.strip.as_str())?;
+        self.condition.matches_end(stem).then(|| format!("{rest}{}", self.add))
+    }
+
+    /// For a prefix, returns `stem` with the prefix attached, if it applies.
+    fn prefix(&self, stem: &str) -> Option<String> {
+        let rest = stem.strip_prefix(self.strip.as_str())?;
+        self.condition.matches_start(stem).then(|| format!("{}{rest}", self.add))
+    }
+}
+
+/// An element of a `COMPOUNDRULE` pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RulePart {
+    One(Flag),
+    Optional(Flag),
+    Repeated(Flag),
+}
+
+/// Flags with a special meaning, as declared in the `.aff` file.
+#[derive(Debug, Default)]
+struct SpecialFlags {
+    need_affix: Option<Flag>,
+    forbidden: Option<Flag>,
+    only_in_compound: Option<Flag>,
+    no_suggest: Option<Flag>,
+    compound: Option<Flag>,
+    compound_begin: Option<Flag>,
+    compound_middle: Option<Flag>,
+    compound_end: Option<Flag>,
+}
+
+/// Where a word sits within a compound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Begin,
+    Middle,
+    End,
+}
+
+/// One way of deriving a word from a stem.
+struct Analysis<'a> {
+    stem_flags: &'a [Flag],
+    /// Continuation flags of the affixes that were applied.
+    affix_flags: Vec<Flag>,
+    /// Whether the word still needs another affix (`NEEDAFFIX`).
+    needs_affix: bool,
+}
+
+impl Analysis<'_> {
+    fn has(&self, flag: Option<Flag>) -> bool {
+        flag.is_some_and(|f| self.stem_flags.contains(&f) || self.affix_flags.contains(&f))
+    }
+}
+
+/// A parsed Hunspell dictionary.
+#[derive(Debug, Default)]
+pub struct Hunspell {
+    /// Lowercased stems, each with the flag sets of its homonyms.
+    stems: HashMap<String, Vec<Vec<Flag>>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    flags: SpecialFlags,
+    compound_min: usize,
+    compound_word_max: Option<usize>,
+    compound_rules: Vec<Vec<RulePart>>,
+}
+
+impl Hunspell {
+    /// Loads a dictionary from its `.aff` and `.dic` files.
+    ///
+    /// Both files must use the encoding named by `SET` in the `.aff` file;
+    /// `UTF-8` (the default) and `ISO8859-1` are supported.
+    pub fn load(aff_path: &Path, dic_path: &Path) -> Result<Self> {
+        let aff = fs::read(aff_path).with_context(|| format!("Failed to read affix file at {:?}", aff_path))?;
+        let dic = fs::read(dic_path).with_context(|| format!("Failed to read dictionary file at {:?}", dic_path))?;
+
+        let encoding = String::from_utf8_lossy(&aff)
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("SET ").map(|e| e.trim().to_ascii_uppercase()))
+            .unwrap_or_else(|| "UTF-8".to_string());
+        let decode = |bytes: Vec<u8>, path: &Path| -> Result<String> {
+            match encoding.as_str() {
+                "UTF-8" => String::from_utf8(bytes).with_context(|| format!("{:?} is not valid UTF-8", path)),
+                "ISO8859-1" | "ISO-8859-1" => Ok(bytes.into_iter().map(char::from).collect()),
+                other => bail!("Unsupported encoding {} in {:?}", other, aff_path),
+            }
+        };
+
+        Self::parse(&decode(aff, aff_path)?, &decode(dic, dic_path)?)
+            .with_context(|| format!("Failed to parse Hunspell dictionary {:?}", dic_path))
+    }
+
+    /// Parses a dictionary from the contents of its `.aff` and `.dic` files.
+    pub fn parse(aff: &str, dic: &str) -> Result<Self> {
+        let mut hunspell = Self { compound_min: 3, ..Self::default() };
+        let mut mode = FlagMode::default();
+        let mut aliases: Vec<Vec<Flag>> = Vec::new();
+        let mut aliases_declared = false;
+        let mut rules_declared = false;
+        // Entries still expected for each affix header, by kind and flag.
+        let mut pending: HashMap<(bool, Flag), (bool, usize)> = HashMap::new();
+
+        for (number, line) in aff.lines().enumerate() {
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let arg = fields.get(1).copied().unwrap_or("");
+            let flag = mode.parse(arg).first().copied();
+
+            match fields[0] {
+                "FLAG" => {
+                    mode = match arg {
+                        "long" => FlagMode::Long,
+                        "num" => FlagMode::Num,
+                        _ => FlagMode::Char,
+                    }
+                }
+                "AF" if !aliases_declared => aliases_declared = true,
+                "AF" => aliases.push(mode.parse(arg)),
+                "NEEDAFFIX" | "PSEUDOROOT" => hunspell.flags.need_affix = flag,
+                "FORBIDDENWORD" => hunspell.flags.forbidden = flag,
+                "ONLYINCOMPOUND" => hunspell.flags.only_in_compound = flag,
+                "NOSUGGEST" => hunspell.flags.no_suggest = flag,
+                "COMPOUNDFLAG" => hunspell.flags.compound = flag,
+                "COMPOUNDBEGIN" => hunspell.flags.compound_begin = flag,
+                "COMPOUNDMIDDLE" => hunspell.flags.compound_middle = flag,
+                "COMPOUNDEND" | "COMPOUNDLAST" => hunspell.flags.compound_end = flag,
+                "COMPOUNDMIN" => hunspell.compound_min = arg.parse::<usize>().unwrap_or(3).max(1),
+                "COMPOUNDWORDMAX" => hunspell.compound_word_max = arg.parse().ok(),
+                "COMPOUNDRULE" if !rules_declared => rules_declared = true,
+                "COMPOUNDRULE" => hunspell.compound_rules.push(parse_rule(mode, arg)),
+                kind @ ("PFX" | "SFX") => {
+                    let is_prefix = kind == "PFX";
+                    let Some(flag) = flag else {
+                        bail!("line {}: {} without a flag", number + 1, kind);
+                    };
+                    match pending.get_mut(&(is_prefix, flag)) {
+                        Some((cross_product, remaining)) if *remaining > 0 => {
+                            *remaining -= 1;
+                            let affix = parse_affix(mode, &aliases, flag, *cross_product, &fields)
+                                .with_context(|| format!("line {}: invalid {} entry", number + 1, kind))?;
+                            if is_prefix {
+                                hunspell.prefixes.push(affix);
+                            } else {
+                                hunspell.suffixes.push(affix);
+                            }
+                        }
+                        _ => {
+                            let cross_product = fields.get(2) == Some(&"Y");
+                            let count = fields.get(3).and_then(|n| n.parse().ok()).unwrap_or(0);
+                            pending.insert((is_prefix, flag), (cross_product, count));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (number, line) in dic.lines().enumerate() {
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() || (number == 0 && line.parse::<usize>().is_ok()) {
+                continue;
+            }
+            // Morphological fields follow the word after whitespace.
+            let entry = line.split(['\t', ' ']).next().unwrap_or(line);
+            let (word, flags) = split_entry(entry);
+            if word.is_empty() {
+                continue;
+            }
+            let flags = resolve_flags(mode, &aliases, &flags);
+            hunspell.stems.entry(word.to_lowercase()).or_default().push(flags);
+        }
+
+        Ok(hunspell)
+    }
+
+    /// Returns `true` if `word` (lowercase) is correct according to this dictionary.
+    pub fn contains(&self, word: &str) -> bool {
+        let analyses = self.analyze(word, true, true);
+        if analyses.iter().any(|a| a.has(self.flags.forbidden) && a.affix_flags.is_empty()) {
+            return false;
+        }
+        if analyses
+            .iter()
+            .any(|a| !a.needs_affix && !a.has(self.flags.forbidden) && !a.has(self.flags.only_in_compound))
+        {
+            return true;
+        }
+        self.is_compound(word)
+    }
+
+    /// Returns the stems and their forms with a single prefix or suffix.
+    ///
+    /// Used as suggestion candidates. Words marked `NOSUGGEST`, `FORBIDDENWORD`
+    /// or `ONLYINCOMPOUND` are left out, as are prefix and suffix combinations
+    /// and compounds, which would make the list far too large.
+    pub fn words(&self) -> HashSet<String> {
+        let excluded = [self.flags.no_suggest, self.flags.forbidden, self.flags.only_in_compound];
+        let is_excluded = |flags: &[Flag]| excluded.iter().flatten().any(|f| flags.contains(f));
+        let needs_affix = |flags: &[Flag]| self.flags.need_affix.is_some_and(|f| flags.contains(&f));
+
+        let mut words = HashSet::new();
+        for (stem, homonyms) in &self.stems {
+            for flags in homonyms {
+                if is_excluded(flags) {
+                    continue;
+                }
+                if !needs_affix(flags) {
+                    words.insert(stem.clone());
+                }
+                for affix in self.suffixes.iter().filter(|a| flags.contains(&a.flag)) {
+                    if !is_excluded(&affix.continuation) && !needs_affix(&affix.continuation) {
+                        words.extend(affix.suffix(stem));
+                    }
+                }
+                for affix in self.prefixes.iter().filter(|a| flags.contains(&a.flag)) {
+                    if !is_excluded(&affix.continuation) && !needs_affix(&affix.continuation) {
+                        words.extend(affix.prefix(stem));
+                    }
+                }
+            }
+        }
+        words
+    }
+
+    /// Returns the number of stems in the dictionary.
+    pub fn count(&self) -> usize {
+        self.stems.len()
+    }
+
+    /// Finds every way `word` can be derived from a stem.
+    ///
+    /// Handles a bare stem, one prefix, one suffix, a suffix on top of another
+    /// suffix (allowed by the inner suffix's continuation flags), and a prefix
+    /// combined with a suffix when both allow cross products.
+    fn analyze(&self, word: &str, allow_prefix: bool, allow_suffix: bool) -> Vec<Analysis<'_>> {
+        let need = |flags: &[Flag]| self.flags.need_affix.is_some_and(|f| flags.contains(&f));
+        let mut found = Vec::new();
+
+        for flags in self.homonyms(word) {
+            found.push(Analysis { stem_flags: flags, affix_flags: Vec::new(), needs_affix: need(flags) });
+        }
+
+        if allow_suffix {
+            for outer in &self.suffixes {
+                let Some(base) = outer.unsuffix(word) else {
+                    continue;
+                };
+                for flags in self.homonyms(&base).filter(|f| f.contains(&outer.flag)) {
+                    found.push(Analysis {
+                        stem_flags: flags,
+                        affix_flags: outer.continuation.clone(),
+                        needs_affix: need(&outer.continuation),
+                    });
+                }
+                for inner in self.suffixes.iter().filter(|s| s.continuation.contains(&outer.flag)) {
+                    let Some(stem) = inner.unsuffix(&base) else {
+                        continue;
+                    };
+                    for flags in self.homonyms(&stem).filter(|f| f.contains(&inner.flag)) {
+                        let mut affix_flags = outer.continuation.clone();
+                        affix_flags.extend(&inner.continuation);
+                        found.push(Analysis { stem_flags: flags, affix_flags, needs_affix: need(&outer.continuation) });
+                    }
+                }
+            }
+        }
+
+        if allow_prefix {
+            for prefix in &self.prefixes {
+                let Some(base) = prefix.unprefix(word) else {
+                    continue;
+                };
+                for flags in self.homonyms(&base).filter(|f| f.contains(&prefix.flag)) {
+                    found.push(Analysis {
+                        stem_flags: flags,
+                        affix_flags: prefix.continuation.clone(),
+                        needs_affix: need(&prefix.continuation),
+                    });
+                }
+                if !allow_suffix || !prefix.cross_product {
+                    continue;
+                }
+                for suffix in self.suffixes.iter().filter(|s| s.cross_product) {
+                    let Some(stem) = suffix.unsuffix(&base) else {
+                        continue;
+                    };
+                    for flags in self.homonyms(&stem).filter(|f| f.contains(&prefix.flag) && f.contains(&suffix.flag)) {
+                        let mut affix_flags = prefix.continuation.clone();
+                        affix_flags.extend(&suffix.continuation);
+                        found.push(Analysis {
+                            stem_flags: flags,
+                            affix_flags,
+                            needs_affix: need(&prefix.continuation) && need(&suffix.continuation),
+                        });
+                    }
+                }
+            }
+        }
+
+        found
+    }
+
+    fn homonyms(&self, stem: &str) -> impl Iterator<Item = &[Flag]> {
+        self.stems.get(stem).into_iter().flatten().map(Vec::as_slice)
+    }
+
+    /// Returns `true` if `word` is a valid compound of two or more words.
+    fn is_compound(&self, word: &str) -> bool {
+        if word.chars().count() < self.compound_min * 2 && self.compound_rules.is_empty() {
+            return false;
+        }
+        let flags = &self.flags;
+        let uses_flags = [flags.compound, flags.compound_begin, flags.compound_middle, flags.compound_end]
+            .iter()
+            .any(Option::is_some);
+        (uses_flags && self.compound_from(word, 0)) || self.compound_rules.iter().any(|rule| self.matches_rule(word, rule, 0))
+    }
+
+    /// Checks whether `rest` completes a flag-based compound whose first
+    /// `parts` words have already been matched.
+    fn compound_from(&self, rest: &str, parts: usize) -> bool {
+        if self.compound_word_max.is_some_and(|max| parts + 2 > max) {
+            return false;
+        }
+        let position = if parts == 0 { Position::Begin } else { Position::Middle };
+        for (split, _) in rest.char_indices().skip(self.compound_min) {
+            let (head, tail) = rest.split_at(split);
+            if tail.chars().count() < self.compound_min || !self.is_compound_part(head, position) {
+                continue;
+            }
+            if self.is_compound_part(tail, Position::End) || self.compound_from(tail, parts + 1) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if `part` may appear at `position` in a compound.
+    ///
+    /// Only the first part may carry a prefix and only the last a suffix.
+    fn is_compound_part(&self, part: &str, position: Position) -> bool {
+        let position_flag = match position {
+            Position::Begin => self.flags.compound_begin,
+            Position::Middle => self.flags.compound_middle,
+            Position::End => self.flags.compound_end,
+        };
+        self.analyze(part, position == Position::Begin, position == Position::End)
+            .iter()
+            .any(|a| !a.has(self.flags.forbidden) && (a.has(self.flags.compound) || a.has(position_flag)))
+    }
+
+    /// Matches `rest` against a `COMPOUNDRULE`, one bare stem per flag.
+    fn matches_rule(&self, rest: &str, rule: &[RulePart], parts: usize) -> bool {
+        if rest.is_empty() {
+            return parts >= 2 && rule.iter().all(|p| !matches!(p, RulePart::One(_)));
+        }
+        let Some((&part, remaining)) = rule.split_first() else {
+            return false;
+        };
+        if matches!(part, RulePart::Optional(_) | RulePart::Repeated(_)) && self.matches_rule(rest, remaining, parts) {
+            return true;
+        }
+        let (RulePart::One(flag) | RulePart::Optional(flag) | RulePart::Repeated(flag)) = part;
+
+        let ends = rest.char_indices().map(|(i, _)| i).skip(1).chain([rest.len()]);
+        for end in ends.skip(self.compound_min - 1) {
+            let (head, tail) = rest.split_at(end);
+            let allowed = self
+                .homonyms(head)
+                .any(|f| f.contains(&flag) && !self.flags.forbidden.is_some_and(|x| f.contains(&x)));
+            if !allowed {
+                continue;
+            }
+            let next = if matches!(part, RulePart::Repeated(_)) { rule } else { remaining };
+            if self.matches_rule(tail, next, parts + 1) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Parses a `PFX`/`SFX` entry line: `SFX flag strip add[/flags] [condition]`.
+fn parse_affix(mode: FlagMode, aliases: &[Vec<Flag>], flag: Flag, cross_product: bool, fields: &[&str]) -> Result<Affix> {
+    let (Some(strip), Some(add)) = (fields.get(2), fields.get(3)) else {
+        bail!("expected strip and affix fields");
+    };
+    let (add, continuation) = match add.split_once('/') {
+        Some((add, flags)) => (add, resolve_flags(mode, aliases, flags)),
+        None => (*add, Vec::new()),
+    };
+    let text = |field: &str| if field == "0" { String::new() } else { field.to_lowercase() };
+    Ok(Affix {
+        flag,
+        cross_product,
+        strip: text(strip),
+        add: text(add),
+        condition: Condition::parse(&fields.get(4).map_or(".".to_string(), |c| c.to_lowercase())),
+        continuation,
+    })
+}
+
+/// Parses a `COMPOUNDRULE` pattern such as `n*1t` or `(aa)(bb)*`.
+fn parse_rule(mode: FlagMode, text: &str) -> Vec<RulePart> {
+    let mut parts = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let flag = if c == '(' {
+            let inner: String = chars.by_ref().take_while(|&c| c != ')').collect();
+            mode.parse(&inner).first().copied()
+        } else {
+            Some(u32::from(c))
+        };
+        let Some(flag) = flag else { continue };
+        parts.push(match chars.peek() {
+            Some('*') => {
+                chars.next();
+                RulePart::Repeated(flag)
+            }
+            Some('?') => {
+                chars.next();
+                RulePart::Optional(flag)
+            }
+            _ => RulePart::One(flag),
+        });
+    }
+    parts
+}
+
+/// Splits a `.dic` entry into the word and its flags at the first unescaped `/`.
+fn split_entry(entry: &str) -> (String, String) {
+    let mut word = String::new();
+    let mut chars = entry.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => word.extend(chars.next()),
+            '/' => return (word, chars.collect()),
+            c => word.push(c),
+        }
+    }
+    (word, String::new())
+}
+
+/// Parses flags, resolving `AF` alias numbers when aliases are declared.
+fn resolve_flags(mode: FlagMode, aliases: &[Vec<Flag>], flags: &str) -> Vec<Flag> {
+    if !aliases.is_empty()
+        && let Some(alias) = flags.parse::<usize>().ok().and_then(|n| aliases.get(n.checked_sub(1)?))
+    {
+        return alias.clone();
+    }
+    mode.parse(flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+SET UTF-8
+TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+NEEDAFFIX X
+FORBIDDENWORD !
+COMPOUNDMIN 1
+ONLYINCOMPOUND c
+COMPOUNDRULE 1
+COMPOUNDRULE n*t
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX S Y 3
+SFX S y ies [^aeiouy]y
+SFX S 0 s [aeiou]y
+SFX S 0 s [^y]
+
+SFX D Y 2
+SFX D 0 ed [^ey]
+SFX D 0 d e
+
+SFX L N 1
+SFX L 0 ly/S .
+
+SFX Z Y 1
+SFX Z 0 ing/X .
+";
+
+    const DIC: &str = "8
+colour/SD
+fly/S
+happy/U
+kind/UL
+1/n
+2/n
+1st/ct
+forbade/!
+";
+
+    fn dictionary() -> Hunspell {
+        Hunspell::parse(AFF, DIC).unwrap()
+    }
+
+    #[test]
+    fn test_stems_and_affixes() {
+        let dict = dictionary();
+        assert_eq!(dict.count(), 8);
+        assert!(dict.contains("colour"));
+        assert!(dict.contains("colours"));
+        assert!(dict.contains("coloured"));
+        assert!(dict.contains("flies"));
+        assert!(!dict.contains("flys"));
+        assert!(dict.contains("unhappy"));
+        assert!(!dict.contains("uncolour"));
+        assert!(!dict.contains("colourly"));
+    }
+
+    #[test]
+    fn test_cross_product_and_continuation() {
+        let dict = dictionary();
+        // `un` + `kind` + `ly`, where `L` is not a cross product.
+        assert!(dict.contains("kindly"));
+        assert!(!dict.contains("unkindly"));
+        // `ly` allows a further `S` suffix.
+        assert!(dict.contains("kindlies"));
+    }
+
+    #[test]
+    fn test_special_flags() {
+        let dict = dictionary();
+        assert!(!dict.contains("forbade"));
+        // `1st` is only valid inside a compound.
+        assert!(!dict.contains("1st"));
+    }
+
+    #[test]
+    fn test_compound_rule() {
+        let dict = dictionary();
+        assert!(dict.contains("21st"));
+        assert!(dict.contains("221st"));
+        assert!(!dict.contains("2st"));
+    }
+
+    #[test]
+    fn test_compound_flags() {
+        let aff = "FLAG long\nCOMPOUNDBEGIN Bb\nCOMPOUNDEND Ee\nCOMPOUNDMIN 3\nSFX Ss Y 1\nSFX Ss 0 en .\n";
+        let dic = "3\nHaus/Bb\nTür/EeSs\nTor/Ee\n";
+        let dict = Hunspell::parse(aff, dic).unwrap();
+        assert!(dict.contains("haustür"));
+        assert!(dict.contains("haustüren"));
+        assert!(!dict.contains("türhaus"));
+        assert!(!dict.contains("torhaus"));
+    }
+
+    #[test]
+    fn test_flag_aliases() {
+        let aff = "AF 1\nAF SD\nSFX S Y 1\nSFX S 0 s .\nSFX D Y 1\nSFX D 0 ed .\n";
+        let dict = Hunspell::parse(aff, "1\nwalk/1\n").unwrap();
+        assert!(dict.contains("walks"));
+        assert!(dict.contains("walked"));
+    }
+
+    #[test]
+    fn test_words_for_suggestions() {
+        let words = dictionary().words();
+        assert!(words.contains("flies"));
+        assert!(words.contains("unhappy"));
+        assert!(!words.contains("forbade"));
+        assert!(!words.contains("1st"));
+    }
+
+    #[test]
+    fn test_load_latin1() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let aff = dir.path().join("de.aff");
+        let dic = dir.path().join("de.dic");
+        fs::write(&aff, b"SET ISO8859-1\n")?;
+        fs::write(&dic, b"1\nT\xfcr\n")?;
+        assert!(Hunspell::load(&aff, &dic)?.contains("tür"));
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+//! LaTeX prose extraction.
+//!
+//! Finds the prose of a LaTeX document, including the arguments of text
+//! commands such as `\section{...}` and `\emph{...}`. Command names, math,
+//! verbatim environments, environment names and specifications, and the
+//! arguments of commands that take keys, paths or definitions (`\ref`,
+//! `\cite`, `\label`, `\includegraphics`, `\usepackage`, ...) are left out.
+//! Comments are returned as comments.
+
+use crate::lexer::{Region, RegionKind};
+use std::ops::Range;
+
+/// Environments whose content is not prose.
+const SKIPPED_ENVIRONMENTS: &[&str] = &[
+    "equation",
+    "equation*",
+    "align",
+    "align*",
+    "alignat",
+    "alignat*",
+    "gather",
+    "gather*",
+    "multline",
+    "multline*",
+    "flalign",
+    "flalign*",
+    "eqnarray",
+    "eqnarray*",
+    "math",
+    "displaymath",
+    "verbatim",
+    "verbatim*",
+    "Verbatim",
+    "lstlisting",
+    "minted",
+    "tikzpicture",
+    "filecontents",
+    "filecontents*",
+];
+
+/// Commands whose arguments are keys, paths, code or definitions, with the
+/// number of mandatory arguments to skip. Optional arguments are skipped too.
+const SKIPPED_ARGUMENTS: &[(&str, usize)] = &[
+    ("label", 1),
+    ("ref", 1),
+    ("eqref", 1),
+    ("pageref", 1),
+    ("autoref", 1),
+    ("nameref", 1),
+    ("cref", 1),
+    ("Cref", 1),
+    ("vref", 1),
+    ("cite", 1),
+    ("citep", 1),
+    ("citet", 1),
+    ("citealp", 1),
+    ("citeauthor", 1),
+    ("citeyear", 1),
+    ("parencite", 1),
+    ("textcite", 1),
+    ("autocite", 1),
+    ("footcite", 1),
+    ("nocite", 1),
+    ("bibitem", 1),
+    ("url", 1),
+    ("href", 1),
+    ("includegraphics", 1),
+    ("input", 1),
+    ("include", 1),
+    ("includeonly", 1),
+    ("usepackage", 1),
+    ("RequirePackage", 1),
+    ("documentclass", 1),
+    ("bibliography", 1),
+    ("bibliographystyle", 1),
+    ("addbibresource", 1),
+    ("graphicspath", 1),
+    ("lstinputlisting", 1),
+    ("inputminted", 2),
+    ("newcommand", 2),
+    ("renewcommand", 2),
+    ("providecommand", 2),
+    ("newenvironment", 3),
+    ("renewenvironment", 3),
+    ("newtheorem", 2),
+    ("setlength", 2),
+    ("addtolength", 2),
+    ("setcounter", 2),
+    ("addtocounter", 2),
+    ("vspace", 1),
+    ("hspace", 1),
+    ("color", 1),
+    ("textcolor", 1),
+    ("colorbox", 1),
+    ("definecolor", 3),
+    ("pagestyle", 1),
+    ("thispagestyle", 1),
+    ("pagenumbering", 1),
+    ("hypersetup", 1),
+    ("geometry", 1),
+    ("lstset", 1),
+    ("ensuremath", 1),
+    ("pgfmathsetmacro", 2),
+];
+
+/// Extracts the prose and comments of a LaTeX document.
+pub fn extract_regions(content: &str) -> Vec<Region> {
+    let mut scanner = Scanner {
+        src: content.as_bytes(),
+        pos: 0,
+        text_start: 0,
+        regions: Vec::new(),
+    };
+    scanner.run();
+    scanner.regions
+}
+
+struct Scanner<'a> {
+    src: &'a [u8],
+    pos: usize,
+    /// Where the current run of prose started.
+    text_start: usize,
+    regions: Vec<Region>,
+}
+
+impl Scanner<'_> {
+    fn run(&mut self) {
+        while self.pos < self.src.len() {
+            match self.src[self.pos] {
+                b'%' => {
+                    let end = self.find(self.pos, b"\n").unwrap_or(self.src.len());
+                    let range = self.pos..end;
+                    self.skip(range.clone());
+                    self.regions.push(Region {
+                        kind: RegionKind::Comment,
+                        range,
+                    });
+                }
+                b'$' => {
+                    let (open, close): (usize, &[u8]) = if self.src[self.pos..].starts_with(b"$$") {
+                        (2, b"$$")
+                    } else {
+                        (1, b"$")
+                    };
+                    let end = self
+                        .find_unescaped(self.pos + open, close)
+                        .map_or(self.src.len(), |c| c + close.len());
+                    self.skip(self.pos..end);
+                }
+                b'\\' => self.command(),
+                _ => self.pos += 1,
+            }
+        }
+        let range = self.text_start..self.src.len();
+        self.push_text(range);
+    }
+
+    fn find(&self, from: usize, pat: &[u8]) -> Option<usize> {
+        let from = from.min(self.src.len());
+        self.src[from..]
+            .windows(pat.len())
+            .position(|w| w == pat)
+            .map(|p| from + p)
+    }
+
+    /// Like `find`, but ignores matches escaped with a backslash, as in `\$`.
+    fn find_unescaped(&self, mut from: usize, pat: &[u8]) -> Option<usize> {
+        while let Some(found) = self.find(from, pat) {
+            if found > 0 && self.src[found - 1] == b'\\' {
+                from = found + 1;
+            } else {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    fn push_text(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region {
+                kind: RegionKind::Text,
+                range,
+            });
+        }
+    }
+
+    /// Ends the current run of prose before `range` and resumes after it.
+    fn skip(&mut self, range: Range<usize>) {
+        let text = self.text_start..range.start;
+        self.push_text(text);
+        self.text_start = range.end;
+        self.pos = range.end;
+    }
+
+    /// Returns the end of the balanced group opened by the `open` byte at `from`.
+    fn group_end(&self, from: usize, open: u8, close: u8) -> usize {
+        let mut depth = 0;
+        let mut i = from;
+        while i < self.src.len() {
+            match self.src[i] {
+                b'\\' => i += 1,
+                b if b == open => depth += 1,
+                b if b == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        self.src.len()
+    }
+
+    /// Returns the position after up to `mandatory` `{...}` groups and any
+    /// `[...]` groups starting at `from`, allowing whitespace between them.
+    fn skip_arguments(&self, mut from: usize, mandatory: usize) -> usize {
+        let mut remaining = mandatory;
+        loop {
+            let next = from
+                + self.src[from..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_whitespace())
+                    .count();
+            match self.src.get(next) {
+                Some(b'[') => from = self.group_end(next, b'[', b']'),
+                Some(b'{') if remaining > 0 => {
+                    from = self.group_end(next, b'{', b'}');
+                    remaining -= 1;
+                }
+                _ => return from,
+            }
+        }
+    }
+
+    fn command(&mut self) {
+        let start = self.pos;
+        let name_len = self.src[start + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphabetic())
+            .count();
+        if name_len == 0 {
+            let end = match self.src.get(start + 1) {
+                Some(b'(') => self
+                    .find(start + 2, b"\\)")
+                    .map_or(self.src.len(), |c| c + 2),
+                Some(b'[') => self
+                    .find(start + 2, b"\\]")
+                    .map_or(self.src.len(), |c| c + 2),
+                // A line break may carry a spacing argument, as in `\\[2pt]`.
+                Some(b'\\') if self.src.get(start + 2) == Some(&b'[') => {
+                    self.group_end(start + 2, b'[', b']')
+                }
+                Some(_) => start + 2,
+                None => start + 1,
+            };
+            self.skip(start..end);
+            return;
+        }
+        let name_end = start + 1 + name_len;
+        let name = std::str::from_utf8(&self.src[start + 1..name_end])
+            .unwrap_or_default()
+            .to_string();
+        let name_end = if self.src.get(name_end) == Some(&b'*') {
+            name_end + 1
+        } else {
+            name_end
+        };
+
+        match name.as_str() {
+            "verb" | "lstinline"
+                if self
+                    .src
+                    .get(name_end)
+                    .is_some_and(|b| *b != b'{' && *b != b'[') =>
+            {
+                let delim = self.src[name_end];
+                let end = self
+                    .find(name_end + 1, &[delim])
+                    .map_or(self.src.len(), |c| c + 1);
+                self.skip(start..end);
+            }
+            "begin" => {
+                let env_end = self.skip_arguments(name_end, 1);
+                let env = self.environment(name_end, env_end);
+                if SKIPPED_ENVIRONMENTS.contains(&env.as_str()) || env == "comment" {
+                    let close = format!("\\end{{{}}}", env);
+                    let end = self
+                        .find(env_end, close.as_bytes())
+                        .map_or(self.src.len(), |c| c + close.len());
+                    self.skip(start..end);
+                    if env == "comment" {
+                        self.regions.push(Region {
+                            kind: RegionKind::Comment,
+                            range: start..end,
+                        });
+                    }
+                } else {
+                    // Specifications such as `{tabular}{lrc}` or `{figure}[htbp]`.
+                    let end = self.skip_arguments(env_end, 1);
+                    self.skip(start..end);
+                }
+            }
+            "end" => {
+                let end = self.skip_arguments(name_end, 1);
+                self.skip(start..end);
+            }
+            _ => match SKIPPED_ARGUMENTS.iter().find(|(n, _)| *n == name) {
+                Some(&(_, count)) => {
+                    let end = self.skip_arguments(name_end, count);
+                    self.skip(start..end);
+                }
+                None => self.skip(start..name_end),
+            },
+        }
+    }
+
+    /// Returns the environment name in the `{...}` group between `from` and `to`.
+    fn environment(&self, from: usize, to: usize) -> String {
+        let group = String::from_utf8_lossy(&self.src[from..to]);
+        group
+            .trim()
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .trim()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prose(content: &str) -> String {
+        extract_regions(content)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::Text)
+            .map(|r| content[r.range].to_string())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    #[test]
+    fn test_commands() {
+        let doc = "\\documentclass[11pt]{artcle}\n\\section{Intro}\\label{sec:intrdction}\nWe \\emph{show} $x^2 + \\alpha$ costs 5\\% as in~\\cite[p.~3]{smth2020} and \\ref{fig:arch}.\\\\[2pt]\n\
+            See \\href{https://x.io}{the site} or \\verb|mian()|. % a commnt\n";
+        assert_eq!(
+            prose(doc),
+            "\n|{Intro}|\nWe |{show} | costs 5| as in~| and |.|\nSee |{the site} or |. |\n"
+        );
+        let comments: Vec<&str> = extract_regions(doc)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::Comment)
+            .map(|r| &doc[r.range])
+            .collect();
+        assert_eq!(comments, vec!["% a commnt"]);
+    }
+
+    #[test]
+    fn test_environments() {
+        let doc = "\\begin{figure}[htbp]\n\\caption{A diagram}\n\\end{figure}\n\\begin{equation}\n  E = mc^2 \\label{eq:enrgy}\n\\end{equation}\n\
+            \\begin{tabular}{lrc}\nCell & Valu \\\\\n\\end{tabular}\n\\[ \\int f \\]\n\\begin{comment}\nHidden\n\\end{comment}\n";
+        assert_eq!(
+            prose(doc),
+            "\n|{A diagram}\n|\n|\n|\nCell & Valu |\n|\n|\n|\n"
+        );
+    }
+}
@@ -0,0 +1,692 @@
+//! Language-aware lexing of source files.
+//!
+//! Spell-checking code line by line flags keywords, type names and crate paths.
+//! This module recognizes the comments, doc comments and string literals of the
+//! supported programming languages so the engine can check only those regions.
+//! Markup formats are handed to their own extractors, which return prose as
+//! `RegionKind::Text`, and structured data files to the `data` extractors,
+//! which return string values.
+
+use crate::config_schema::LanguagesConfig;
+use crate::data::{self, KeyFilter};
+use crate::{asciidoc, html, latex, markdown, notebook, rst};
+use std::ops::Range;
+use std::path::Path;
+
+/// A programming language or markup format with a dedicated lexer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+    /// C and C++ sources and headers.
+    C,
+    Java,
+    Markdown,
+    Html,
+    /// XML documents, including SVG images.
+    Xml,
+    /// reStructuredText documents.
+    Rst,
+    AsciiDoc,
+    Latex,
+    /// Jupyter notebooks, whose cells are lexed by their own language.
+    Notebook,
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// The kind of a region extracted from a source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    /// A regular line or block comment.
+    Comment,
+    /// A documentation comment or docstring.
+    DocComment,
+    /// The contents of a string literal.
+    String,
+    /// Prose in a markup document.
+    Text,
+}
+
+/// A checkable region of a source file, as a byte range into the original content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// What kind of source construct this region belongs to.
+    pub kind: RegionKind,
+    /// The byte range of the region within the file.
+    pub range: Range<usize>,
+}
+
+impl Language {
+    /// Detects the language of a file from its extension.
+    ///
+    /// Returns `None` for plain text and unknown files, which are checked in full.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "rs" => Some(Self::Rust),
+            "py" | "pyi" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(Self::JavaScript),
+            "go" => Some(Self::Go),
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => Some(Self::C),
+            "java" => Some(Self::Java),
+            "md" | "markdown" | "mdown" | "mkd" | "mkdn" => Some(Self::Markdown),
+            "html" | "htm" | "xhtml" => Some(Self::Html),
+            "xml" | "svg" | "xsl" | "xslt" | "xsd" | "rss" | "atom" => Some(Self::Xml),
+            "rst" | "rest" => Some(Self::Rst),
+            "adoc" | "asciidoc" | "asc" => Some(Self::AsciiDoc),
+            "tex" | "latex" | "ltx" => Some(Self::Latex),
+            "ipynb" => Some(Self::Notebook),
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for markup formats, whose character references such as
+    /// `&amp;` are decoded before checking.
+    pub fn is_markup(self) -> bool {
+        matches!(self, Self::Markdown | Self::Html | Self::Xml | Self::AsciiDoc)
+    }
+
+}
+
+/// How single-quoted literals are treated by a language.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharQuote {
+    /// `'` delimits a string literal.
+    String,
+    /// `'` delimits a character literal, which is skipped.
+    Char,
+    /// Like `Char`, but a `'` not closing a single character is a lifetime or label.
+    Lifetime,
+}
+
+/// The raw string literals of a language, whose contents have no escapes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RawStrings {
+    None,
+    /// Rust `r"..."`/`r#"..."#`, also as byte (`br`) and C (`cr`) strings.
+    Rust,
+    /// C++ `R"delim(...)delim"`, also with the `u8`, `u`, `U` and `L` prefixes.
+    Cpp,
+}
+
+/// Lexical rules shared by the supported languages.
+struct Syntax {
+    line_comments: &'static [&'static str],
+    line_docs: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    block_docs: &'static [&'static str],
+    nested_blocks: bool,
+    char_quote: CharQuote,
+    /// Whether `` ` `` delimits a literal (JS template literals, Go raw strings).
+    backtick_strings: bool,
+    /// Whether backtick literals process escapes.
+    backtick_escapes: bool,
+    /// Whether `"` strings may span several lines.
+    multiline_strings: bool,
+    /// Python triple-quoted strings and docstrings.
+    triple_quotes: bool,
+    raw_strings: RawStrings,
+    /// Whether `#include` lines name a header, which is skipped.
+    include_directives: bool,
+}
+
+const RUST: Syntax = Syntax {
+    line_comments: &["//"],
+    line_docs: &["///", "//!"],
+    block_comment: Some(("/*", "*/")),
+    block_docs: &["/**", "/*!"],
+    nested_blocks: true,
+    char_quote: CharQuote::Lifetime,
+    backtick_strings: false,
+    backtick_escapes: false,
+    multiline_strings: true,
+    triple_quotes: false,
+    raw_strings: RawStrings::Rust,
+    include_directives: false,
+};
+
+const PYTHON: Syntax = Syntax {
+    line_comments: &["#"],
+    line_docs: &[],
+    block_comment: None,
+    block_docs: &[],
+    nested_blocks: false,
+    char_quote: CharQuote::String,
+    backtick_strings: false,
+    backtick_escapes: false,
+    multiline_strings: false,
+    triple_quotes: true,
+    raw_strings: RawStrings::None,
+    include_directives: false,
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    line_comments: &["//"],
+    line_docs: &[],
+    block_comment: Some(("/*", "*/")),
+    block_docs: &["/**"],
+    nested_blocks: false,
+    char_quote: CharQuote::String,
+    backtick_strings: true,
+    backtick_escapes: true,
+    multiline_strings: false,
+    triple_quotes: false,
+    raw_strings: RawStrings::None,
+    include_directives: false,
+};
+
+const GO: Syntax = Syntax {
+    line_comments: &["//"],
+    line_docs: &[],
+    block_comment: Some(("/*", "*/")),
+    block_docs: &[],
+    nested_blocks: false,
+    char_quote: CharQuote::Char,
+    backtick_strings: true,
+    backtick_escapes: false,
+    multiline_strings: false,
+    triple_quotes: false,
+    raw_strings: RawStrings::None,
+    include_directives: false,
+};
+
+const C: Syntax = Syntax {
+    line_comments: &["//"],
+    line_docs: &["///", "//!"],
+    block_comment: Some(("/*", "*/")),
+    block_docs: &["/**", "/*!"],
+    nested_blocks: false,
+    char_quote: CharQuote::Char,
+    backtick_strings: false,
+    backtick_escapes: false,
+    multiline_strings: false,
+    triple_quotes: false,
+    raw_strings: RawStrings::Cpp,
+    include_directives: true,
+};
+
+const JAVA: Syntax = Syntax {
+    line_comments: &["//"],
+    line_docs: &[],
+    block_comment: Some(("/*", "*/")),
+    block_docs: &["/**"],
+    nested_blocks: false,
+    char_quote: CharQuote::Char,
+    backtick_strings: false,
+    backtick_escapes: false,
+    multiline_strings: false,
+    triple_quotes: false,
+    raw_strings: RawStrings::None,
+    include_directives: false,
+};
+
+/// Extracts the comments, doc comments and string literals of a source file,
+/// or the prose and comments of a markup document, using the per-language
+/// settings in `config`.
+///
+/// String regions cover the literal's contents only and are split around
+/// escape sequences, so `"line\nbreak"` yields `line` and `break`.
+pub fn extract_regions(language: Language, content: &str, config: &LanguagesConfig) -> Vec<Region> {
+    let syntax = match language {
+        Language::Rust => &RUST,
+        Language::Python => &PYTHON,
+        Language::JavaScript => &JAVASCRIPT,
+        Language::Go => &GO,
+        Language::C => &C,
+        Language::Java => &JAVA,
+        Language::Markdown => return markdown::extract_regions(content),
+        Language::Html => return html::extract_regions(content, false, &config.html.attributes),
+        Language::Xml => return html::extract_regions(content, true, &config.xml.attributes),
+        Language::Rst => return rst::extract_regions(content),
+        Language::AsciiDoc => return asciidoc::extract_regions(content),
+        Language::Latex => return latex::extract_regions(content),
+        Language::Notebook => return notebook::extract_regions(content, config),
+        Language::Json => return data::json::extract_regions(content, &KeyFilter::new(&config.json)),
+        Language::Yaml => return data::yaml::extract_regions(content, &KeyFilter::new(&config.yaml)),
+        Language::Toml => return data::toml::extract_regions(content, &KeyFilter::new(&config.toml)),
+    };
+    Lexer {
+        syntax,
+        text: content,
+        src: content.as_bytes(),
+        pos: 0,
+        regions: Vec::new(),
+    }
+    .run()
+}
+
+/// Returns the byte range of each line of a document, without its line terminator.
+pub(crate) fn lines(content: &str) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for line in content.split_inclusive('\n') {
+        let text = line.strip_suffix('\n').map_or(line, |l| l.strip_suffix('\r').unwrap_or(l));
+        lines.push(start..start + text.len());
+        start += line.len();
+    }
+    lines
+}
+
+/// Returns the width of the leading whitespace of a line, with tabs as 4 columns.
+pub(crate) fn indentation(line: &str) -> usize {
+    line.chars().take_while(|c| *c == ' ' || *c == '\t').map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+/// Replaces every character outside `keep` with spaces, preserving newlines.
+///
+/// The result has exactly the same byte length and line structure as `content`,
+/// so offsets, lines and columns found in it are valid for the original text.
+pub fn mask<'a, I>(content: &str, keep: I) -> String
+where
+    I: IntoIterator<Item = &'a Range<usize>>,
+{
+    let mut keep: Vec<&Range<usize>> = keep.into_iter().collect();
+    keep.sort_by_key(|r| r.start);
+
+    let mut masked = String::with_capacity(content.len());
+    let mut ranges = keep.into_iter().peekable();
+    for (i, c) in content.char_indices() {
+        while ranges.peek().is_some_and(|r| r.end <= i) {
+            ranges.next();
+        }
+        let kept = ranges.peek().is_some_and(|r| r.start <= i);
+        if kept || c == '\n' {
+            masked.push(c);
+        } else {
+            masked.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        }
+    }
+    masked
+}
+
+struct Lexer<'a> {
+    syntax: &'static Syntax,
+    text: &'a str,
+    src: &'a [u8],
+    pos: usize,
+    regions: Vec<Region>,
+}
+
+impl Lexer<'_> {
+    fn run(mut self) -> Vec<Region> {
+        while self.pos < self.src.len() {
+            if self.line_comment() || self.block_comment() || self.include_directive() || self.literal() {
+                continue;
+            }
+            self.pos += 1;
+        }
+        self.regions
+    }
+
+    fn starts_with(&self, pat: &str) -> bool {
+        self.src[self.pos..].starts_with(pat.as_bytes())
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.src.get(self.pos + offset).copied()
+    }
+
+    fn push(&mut self, kind: RegionKind, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region { kind, range });
+        }
+    }
+
+    fn line_comment(&mut self) -> bool {
+        if !self.syntax.line_comments.iter().any(|p| self.starts_with(p)) {
+            return false;
+        }
+        let start = self.pos;
+        let end = self.find_byte(b'\n').unwrap_or(self.src.len());
+        // `////` separators are plain comments, not doc comments.
+        let is_doc = self.syntax.line_docs.iter().any(|p| {
+            self.starts_with(p) && self.src.get(start + p.len()) != Some(&b'/')
+        });
+        let kind = if is_doc { RegionKind::DocComment } else { RegionKind::Comment };
+        self.push(kind, start..end);
+        self.pos = end;
+        true
+    }
+
+    fn block_comment(&mut self) -> bool {
+        let Some((open, close)) = self.syntax.block_comment else {
+            return false;
+        };
+        if !self.starts_with(open) {
+            return false;
+        }
+        let start = self.pos;
+        // `/**/` is an empty comment, not a doc comment opener.
+        let is_doc = !self.starts_with("/**/")
+            && self.syntax.block_docs.iter().any(|p| {
+                self.starts_with(p) && self.src.get(start + p.len()) != Some(&b'*')
+            });
+
+        self.pos += open.len();
+        let mut depth = 1;
+        while self.pos < self.src.len() {
+            if self.syntax.nested_blocks && self.starts_with(open) {
+                depth += 1;
+                self.pos += open.len();
+            } else if self.starts_with(close) {
+                depth -= 1;
+                self.pos += close.len();
+                if depth == 0 {
+                    break;
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+        let end = self.pos.min(self.src.len());
+        let kind = if is_doc { RegionKind::DocComment } else { RegionKind::Comment };
+        self.push(kind, start..end);
+        true
+    }
+
+    fn literal(&mut self) -> bool {
+        let c = self.src[self.pos];
+        let syntax = self.syntax;
+
+        if syntax.triple_quotes && (self.starts_with("\"\"\"") || self.starts_with("'''")) {
+            let is_doc = self.only_whitespace_before();
+            let delim = if c == b'"' { "\"\"\"" } else { "'''" };
+            let kind = if is_doc { RegionKind::DocComment } else { RegionKind::String };
+            self.pos += 3;
+            self.quoted(kind, delim, true, true);
+            return true;
+        }
+
+        if self.raw_string() {
+            return true;
+        }
+
+        match c {
+            b'"' => {
+                self.pos += 1;
+                self.quoted(RegionKind::String, "\"", true, syntax.multiline_strings);
+                true
+            }
+            b'`' if syntax.backtick_strings => {
+                self.pos += 1;
+                self.quoted(RegionKind::String, "`", syntax.backtick_escapes, true);
+                true
+            }
+            b'\'' => {
+                match syntax.char_quote {
+                    CharQuote::String => {
+                        self.pos += 1;
+                        self.quoted(RegionKind::String, "'", true, false);
+                    }
+                    CharQuote::Char => self.skip_char_literal(),
+                    CharQuote::Lifetime => {
+                        if self.is_char_literal() {
+                            self.skip_char_literal();
+                        } else {
+                            self.pos += 1;
+                        }
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Consumes a raw string literal, whose contents are pushed without
+    /// splitting on backslashes.
+    fn raw_string(&mut self) -> bool {
+        if self.follows_ident() {
+            return false;
+        }
+        match self.syntax.raw_strings {
+            RawStrings::None => false,
+            RawStrings::Rust => {
+                let prefix = usize::from(matches!(self.peek(0), Some(b'b' | b'c')));
+                if self.peek(prefix) != Some(b'r') {
+                    return false;
+                }
+                let hashes = self.src[self.pos + prefix + 1..].iter().take_while(|&&b| b == b'#').count();
+                if self.peek(prefix + 1 + hashes) != Some(b'"') {
+                    return false;
+                }
+                let close = format!("\"{}", "#".repeat(hashes));
+                self.pos += prefix + 2 + hashes;
+                self.quoted(RegionKind::String, &close, false, true);
+                true
+            }
+            RawStrings::Cpp => {
+                let Some(prefix) = ["R\"", "u8R\"", "uR\"", "UR\"", "LR\""].iter().find(|p| self.starts_with(p)) else {
+                    return false;
+                };
+                // The delimiter is at most 16 characters other than parentheses,
+                // backslashes and spaces.
+                let open = self.pos + prefix.len();
+                let len = self.src[open..]
+                    .iter()
+                    .take_while(|&&b| b.is_ascii_graphic() && !matches!(b, b'(' | b')' | b'\\' | b'"'))
+                    .count();
+                if len > 16 || self.src.get(open + len) != Some(&b'(') {
+                    return false;
+                }
+                let close = format!("){}\"", &self.text[open..open + len]);
+                self.pos = open + len + 1;
+                self.quoted(RegionKind::String, &close, false, true);
+                true
+            }
+        }
+    }
+
+    /// Skips the header name of a C `#include` directive, such as `"config.h"`
+    /// or `<stdio.h>`. The rest of the line is lexed as usual, so a trailing
+    /// comment is still checked.
+    fn include_directive(&mut self) -> bool {
+        if !self.syntax.include_directives || self.src[self.pos] != b'#' || !self.only_whitespace_before() {
+            return false;
+        }
+        let blank = |b: &u8| *b == b' ' || *b == b'\t';
+        let mut pos = self.pos + 1;
+        pos += self.src[pos..].iter().take_while(|b| blank(b)).count();
+        let Some(directive) = ["include_next", "include", "import"].iter().find(|d| self.src[pos..].starts_with(d.as_bytes())) else {
+            return false;
+        };
+        pos += directive.len();
+        pos += self.src[pos..].iter().take_while(|b| blank(b)).count();
+
+        let close = match self.src.get(pos) {
+            Some(b'"') => b'"',
+            Some(b'<') => b'>',
+            _ => {
+                self.pos = pos;
+                return true;
+            }
+        };
+        let name = self.src[pos + 1..].iter().position(|&b| b == close || b == b'\n');
+        self.pos = match name {
+            Some(n) if self.src[pos + 1 + n] == close => pos + n + 2,
+            Some(n) => pos + 1 + n,
+            None => self.src.len(),
+        };
+        true
+    }
+
+    /// Consumes a literal body up to `close`, pushing its contents as regions
+    /// split around escape sequences.
+    fn quoted(&mut self, kind: RegionKind, close: &str, escapes: bool, multiline: bool) {
+        let mut seg_start = self.pos;
+        while self.pos < self.src.len() {
+            if self.starts_with(close) {
+                self.push(kind, seg_start..self.pos);
+                self.pos += close.len();
+                return;
+            }
+            match self.src[self.pos] {
+                b'\\' if escapes => {
+                    self.push(kind, seg_start..self.pos);
+                    self.pos = (self.pos + 2).min(self.src.len());
+                    seg_start = self.pos;
+                }
+                b'\n' if !multiline => break,
+                _ => self.pos += 1,
+            }
+        }
+        // Unterminated literal: keep what we have.
+        self.push(kind, seg_start..self.pos);
+    }
+
+    fn is_char_literal(&self) -> bool {
+        if self.peek(1) == Some(b'\\') {
+            return true;
+        }
+        // `'` is ASCII, so the next byte is always a character boundary.
+        match self.text[self.pos + 1..].chars().next() {
+            Some(ch) => self.peek(1 + ch.len_utf8()) == Some(b'\''),
+            None => false,
+        }
+    }
+
+    fn skip_char_literal(&mut self) {
+        self.pos += 1;
+        while self.pos < self.src.len() {
+            match self.src[self.pos] {
+                b'\\' => self.pos += 2,
+                b'\'' => {
+                    self.pos += 1;
+                    return;
+                }
+                b'\n' => return,
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn find_byte(&self, byte: u8) -> Option<usize> {
+        self.src[self.pos..].iter().position(|&b| b == byte).map(|p| self.pos + p)
+    }
+
+    fn follows_ident(&self) -> bool {
+        self.pos > 0 && {
+            let prev = self.src[self.pos - 1];
+            prev.is_ascii_alphanumeric() || prev == b'_'
+        }
+    }
+
+    fn only_whitespace_before(&self) -> bool {
+        self.src[..self.pos]
+            .iter()
+            .rev()
+            .take_while(|&&b| b != b'\n')
+            .all(|b| b.is_ascii_whitespace())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(language: Language, content: &str) -> Vec<(RegionKind, &str)> {
+        extract_regions(language, content, &LanguagesConfig::default())
+            .into_iter()
+            .map(|r| (r.kind, &content[r.range]))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_regions() {
+        let src = "/// Docs here\nfn f<'a>(x: &'a str) -> char {\n    // note\n    let s = \"a\\nb\"; /* block */\n    let r = r#\"raw \"q\"\"#;\n    '\"'\n}\n";
+        assert_eq!(
+            texts(Language::Rust, src),
+            vec![
+                (RegionKind::DocComment, "/// Docs here"),
+                (RegionKind::Comment, "// note"),
+                (RegionKind::String, "a"),
+                (RegionKind::String, "b"),
+                (RegionKind::Comment, "/* block */"),
+                (RegionKind::String, "raw \"q\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_raw_strings() {
+        let src = "let a = br\"no \\escapes\";\nlet b = br#\"with \"quotes\"\"#;\nlet c = cr\"c \\string\";\nlet d = bar\"x\";";
+        assert_eq!(
+            texts(Language::Rust, src),
+            vec![
+                (RegionKind::String, "no \\escapes"),
+                (RegionKind::String, "with \"quotes\""),
+                (RegionKind::String, "c \\string"),
+                (RegionKind::String, "x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cpp_raw_strings() {
+        let src = "auto a = R\"(no \\escapes)\";\nauto b = R\"json({\"key\": \"vaule\"})json\";\nauto c = u8R\"x(multi\nline)\")x\";";
+        assert_eq!(
+            texts(Language::C, src),
+            vec![
+                (RegionKind::String, "no \\escapes"),
+                (RegionKind::String, "{\"key\": \"vaule\"}"),
+                (RegionKind::String, "multi\nline)\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_c_include_directives() {
+        let src = "#include \"confg.h\"\n  #  include <sys/stdio.h> // comnt\n#define NAME \"strng\"\nprintf(\"#include \\\"x.h\\\"\");";
+        assert_eq!(
+            texts(Language::C, src),
+            vec![
+                (RegionKind::Comment, "// comnt"),
+                (RegionKind::String, "strng"),
+                (RegionKind::String, "#include "),
+                (RegionKind::String, "x.h"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_regions() {
+        let src = "def f():\n    \"\"\"Docstring.\"\"\"\n    x = 'it' # trailing\n    y = \"\"\"text\"\"\"\n";
+        assert_eq!(
+            texts(Language::Python, src),
+            vec![
+                (RegionKind::DocComment, "Docstring."),
+                (RegionKind::String, "it"),
+                (RegionKind::Comment, "# trailing"),
+                (RegionKind::String, "text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_javascript_and_go_backticks() {
+        let js = "const a = `tmpl ${x}`; /** doc */";
+        assert_eq!(
+            texts(Language::JavaScript, js),
+            vec![(RegionKind::String, "tmpl ${x}"), (RegionKind::DocComment, "/** doc */")]
+        );
+
+        let go = "s := `raw\\n` + string('x')";
+        assert_eq!(texts(Language::Go, go), vec![(RegionKind::String, "raw\\n")]);
+    }
+
+    #[test]
+    fn test_mask_preserves_offsets() {
+        let src = "let x = \"héllo\";\nfoo();";
+        let regions = extract_regions(Language::Rust, src, &LanguagesConfig::default());
+        let masked = mask(src, regions.iter().map(|r| &r.range));
+        assert_eq!(masked.len(), src.len());
+        assert_eq!(masked, "         héllo  \n      ");
+    }
+}
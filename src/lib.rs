@@ -3,8 +3,29 @@
 //! `spell_check` is a high-performance, memory-safe spell-checking tool designed for developers.
 //! It supports concurrent scanning, custom dictionaries, and robust configuration.
 
+pub mod asciidoc;
+pub mod baseline;
+pub mod cache;
 pub mod config;
 pub mod config_schema;
+pub mod data;
 pub mod engine;
 pub mod cli;
+pub mod compiled;
 pub mod dictionary;
+pub mod fix;
+pub mod git;
+pub mod hunspell;
+pub mod html;
+pub mod latex;
+pub mod lexer;
+pub mod lsp;
+pub mod markdown;
+pub mod notebook;
+pub mod patterns;
+pub mod report;
+pub mod review;
+pub mod rst;
+pub mod suggest;
+pub mod tokenizer;
+pub mod watch;
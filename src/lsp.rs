@@ -0,0 +1,402 @@
+//! Language Server Protocol mode.
+//!
+//! `spell_check lsp` speaks LSP over stdio so editors use the same tokenizer,
+//! dictionary and include/exclude globs as `spell_check check`. Documents are
+//! checked from their in-memory text once changes pause for `DEBOUNCE`, and
+//! each misspelling of the latest version is published as a diagnostic with
+//! quick fixes for its suggestions and a command that adds the word to
+//! `spellcheck.toml`.
+
+use crate::config::{add_extra_word, load_project};
+use crate::engine::{Engine, SpellError};
+use crate::fix;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+/// The diagnostic source shown by editors.
+const SOURCE: &str = "spell_check";
+
+/// The diagnostic code of all misspellings.
+const CODE: &str = "spelling/unknown-word";
+
+/// The command adding a word to the project dictionary.
+const ADD_TO_DICTIONARY: &str = "spell_check.addToDictionary";
+
+/// How long a document must go without changes before it is checked again.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Runs the language server on stdin/stdout until the client disconnects.
+///
+/// The workspace root is taken from the client; the config file is
+/// `config_override` if given, otherwise `spellcheck.toml` in the root.
+pub async fn serve(config_override: Option<PathBuf>) -> anyhow::Result<()> {
+    let (service, socket) = LspService::new(|client| Backend {
+        client,
+        config_override,
+        workspace: Arc::new(RwLock::new(None)),
+        documents: Arc::new(RwLock::new(HashMap::new())),
+    });
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket).serve(service).await;
+    Ok(())
+}
+
+/// The engine for the open workspace and where its config lives.
+struct Workspace {
+    engine: Engine,
+    root: PathBuf,
+    config_path: PathBuf,
+}
+
+impl Workspace {
+    /// Loads the configs and dictionaries for `root`, using `config_override`
+    /// instead of discovering config files if given.
+    ///
+    /// Loading the dictionary is CPU-bound, so this runs on a blocking thread.
+    async fn load(root: PathBuf, config_override: Option<PathBuf>) -> anyhow::Result<Self> {
+        tokio::task::spawn_blocking(move || {
+            let project = load_project(&root, config_override.as_deref())?;
+            let config_path = project.path.clone();
+            let engine = Engine::for_project(project)?;
+            Ok(Self { engine, root, config_path })
+        })
+        .await?
+    }
+}
+
+/// The text of an open document and the version the client gave it.
+#[derive(Clone)]
+struct Document {
+    text: String,
+    version: i32,
+}
+
+/// Returns `true` if `uri` is open at `version`, i.e. no change arrived
+/// since that version was stored.
+fn is_current(documents: &HashMap<Url, Document>, uri: &Url, version: i32) -> bool {
+    documents.get(uri).is_some_and(|document| document.version == version)
+}
+
+/// Shared with the tasks that check documents once their changes settle.
+#[derive(Clone)]
+struct Backend {
+    client: Client,
+    config_override: Option<PathBuf>,
+    workspace: Arc<RwLock<Option<Workspace>>>,
+    documents: Arc<RwLock<HashMap<Url, Document>>>,
+}
+
+impl Backend {
+    /// Checks the open document `uri` and publishes its diagnostics.
+    ///
+    /// Files outside the workspace root or not matched by the globs get an
+    /// empty set of diagnostics, clearing any stale ones. The results are
+    /// dropped if the document changed or closed during the check.
+    async fn publish(&self, uri: Url) {
+        let Some(Document { text, version }) = self.documents.read().await.get(&uri).cloned() else {
+            return;
+        };
+        let engine = {
+            let workspace = self.workspace.read().await;
+            let Some(workspace) = workspace.as_ref() else {
+                return;
+            };
+            match uri.to_file_path() {
+                Ok(path) => path
+                    .strip_prefix(&workspace.root)
+                    .ok()
+                    .filter(|relative| workspace.engine.should_check(relative))
+                    .map(|relative| (workspace.engine.clone(), relative.to_path_buf())),
+                Err(()) => None,
+            }
+        };
+
+        let diagnostics = match engine {
+            Some((engine, relative)) => {
+                tokio::task::spawn_blocking(move || engine.check_text(&relative, &text))
+                    .await
+                    .unwrap_or_default()
+                    .iter()
+                    .map(diagnostic)
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        if !is_current(&*self.documents.read().await, &uri, version) {
+            return;
+        }
+        self.client.publish_diagnostics(uri, diagnostics, Some(version)).await;
+    }
+
+    /// Re-checks every open document, e.g. after the dictionary changed.
+    async fn publish_all(&self) {
+        let uris: Vec<Url> = self.documents.read().await.keys().cloned().collect();
+        for uri in uris {
+            self.publish(uri).await;
+        }
+    }
+
+    /// Adds `word` to the project config and reloads the workspace engine.
+    async fn add_to_dictionary(&self, word: &str) -> anyhow::Result<()> {
+        let (root, config_path) = {
+            let workspace = self.workspace.read().await;
+            let workspace = workspace.as_ref().ok_or_else(|| anyhow::anyhow!("Workspace is not initialized"))?;
+            (workspace.root.clone(), workspace.config_path.clone())
+        };
+        add_extra_word(&config_path, word)?;
+        let reloaded = Workspace::load(root, self.config_override.clone()).await?;
+        *self.workspace.write().await = Some(reloaded);
+        Ok(())
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let root = params
+            .workspace_folders
+            .as_ref()
+            .and_then(|folders| folders.first())
+            .map(|folder| &folder.uri)
+            .or(params.root_uri.as_ref())
+            .and_then(|uri| uri.to_file_path().ok())
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."));
+        let workspace = Workspace::load(root, self.config_override.clone()).await.map_err(|e| Error {
+            code: tower_lsp::jsonrpc::ErrorCode::InternalError,
+            message: format!("Failed to load spell_check config: {e:#}").into(),
+            data: None,
+        })?;
+        *self.workspace.write().await = Some(workspace);
+
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![ADD_TO_DICTIONARY.to_string()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = Document { text: params.text_document.text, version: params.text_document.version };
+        let uri = params.text_document.uri;
+        self.documents.write().await.insert(uri.clone(), document);
+        self.publish(uri).await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // With full sync the last change holds the whole document.
+        let Some(change) = params.content_changes.into_iter().last() else {
+            return;
+        };
+        let (uri, version) = (params.text_document.uri, params.text_document.version);
+        self.documents.write().await.insert(uri.clone(), Document { text: change.text, version });
+
+        // Check once typing pauses; a newer change supersedes this one.
+        let backend = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(DEBOUNCE).await;
+            if is_current(&*backend.documents.read().await, &uri, version) {
+                backend.publish(uri).await;
+            }
+        });
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents.write().await.remove(&uri);
+        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let actions = code_actions(&params.text_document.uri, &params.context.diagnostics);
+        Ok((!actions.is_empty()).then_some(actions))
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        if params.command != ADD_TO_DICTIONARY {
+            return Err(Error::method_not_found());
+        }
+        let Some(word) = params.arguments.first().and_then(Value::as_str) else {
+            return Err(Error::invalid_params("Expected the word to add"));
+        };
+
+        if let Err(e) = self.add_to_dictionary(word).await {
+            self.client
+                .show_message(MessageType::ERROR, format!("Failed to add '{word}' to the dictionary: {e:#}"))
+                .await;
+            return Ok(None);
+        }
+        self.publish_all().await;
+        Ok(None)
+    }
+}
+
+/// Converts a `SpellError` into an LSP diagnostic.
+///
+/// LSP positions count UTF-16 code units, so the byte column is converted
+/// using the line in `context`. The range covers the word as written, so
+/// quick fixes replace all of `caf&eacute;`. The word and its suggestions
+/// are kept in `data` for code actions.
+fn diagnostic(error: &SpellError) -> Diagnostic {
+    let prefix = error.context.get(..error.col.saturating_sub(1)).unwrap_or("");
+    let line = (error.line - 1) as u32;
+    let start = utf16_len(prefix);
+    let end = start + utf16_len(error.source());
+
+    let mut message = format!("Unknown word '{}'", error.word);
+    if !error.suggestions.is_empty() {
+        message.push_str(&format!(". Did you mean: {}?", error.suggestions.join(", ")));
+    }
+
+    Diagnostic {
+        range: Range::new(Position::new(line, start), Position::new(line, end)),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(CODE.to_string())),
+        source: Some(SOURCE.to_string()),
+        message,
+        data: Some(json!({ "word": error.word, "suggestions": error.suggestions })),
+        ..Default::default()
+    }
+}
+
+/// Builds the quick fixes for our diagnostics among `diagnostics`.
+///
+/// Each suggestion becomes an edit replacing the word (the first one is
+/// preferred), followed by a command adding the word to the dictionary.
+fn code_actions(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    for diagnostic in diagnostics {
+        if diagnostic.source.as_deref() != Some(SOURCE) {
+            continue;
+        }
+        let Some(data) = &diagnostic.data else {
+            continue;
+        };
+        let Some(word) = data["word"].as_str() else {
+            continue;
+        };
+
+        let suggestions = data["suggestions"].as_array().map(Vec::as_slice).unwrap_or_default();
+        for (i, suggestion) in suggestions.iter().filter_map(Value::as_str).enumerate() {
+            let replacement = fix::match_case(word, suggestion);
+            let edit = TextEdit::new(diagnostic.range, replacement.clone());
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Replace with '{replacement}'"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit::new(HashMap::from([(uri.clone(), vec![edit])]))),
+                is_preferred: Some(i == 0),
+                ..Default::default()
+            }));
+        }
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Add '{word}' to dictionary"),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            command: Some(Command::new(
+                format!("Add '{word}' to dictionary"),
+                ADD_TO_DICTIONARY.to_string(),
+                Some(vec![json!(word)]),
+            )),
+            ..Default::default()
+        }));
+    }
+    actions
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error() -> SpellError {
+        SpellError {
+            file: PathBuf::from("src/main.rs"),
+            line: 3,
+            col: 12,
+            span: 40..49,
+            word: "Occurance".to_string(),
+            context: "// 😀 é Occurance here".to_string(),
+            suggestions: vec!["occurrence".to_string(), "occurrences".to_string()],
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_diagnostic_uses_utf16_columns() {
+        let d = diagnostic(&error());
+        // "// 😀 é " is 11 bytes but 8 UTF-16 code units.
+        assert_eq!(d.range, Range::new(Position::new(2, 8), Position::new(2, 17)));
+        assert_eq!(d.source.as_deref(), Some(SOURCE));
+        assert_eq!(d.message, "Unknown word 'Occurance'. Did you mean: occurrence, occurrences?");
+    }
+
+    #[test]
+    fn test_diagnostic_covers_decoded_word() {
+        let error = SpellError {
+            col: 11,
+            span: 10..24,
+            word: "Occurance".to_string(),
+            context: "{\"text\": \"Occ\\u0075rance\"}".to_string(),
+            ..error()
+        };
+        let d = diagnostic(&error);
+        assert_eq!(d.range, Range::new(Position::new(2, 10), Position::new(2, 24)));
+    }
+
+    #[test]
+    fn test_is_current() {
+        let uri = Url::parse("file:///project/README.md").unwrap();
+        let mut documents = HashMap::new();
+        assert!(!is_current(&documents, &uri, 1));
+
+        documents.insert(uri.clone(), Document { text: "teh".to_string(), version: 1 });
+        assert!(is_current(&documents, &uri, 1));
+        documents.insert(uri.clone(), Document { text: "the".to_string(), version: 2 });
+        assert!(!is_current(&documents, &uri, 1));
+        assert!(is_current(&documents, &uri, 2));
+    }
+
+    #[test]
+    fn test_code_actions() {
+        let uri = Url::parse("file:///project/src/main.rs").unwrap();
+        let foreign = Diagnostic { source: Some("rustc".to_string()), ..diagnostic(&error()) };
+        let actions = code_actions(&uri, &[diagnostic(&error()), foreign]);
+        assert_eq!(actions.len(), 3);
+
+        let CodeActionOrCommand::CodeAction(first) = &actions[0] else { panic!("expected a code action") };
+        assert_eq!(first.title, "Replace with 'Occurrence'");
+        assert_eq!(first.is_preferred, Some(true));
+        let edits = &first.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].new_text, "Occurrence");
+
+        let CodeActionOrCommand::CodeAction(add) = &actions[2] else { panic!("expected a code action") };
+        let command = add.command.as_ref().unwrap();
+        assert_eq!(command.command, ADD_TO_DICTIONARY);
+        assert_eq!(command.arguments, Some(vec![json!("Occurance")]));
+    }
+}
@@ -0,0 +1,397 @@
+//! Markdown-aware extraction of prose.
+//!
+//! Markdown mixes prose with code, link targets, HTML and metadata. This
+//! module finds the human-readable text of a CommonMark document (with GFM
+//! tables): paragraphs, headings, list items, table cells, link text and
+//! image alt text. Code blocks, code spans, link destinations, reference
+//! definitions, autolinks, HTML tags and front matter are left out; HTML
+//! comments are returned as comments.
+
+use crate::lexer::{Region, RegionKind, indentation, lines};
+use std::ops::Range;
+
+/// Elements whose content is not Markdown and is skipped up to the closing tag.
+const RAW_ELEMENTS: &[&str] = &["script", "pre", "style", "textarea"];
+
+/// Extracts the prose and HTML comments of a Markdown document.
+pub fn extract_regions(content: &str) -> Vec<Region> {
+    let mut regions = Vec::new();
+    for paragraph in Blocks::new(content, &mut regions).run() {
+        Inline {
+            src: content.as_bytes(),
+            pos: paragraph.start,
+            end: paragraph.end,
+            text_start: paragraph.start,
+            regions: &mut regions,
+        }
+        .run();
+    }
+    regions.sort_by_key(|r| r.range.start);
+    regions
+}
+
+/// Returns the fence character and length if `trimmed` opens a code fence.
+fn fence(trimmed: &str) -> Option<(char, usize)> {
+    let c = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|&ch| ch == c).count();
+    // A backtick fence's info string cannot contain backticks.
+    (len >= 3 && (c == '~' || !trimmed[len..].contains('`'))).then_some((c, len))
+}
+
+/// Returns `true` if `trimmed` is a bullet or ordered list item marker line.
+fn is_list_item(trimmed: &str) -> bool {
+    let rest = if let Some(rest) = trimmed.strip_prefix(['-', '*', '+']) {
+        rest
+    } else {
+        let digits = trimmed.bytes().take_while(u8::is_ascii_digit).count();
+        match trimmed[digits..].strip_prefix(['.', ')']) {
+            Some(rest) if (1..=9).contains(&digits) => rest,
+            _ => return false,
+        }
+    };
+    rest.is_empty() || rest.starts_with([' ', '\t'])
+}
+
+/// Returns `true` for a link reference definition such as `[label]: /url "title"`.
+///
+/// Footnote definitions (`[^note]: text`) contain prose and are not matched.
+fn is_reference_definition(trimmed: &str) -> bool {
+    let Some(rest) = trimmed.strip_prefix('[') else { return false };
+    if rest.starts_with('^') {
+        return false;
+    }
+    match rest.find("]:") {
+        Some(close) => close > 0 && !rest[..close].contains('['),
+        None => false,
+    }
+}
+
+/// Returns the name of the raw element `trimmed` opens, if any.
+fn raw_element(trimmed: &str) -> Option<&'static str> {
+    let tag = trimmed.strip_prefix('<')?;
+    RAW_ELEMENTS.iter().copied().find(|name| {
+        tag.get(..name.len()).is_some_and(|t| t.eq_ignore_ascii_case(name))
+            && tag[name.len()..].chars().next().is_none_or(|c| c == '>' || c.is_whitespace())
+    })
+}
+
+/// The block structure pass: finds paragraphs and skips everything else.
+struct Blocks<'a, 'r> {
+    content: &'a str,
+    lines: Vec<Range<usize>>,
+    regions: &'r mut Vec<Region>,
+    paragraphs: Vec<Range<usize>>,
+    paragraph: Option<Range<usize>>,
+}
+
+impl<'a, 'r> Blocks<'a, 'r> {
+    fn new(content: &'a str, regions: &'r mut Vec<Region>) -> Self {
+        Self { content, lines: lines(content), regions, paragraphs: Vec::new(), paragraph: None }
+    }
+
+    fn line(&self, i: usize) -> &'a str {
+        &self.content[self.lines[i].clone()]
+    }
+
+    fn run(mut self) -> Vec<Range<usize>> {
+        let mut i = self.front_matter();
+        let mut in_list = false;
+        let mut after_blank = true;
+        while i < self.lines.len() {
+            let line = self.line(i);
+            let indent = indentation(line);
+            let trimmed = line.trim_start();
+
+            if trimmed.is_empty() {
+                self.flush();
+                after_blank = true;
+                i += 1;
+                continue;
+            }
+            let starts_block = self.paragraph.is_none();
+            let follows_blank = std::mem::take(&mut after_blank);
+
+            if let Some((c, len)) = fence(trimmed) {
+                self.flush();
+                i = self.skip_fence(i + 1, c, len);
+            } else if indent >= 4 && starts_block && !in_list {
+                // Indented code block.
+                i += 1;
+            } else if indent < 4 && trimmed.starts_with("<!--") {
+                self.flush();
+                i = self.html_comment(i);
+            } else if let Some(name) = raw_element(trimmed).filter(|_| indent < 4) {
+                self.flush();
+                let close = format!("</{}>", name);
+                i = (i..self.lines.len())
+                    .find(|&j| self.line(j).to_ascii_lowercase().contains(&close))
+                    .map_or(self.lines.len(), |j| j + 1);
+            } else if starts_block && is_reference_definition(trimmed) {
+                i += 1;
+            } else {
+                if is_list_item(trimmed) {
+                    in_list = true;
+                } else if indent == 0 && follows_blank {
+                    in_list = false;
+                }
+                // Headings are paragraphs of their own.
+                let heading = trimmed.starts_with('#');
+                if heading {
+                    self.flush();
+                }
+                let range = self.lines[i].clone();
+                self.paragraph = Some(self.paragraph.take().map_or(range.clone(), |p| p.start..range.end));
+                if heading {
+                    self.flush();
+                }
+                i += 1;
+            }
+        }
+        self.flush();
+        self.paragraphs
+    }
+
+    fn flush(&mut self) {
+        self.paragraphs.extend(self.paragraph.take());
+    }
+
+    /// Skips YAML (`---`) or TOML (`+++`) front matter, returning the first line after it.
+    fn front_matter(&self) -> usize {
+        let Some(open) = (!self.lines.is_empty()).then(|| self.line(0).trim_end()) else { return 0 };
+        if open != "---" && open != "+++" {
+            return 0;
+        }
+        (1..self.lines.len())
+            .find(|&j| {
+                let close = self.line(j).trim_end();
+                close == open || (open == "---" && close == "...")
+            })
+            .map_or(0, |j| j + 1)
+    }
+
+    /// Skips the body of a code fence, returning the line after the closing fence.
+    fn skip_fence(&self, from: usize, c: char, len: usize) -> usize {
+        (from..self.lines.len())
+            .find(|&j| {
+                let trimmed = self.line(j).trim();
+                trimmed.chars().take_while(|&ch| ch == c).count() >= len && trimmed.chars().all(|ch| ch == c)
+            })
+            .map_or(self.lines.len(), |j| j + 1)
+    }
+
+    /// Records an HTML comment block starting on line `i`, returning the line after it.
+    fn html_comment(&mut self, i: usize) -> usize {
+        let start = self.lines[i].start + self.line(i).find("<!--").unwrap_or(0);
+        let end = match self.content[start + 4..].find("-->") {
+            Some(close) => start + 4 + close + 3,
+            None => self.content.len(),
+        };
+        self.regions.push(Region { kind: RegionKind::Comment, range: start..end });
+        (i..self.lines.len()).find(|&j| self.lines[j].end >= end).map_or(self.lines.len(), |j| j + 1)
+    }
+}
+
+/// The inline pass over one paragraph.
+struct Inline<'a> {
+    src: &'a [u8],
+    pos: usize,
+    end: usize,
+    /// Where the current run of prose started.
+    text_start: usize,
+    regions: &'a mut Vec<Region>,
+}
+
+impl Inline<'_> {
+    fn run(mut self) {
+        while self.pos < self.end {
+            match self.src[self.pos] {
+                b'\\' => self.pos = (self.pos + 2).min(self.end),
+                b'`' => self.code_span(),
+                b'<' => self.html(),
+                b']' => self.link_target(),
+                b'[' if self.peek(1) == Some(b'^') => {
+                    // Footnote labels are identifiers, not prose.
+                    let close = self.find(self.pos, b"]").map_or(self.pos + 1, |c| c + 1);
+                    self.skip(self.pos..close);
+                }
+                _ => self.pos += 1,
+            }
+        }
+        let range = self.text_start..self.end;
+        self.push_text(range);
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        let i = self.pos + offset;
+        (i < self.end).then(|| self.src[i])
+    }
+
+    /// Returns the position of the next `pat` at or after `from` within the paragraph.
+    fn find(&self, from: usize, pat: &[u8]) -> Option<usize> {
+        self.src[from..self.end].windows(pat.len()).position(|w| w == pat).map(|p| from + p)
+    }
+
+    fn push(&mut self, kind: RegionKind, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region { kind, range });
+        }
+    }
+
+    fn push_text(&mut self, range: Range<usize>) {
+        self.push(RegionKind::Text, range);
+    }
+
+    /// Ends the current run of prose before `range` and resumes after it.
+    fn skip(&mut self, range: Range<usize>) {
+        let text = self.text_start..range.start;
+        self.push_text(text);
+        self.text_start = range.end;
+        self.pos = range.end;
+    }
+
+    fn backticks(&self, from: usize) -> usize {
+        self.src[from..self.end].iter().take_while(|&&b| b == b'`').count()
+    }
+
+    /// Skips a code span, or the backticks alone if the span is not closed.
+    fn code_span(&mut self) {
+        let len = self.backticks(self.pos);
+        let mut i = self.pos + len;
+        while let Some(next) = self.find(i, b"`") {
+            let run = self.backticks(next);
+            if run == len {
+                self.skip(self.pos..next + run);
+                return;
+            }
+            i = next + run;
+        }
+        self.pos += len;
+    }
+
+    /// Skips HTML tags, autolinks and declarations; records HTML comments.
+    fn html(&mut self) {
+        if self.src[self.pos..self.end].starts_with(b"<!--") {
+            let end = self.find(self.pos + 4, b"-->").map_or(self.end, |c| c + 3);
+            let range = self.pos..end;
+            self.skip(range.clone());
+            self.push(RegionKind::Comment, range);
+            return;
+        }
+        if !self.peek(1).is_some_and(|b| b.is_ascii_alphabetic() || matches!(b, b'/' | b'!' | b'?')) {
+            self.pos += 1;
+            return;
+        }
+        let mut i = self.pos + 1;
+        while i < self.end {
+            match self.src[i] {
+                b'>' => {
+                    self.skip(self.pos..i + 1);
+                    return;
+                }
+                b'<' => break,
+                quote @ (b'"' | b'\'') => match self.find(i + 1, &[quote]) {
+                    Some(close) => i = close + 1,
+                    None => break,
+                },
+                _ => i += 1,
+            }
+        }
+        self.pos += 1;
+    }
+
+    /// Skips the destination of `[text](url "title")`, keeping the title, and
+    /// the label of `[text][label]`.
+    fn link_target(&mut self) {
+        match self.peek(1) {
+            Some(b'(') => {}
+            Some(b'[') => {
+                let close = self.find(self.pos + 1, b"]").map_or(self.end, |c| c + 1);
+                self.skip(self.pos + 1..close);
+                return;
+            }
+            _ => {
+                self.pos += 1;
+                return;
+            }
+        }
+        let open = self.pos + 1;
+        let mut i = open + 1;
+        let skip_space = |i: &mut usize| {
+            while *i < self.end && self.src[*i].is_ascii_whitespace() {
+                *i += 1;
+            }
+        };
+        skip_space(&mut i);
+        if self.src.get(i) == Some(&b'<') {
+            i = self.find(i, b">").map_or(self.end, |c| c + 1);
+        } else {
+            let mut depth = 0;
+            while i < self.end && !self.src[i].is_ascii_whitespace() {
+                match self.src[i] {
+                    b'(' => depth += 1,
+                    b')' if depth == 0 => break,
+                    b')' => depth -= 1,
+                    b'\\' => i += 1,
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+        skip_space(&mut i);
+        let title_close = match self.src.get(i).filter(|_| i < self.end) {
+            Some(b'"') => Some(b'"'),
+            Some(b'\'') => Some(b'\''),
+            Some(b'(') => Some(b')'),
+            _ => None,
+        };
+        if let Some(close) = title_close
+            && let Some(end) = self.find(i + 1, &[close])
+        {
+            self.skip(self.pos + 1..i + 1);
+            self.pos = end;
+            i = end + 1;
+        }
+        let close = self.find(i, b")").map_or(self.end, |c| c + 1);
+        self.skip(self.pos.max(open)..close);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(content: &str) -> Vec<(RegionKind, &str)> {
+        extract_regions(content).into_iter().map(|r| (r.kind, &content[r.range])).collect()
+    }
+
+    fn prose(content: &str) -> String {
+        extract_regions(content)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::Text)
+            .map(|r| content[r.range].to_string())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    #[test]
+    fn test_blocks() {
+        let doc = "---\ntitle: Frnt matter\n---\n# Heading\n\nSome text.\n\n```rust\nlet wrod = 1;\n```\n\n    indented cdoe\n\n[ref]: https://exmaple.com \"Titel\"\n<!-- a\n\nnote -->\n<script>\nvar x;\n</script>\nEnd.\n";
+        assert_eq!(prose(doc), "# Heading|Some text.|End.");
+        assert!(texts(doc).contains(&(RegionKind::Comment, "<!-- a\n\nnote -->")));
+    }
+
+    #[test]
+    fn test_lists_and_tables() {
+        let doc = "- item one\n\n    continued item\n\n| Name | Value |\n|------|-------|\n| `cde` | text |\n";
+        assert_eq!(prose(doc), "- item one|    continued item|| Name | Value |\n|------|-------|\n| | | text |");
+    }
+
+    #[test]
+    fn test_inline() {
+        let doc = "Use `cargo bild` or ``a ` b``, see [the docs](https://exmaple.com/pth \"Docs titel\") and ![alt txt](img.png).\n";
+        assert_eq!(prose(doc), "Use | or |, see [the docs]|Docs titel| and ![alt txt]|.");
+        let doc = "A <span class=\"bdge\">tag</span>, <https://x.io/pth>, &nbsp; [text][lbl], note[^fnote] <!-- cmnt -->\n";
+        assert_eq!(prose(doc), "A |tag|, |, &nbsp; [text]|, note| ");
+        assert!(texts(doc).contains(&(RegionKind::Comment, "<!-- cmnt -->")));
+    }
+}
@@ -0,0 +1,307 @@
+//! Jupyter notebook extraction.
+//!
+//! A notebook is a JSON document whose cells hold their source as JSON
+//! strings. This module finds the source of each cell with its byte offsets,
+//! checks Markdown cells as Markdown and code cells in the notebook's kernel
+//! language, and maps the regions found back to the JSON text. Outputs,
+//! metadata, raw cells and the JSON syntax itself are left out.
+
+use crate::config_schema::LanguagesConfig;
+use crate::data::json::{self, Value};
+use crate::html;
+use crate::lexer::{self, Language, Region};
+use crate::markdown;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Extracts the prose, comments and strings of the cells of a notebook.
+///
+/// Returns no regions if `content` is not a notebook.
+pub fn extract_regions(content: &str, config: &LanguagesConfig) -> Vec<Region> {
+    Notebook::parse(content).map_or_else(Vec::new, |notebook| notebook.regions(config))
+}
+
+/// A position within the source of a notebook cell.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellPosition {
+    /// The 1-based index of the cell in the notebook.
+    pub index: usize,
+    /// The 1-based line number within the cell.
+    pub line: usize,
+    /// The 1-based column number within the line.
+    pub col: usize,
+    /// The content of the line within the cell.
+    pub context: String,
+}
+
+/// The cells of a parsed notebook.
+pub struct Notebook {
+    /// The language of code cells, or `None` if it is not supported.
+    language: Option<Language>,
+    cells: Vec<Cell>,
+}
+
+struct Cell {
+    /// The 1-based index of the cell in the notebook.
+    index: usize,
+    kind: CellKind,
+    /// The decoded source of the cell.
+    text: String,
+    /// How `text` maps to the JSON text, in order.
+    segments: Vec<Segment>,
+}
+
+enum CellKind {
+    Markdown,
+    Code,
+    Raw,
+}
+
+/// A run of decoded text and the JSON text it came from: either characters
+/// copied verbatim, with ranges of equal length, or a single escape sequence,
+/// whose decoded text is always shorter than the escape.
+struct Segment {
+    decoded: Range<usize>,
+    original: Range<usize>,
+}
+
+impl Segment {
+    fn is_verbatim(&self) -> bool {
+        self.decoded.len() == self.original.len()
+    }
+}
+
+impl Notebook {
+    /// Parses a notebook, or returns `None` if `content` is not one.
+    pub fn parse(content: &str) -> Option<Self> {
+        let root = json::parse(content)?;
+        let metadata = root.get("metadata");
+        let language = metadata
+            .and_then(|m| m.get("kernelspec"))
+            .and_then(|k| k.get("language"))
+            .or_else(|| metadata.and_then(|m| m.get("language_info")).and_then(|l| l.get("name")))
+            .and_then(|name| name.as_string(content))
+            .map_or(Some(Language::Python), |name| kernel_language(&name));
+
+        let Value::Array(values) = root.get("cells")? else { return None };
+        let mut cells = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            let kind = match value.get("cell_type").and_then(|t| t.as_string(content)).as_deref() {
+                Some("markdown") => CellKind::Markdown,
+                Some("code") => CellKind::Code,
+                _ => CellKind::Raw,
+            };
+            let mut cell = Cell { index: i + 1, kind, text: String::new(), segments: Vec::new() };
+            match value.get("source") {
+                Some(Value::String(raw)) => decode(content, raw.clone(), &mut cell.text, &mut cell.segments),
+                Some(Value::Array(lines)) => {
+                    for line in lines {
+                        if let Value::String(raw) = line {
+                            decode(content, raw.clone(), &mut cell.text, &mut cell.segments);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            cells.push(cell);
+        }
+        Some(Self { language, cells })
+    }
+
+    /// Extracts the regions of every cell, as ranges into the JSON text.
+    pub fn regions(&self, config: &LanguagesConfig) -> Vec<Region> {
+        let mut regions = Vec::new();
+        for cell in &self.cells {
+            let found = match (&cell.kind, self.language) {
+                (CellKind::Markdown, _) => markdown::extract_regions(&cell.text),
+                (CellKind::Code, Some(language)) => lexer::extract_regions(language, &cell.text, config),
+                _ => continue,
+            };
+            for region in found {
+                for range in cell.to_original(region.range) {
+                    regions.push(Region { kind: region.kind, range });
+                }
+            }
+        }
+        regions
+    }
+
+    /// Returns the cell position of a byte offset into the JSON text, or
+    /// `None` if it is not inside the source of a cell.
+    pub fn position(&self, offset: usize) -> Option<CellPosition> {
+        let cell = self.cells.iter().find(|c| {
+            c.segments.first().is_some_and(|s| s.original.start <= offset)
+                && c.segments.last().is_some_and(|s| offset < s.original.end)
+        })?;
+        let segment = cell.segments.iter().find(|s| s.original.contains(&offset))?;
+        let decoded = if segment.is_verbatim() {
+            segment.decoded.start + (offset - segment.original.start)
+        } else {
+            segment.decoded.start
+        };
+        let line_start = cell.text[..decoded].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = cell.text[decoded..].find('\n').map_or(cell.text.len(), |i| decoded + i);
+        Some(CellPosition {
+            index: cell.index,
+            line: cell.text[..line_start].matches('\n').count() + 1,
+            col: decoded - line_start + 1,
+            context: cell.text[line_start..line_end].trim_end_matches('\r').to_string(),
+        })
+    }
+}
+
+impl Cell {
+    /// Maps a range of the decoded source to the ranges of JSON text it came
+    /// from, split where the source continues in another JSON string.
+    fn to_original(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        let first = self.segments.partition_point(|s| s.decoded.end <= range.start);
+        for segment in self.segments[first..].iter().take_while(|s| s.decoded.start < range.end) {
+            let original = if segment.is_verbatim() {
+                let start = range.start.max(segment.decoded.start) - segment.decoded.start;
+                let end = range.end.min(segment.decoded.end) - segment.decoded.start;
+                segment.original.start + start..segment.original.start + end
+            } else {
+                segment.original.clone()
+            };
+            match ranges.last_mut() {
+                Some(last) if last.end == original.start => last.end = original.end,
+                _ => ranges.push(original),
+            }
+        }
+        ranges
+    }
+}
+
+/// Returns the lexer for a kernel language name such as `python`.
+fn kernel_language(name: &str) -> Option<Language> {
+    match name.to_ascii_lowercase().as_str() {
+        "python" | "python3" => Some(Language::Python),
+        "rust" => Some(Language::Rust),
+        "javascript" | "typescript" => Some(Language::JavaScript),
+        "go" => Some(Language::Go),
+        "c" | "c++" | "cpp" => Some(Language::C),
+        "java" => Some(Language::Java),
+        _ => None,
+    }
+}
+
+/// Finds the JSON escape sequences and character references in the checked
+/// text of a notebook, returning each with its decoded text.
+pub fn references(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut found = json::escapes(text);
+    let escapes = found.len();
+    for (range, decoded) in html::entities(text) {
+        if !found[..escapes].iter().any(|(e, _)| e.start < range.end && range.start < e.end) {
+            found.push((range, decoded));
+        }
+    }
+    found.sort_by_key(|(range, _)| range.start);
+    found
+}
+
+/// Appends the decoded contents of the JSON string literal at `raw`, without
+/// its quotes, to `text`, recording how it maps to the JSON text.
+fn decode(content: &str, raw: Range<usize>, text: &mut String, segments: &mut Vec<Segment>) {
+    let mut verbatim = raw.start;
+    let mut i = raw.start;
+    while i < raw.end {
+        if content.as_bytes()[i] != b'\\' {
+            i += 1;
+            continue;
+        }
+        push_segment(text, segments, &content[verbatim..i], verbatim..i);
+        let (c, len) = json::escape(content, i);
+        let len = len.min(raw.end - i);
+        push_segment(text, segments, c.encode_utf8(&mut [0; 4]), i..i + len);
+        i += len;
+        verbatim = i;
+    }
+    push_segment(text, segments, &content[verbatim..raw.end], verbatim..raw.end);
+}
+
+fn push_segment(text: &mut String, segments: &mut Vec<Segment>, decoded: &str, original: Range<usize>) {
+    if !original.is_empty() {
+        let start = text.len();
+        text.push_str(decoded);
+        segments.push(Segment { decoded: start..text.len(), original });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::RegionKind;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Intro\n",
+    "Some \"quoted\" caf\u00e9 `cde` text."
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [{"name": "stdout", "output_type": "stream", "text": ["outptu\n"]}],
+   "source": "import os  # a commnt\nprint(\"helo\\n\")"
+  },
+  {
+   "cell_type": "raw",
+   "metadata": {},
+   "source": ["raw txt"]
+  }
+ ],
+ "metadata": {"kernelspec": {"display_name": "Python 3", "language": "python", "name": "python3"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    fn regions(content: &str) -> Vec<(RegionKind, &str)> {
+        extract_regions(content, &LanguagesConfig::default())
+            .into_iter()
+            .map(|r| (r.kind, &content[r.range]))
+            .collect()
+    }
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(
+            regions(NOTEBOOK),
+            vec![
+                (RegionKind::Text, "# Intro"),
+                (RegionKind::Text, "Some \\\"quoted\\\" caf\\u00e9 "),
+                (RegionKind::Text, " text."),
+                (RegionKind::Comment, "# a commnt"),
+                (RegionKind::String, "helo"),
+            ]
+        );
+        assert!(regions("{\"cells\": [").is_empty());
+        assert!(regions("not json").is_empty());
+    }
+
+    #[test]
+    fn test_position() {
+        let notebook = Notebook::parse(NOTEBOOK).expect("notebook");
+        let at = |word: &str| notebook.position(NOTEBOOK.find(word).expect("word"));
+        let cell = at("text.").expect("position");
+        assert_eq!((cell.index, cell.line, cell.col), (1, 2, 27));
+        assert_eq!(cell.context, "Some \"quoted\" café `cde` text.");
+        let cell = at("commnt").expect("position");
+        assert_eq!((cell.index, cell.line, cell.col), (2, 1, 16));
+        assert_eq!(at("outptu"), None);
+    }
+
+    #[test]
+    fn test_references() {
+        let found = references("caf\\u00e9 \\ud83d\\ude00 \\\"a\\\" &amp;");
+        let decoded: Vec<&str> = found.iter().map(|(_, d)| d.as_str()).collect();
+        assert_eq!(decoded, vec!["é", "😀", "\"", "\"", "&"]);
+        assert_eq!(found[1].0, 10..22);
+    }
+}
@@ -0,0 +1,160 @@
+//! Ignoring text that matches patterns.
+//!
+//! URLs, email addresses, hashes and similar tokens are not prose, but the
+//! tokenizer still splits them into plausible-looking words. Matches of the
+//! built-in and configured patterns are blanked out before words are
+//! extracted, keeping byte offsets intact like `lexer::mask`.
+
+use crate::config_schema::IgnoreConfig;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::ops::Range;
+
+/// A compiled pattern and a check that rejects matches which only look like it.
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: Regex,
+    /// Called with the whole text and the range of a match.
+    accept: fn(&str, &Range<usize>) -> bool,
+}
+
+impl Pattern {
+    fn new(regex: &str, accept: fn(&str, &Range<usize>) -> bool) -> Self {
+        Self { regex: Regex::new(regex).expect("built-in pattern is valid"), accept }
+    }
+}
+
+fn any(_: &str, _: &Range<usize>) -> bool {
+    true
+}
+
+/// Hex runs are only hashes if they contain a digit; `defaced` is a word.
+fn has_digit(text: &str, range: &Range<usize>) -> bool {
+    text[range.clone()].bytes().any(|b| b.is_ascii_digit())
+}
+
+/// Base64 mixes cases with digits or symbols; long identifiers rarely do both.
+fn is_base64(text: &str, range: &Range<usize>) -> bool {
+    let blob = &text[range.clone()];
+    let digits = blob.bytes().filter(u8::is_ascii_digit).count();
+    blob.bytes().any(|b| b.is_ascii_uppercase())
+        && blob.bytes().any(|b| b.is_ascii_lowercase())
+        && (digits >= 2 || blob.contains(['+', '/', '=']))
+}
+
+/// Rooted paths must not continue a word, so `and/or` is not a path.
+fn starts_path(text: &str, range: &Range<usize>) -> bool {
+    text[..range.start].chars().next_back().is_none_or(|c| !(c.is_alphanumeric() || c == '_' || c == '/'))
+}
+
+/// The compiled ignore patterns of a config.
+#[derive(Debug, Clone, Default)]
+pub struct IgnorePatterns {
+    patterns: Vec<Pattern>,
+}
+
+impl IgnorePatterns {
+    /// Compiles the enabled built-in patterns and the configured ones.
+    pub fn from_config(config: &IgnoreConfig) -> Result<Self> {
+        let builtin = &config.builtin;
+        let mut patterns = Vec::new();
+        if builtin.urls {
+            patterns.push(Pattern::new(r#"(?i)\b(?:[a-z][a-z0-9+.-]*://|www\.|mailto:)[^\s<>"'`]+"#, any));
+        }
+        if builtin.emails {
+            patterns.push(Pattern::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b", any));
+        }
+        if builtin.hashes {
+            patterns.push(Pattern::new(r"\b[0-9a-fA-F]{8}(?:-[0-9a-fA-F]{4}){3}-[0-9a-fA-F]{12}\b", any));
+            patterns.push(Pattern::new(r"\b(?:0x)?[0-9a-fA-F]{7,}\b", has_digit));
+        }
+        if builtin.base64 {
+            patterns.push(Pattern::new(r"[A-Za-z0-9+/]{20,}={0,2}", is_base64));
+        }
+        if builtin.paths {
+            patterns.push(Pattern::new(r"(?:~|\.{1,2})?/[\w.@+-]+(?:/[\w.@+-]*)*", starts_path));
+            patterns.push(Pattern::new(r"\b[\w.-]+(?:/[\w.-]+)*/[\w-]+\.[A-Za-z0-9]+\b", any));
+            patterns.push(Pattern::new(r#"\b[A-Za-z]:\\[^\s"'<>|*?]*"#, any));
+        }
+        let mut ignore = Self { patterns };
+        ignore.patterns.extend(Self::new(&config.patterns)?.patterns);
+        Ok(ignore)
+    }
+
+    /// Compiles configured regular expressions.
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let regex = Regex::new(pattern).with_context(|| format!("Invalid ignore pattern: {}", pattern))?;
+                Ok(Pattern { regex, accept: any })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Returns the byte ranges of `text` matched by any pattern.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        self.patterns
+            .iter()
+            .flat_map(|pattern| {
+                pattern.regex.find_iter(text).map(|m| m.range()).filter(|range| (pattern.accept)(text, range))
+            })
+            .collect()
+    }
+}
+
+/// Replaces every character inside `ranges` with spaces, preserving newlines.
+///
+/// The counterpart of `lexer::mask`: the result has the same byte length and
+/// line structure as `text`.
+pub fn blank(text: &str, ranges: &[Range<usize>]) -> String {
+    let mut blanked = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        if c != '\n' && ranges.iter().any(|range| range.contains(&i)) {
+            blanked.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        } else {
+            blanked.push(c);
+        }
+    }
+    blanked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_schema::BuiltinPatterns;
+
+    fn ignored(config: &IgnoreConfig, text: &str) -> Vec<String> {
+        let patterns = IgnorePatterns::from_config(config).unwrap();
+        let blanked = blank(text, &patterns.find(text));
+        assert_eq!(blanked.len(), text.len());
+        crate::tokenizer::extract_tokens(&blanked).into_iter().map(|(_, token)| token.to_string()).collect()
+    }
+
+    #[test]
+    fn test_builtin_patterns() {
+        let config = IgnoreConfig::default();
+        let words = |text: &str| ignored(&config, text);
+        assert_eq!(words("See https://exampel.com/some-pth?q=1 now"), vec!["See", "now"]);
+        assert_eq!(words("Mail jdoe@exmaple.org or www.exmaple.org"), vec!["Mail", "or"]);
+        assert_eq!(words("Fixed in 4841c24, not defaced"), vec!["Fixed", "in", "not", "defaced"]);
+        assert_eq!(words("id 123e4567-e89b-12d3-a456-426614174000"), vec!["id"]);
+        assert_eq!(words("key: dGhpcyBpcyBhIHNlY3JldCBrZXk9PQ== end"), vec!["key", "end"]);
+        assert_eq!(words("parseHttpResponseHeaders"), vec!["parseHttpResponseHeaders"]);
+        assert_eq!(words("Edit ./src/cnfg.rs, /usr/lib/libfoo or C:\\Usrs\\me"), vec!["Edit", "or"]);
+        assert_eq!(words("Read docs/usge.md and/or this"), vec!["Read", "and", "or", "this"]);
+    }
+
+    #[test]
+    fn test_configured_patterns() {
+        let config = IgnoreConfig {
+            patterns: vec![r"JIRA-\d+".to_string(), r"(?s)BEGIN.*?END".to_string()],
+            builtin: BuiltinPatterns { urls: false, ..BuiltinPatterns::default() },
+            ..IgnoreConfig::default()
+        };
+        assert_eq!(ignored(&config, "Fixs JIRA-12 at https://host"), vec!["Fixs", "at", "https", "host"]);
+        assert_eq!(ignored(&config, "a BEGIN\nxyzzy\nEND b"), vec![] as Vec<String>);
+        assert!(IgnorePatterns::new(&["(".to_string()]).is_err());
+    }
+}
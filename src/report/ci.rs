@@ -0,0 +1,217 @@
+//! Annotation formats for CI platforms: GitHub Actions and GitLab.
+
+use super::{Reporter, Summary, char_column, end_char_column, relative_uri};
+use crate::engine::SpellError;
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Writes GitHub Actions `::error` workflow commands as results arrive.
+///
+/// GitHub turns these into inline annotations on the pull request diff.
+pub struct GithubReporter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> GithubReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+impl<W: Write> Reporter for GithubReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        let col = char_column(error);
+        let mut message = format!("Unknown word '{}'", error.word);
+        if !error.suggestions.is_empty() {
+            message.push_str(&format!(". Did you mean: {}?", error.suggestions.join(", ")));
+        }
+        writeln!(
+            self.out,
+            "::error file={},line={},col={},endColumn={},title={}::{}",
+            escape_property(&relative_uri(&error.file)),
+            error.line,
+            col,
+            end_char_column(error),
+            escape_property("Spelling"),
+            escape_data(&message)
+        )?;
+        Ok(())
+    }
+
+    fn processing_error(&mut self, message: &str) -> Result<()> {
+        writeln!(self.out, "::error::{}", escape_data(message))?;
+        Ok(())
+    }
+
+    fn finish(&mut self, _summary: &Summary) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Escapes the message part of a workflow command.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Writes a GitLab Code Quality report once the scan is complete.
+///
+/// Each issue carries a fingerprint derived from the file, the word and the
+/// trimmed line content, like baseline entries, plus an index for repeats on
+/// identical lines. GitLab uses it to match issues between the source and
+/// target branches, so it stays the same when lines are added above.
+pub struct GitlabReporter<W: Write> {
+    out: W,
+    issues: Vec<Value>,
+    /// How often each fingerprint input has been seen, for the index.
+    occurrences: HashMap<String, usize>,
+}
+
+impl<W: Write> GitlabReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out, issues: Vec::new(), occurrences: HashMap::new() }
+    }
+}
+
+impl<W: Write> Reporter for GitlabReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        let path = relative_uri(&error.file);
+        let col = char_column(error);
+        let mut description = format!("Unknown word '{}'", error.word);
+        if !error.suggestions.is_empty() {
+            description.push_str(&format!(". Did you mean: {}?", error.suggestions.join(", ")));
+        }
+        let input = format!("{}:{}:{}", path, error.word, crate::baseline::line_hash(error));
+        let occurrence = self.occurrences.entry(input.clone()).or_insert(0);
+        let fingerprint = fingerprint(&format!("{}:{}", input, occurrence));
+        *occurrence += 1;
+
+        self.issues.push(json!({
+            "type": "issue",
+            "check_name": "spelling/unknown-word",
+            "description": description,
+            "categories": ["Style"],
+            "severity": "major",
+            "fingerprint": fingerprint,
+            "location": {
+                "path": path,
+                "positions": {
+                    "begin": { "line": error.line, "column": col },
+                    "end": { "line": error.line, "column": end_char_column(error) },
+                },
+            },
+        }));
+        Ok(())
+    }
+
+    fn processing_error(&mut self, _message: &str) -> Result<()> {
+        // Code Quality reports only describe issues in files.
+        Ok(())
+    }
+
+    fn finish(&mut self, _summary: &Summary) -> Result<()> {
+        serde_json::to_writer_pretty(&mut self.out, &self.issues)?;
+        writeln!(self.out)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Returns a stable hash of `text` as hex, so fingerprints match between
+/// pipeline runs.
+fn fingerprint(text: &str) -> String {
+    crate::cache::hash(text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn error() -> SpellError {
+        SpellError {
+            file: PathBuf::from("./docs/a,b.md"),
+            line: 3,
+            col: 30,
+            span: 46..55,
+            word: "occurance".to_string(),
+            context: "This is a doc with an error: occurance.".to_string(),
+            suggestions: vec!["occurrence".to_string()],
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_github_annotation() -> Result<()> {
+        let mut out = Vec::new();
+        let mut reporter = GithubReporter::new(&mut out);
+        reporter.spelling_error(&error())?;
+        reporter.processing_error("Walk error: 100% broken\nreally")?;
+        reporter.finish(&Summary::default())?;
+
+        let text = String::from_utf8(out)?;
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "::error file=docs/a%2Cb.md,line=3,col=30,endColumn=39,title=Spelling::Unknown word 'occurance'. Did you mean: occurrence?"
+        );
+        assert_eq!(lines[1], "::error::Walk error: 100%25 broken%0Areally");
+        Ok(())
+    }
+
+    #[test]
+    fn test_gitlab_code_quality() -> Result<()> {
+        let mut out = Vec::new();
+        let mut reporter = GitlabReporter::new(&mut out);
+        reporter.spelling_error(&error())?;
+        reporter.finish(&Summary::default())?;
+
+        let report: Value = serde_json::from_slice(&out)?;
+        let issue = &report[0];
+        assert_eq!(issue["location"]["path"], "docs/a,b.md");
+        assert_eq!(issue["location"]["positions"]["begin"]["line"], 3);
+        assert_eq!(issue["severity"], "major");
+        assert_eq!(issue["fingerprint"].as_str().map(str::len), Some(16));
+        Ok(())
+    }
+
+    #[test]
+    fn test_gitlab_fingerprint_survives_line_shifts() -> Result<()> {
+        let fingerprints = |errors: &[SpellError]| -> Result<Vec<String>> {
+            let mut out = Vec::new();
+            let mut reporter = GitlabReporter::new(&mut out);
+            for error in errors {
+                reporter.spelling_error(error)?;
+            }
+            reporter.finish(&Summary::default())?;
+            let report: Value = serde_json::from_slice(&out)?;
+            Ok(report.as_array().unwrap().iter().map(|issue| issue["fingerprint"].as_str().unwrap().to_string()).collect())
+        };
+        let repeat = SpellError { line: 7, ..error() };
+        let before = fingerprints(&[error(), repeat.clone()])?;
+        // One line inserted above both errors.
+        let shifted = [SpellError { line: 4, ..error() }, SpellError { line: 8, ..repeat }];
+        assert_eq!(fingerprints(&shifted)?, before);
+        // Repeats on identical lines are told apart.
+        assert_ne!(before[0], before[1]);
+
+        let edited = SpellError { context: "Another line with occurance.".to_string(), ..error() };
+        assert_ne!(fingerprints(&[edited])?[0], before[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        assert_eq!(fingerprint(""), "cbf29ce484222325");
+        assert_eq!(fingerprint("a"), "af63dc4c8601ec8c");
+    }
+}
@@ -0,0 +1,76 @@
+//! Colored terminal output.
+
+use super::{Reporter, Summary};
+use crate::engine::SpellError;
+use anyhow::Result;
+use colored::*;
+use std::io::Write;
+
+/// Prints each error with its context line, a caret and suggestions.
+///
+/// Processing errors go to stderr so they are not mixed with the report.
+pub struct HumanReporter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> HumanReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+impl<W: Write> Reporter for HumanReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        // Notebook errors are located by cell, as in the notebook editor.
+        let (location, context, col) = match &error.cell {
+            Some(cell) => (
+                format!("cell {}:{}:{}", cell.index, cell.line.to_string().yellow(), cell.col.to_string().yellow()),
+                &cell.context,
+                cell.col,
+            ),
+            None => (format!("{}:{}", error.line.to_string().yellow(), error.col.to_string().yellow()), &error.context, error.col),
+        };
+        writeln!(
+            self.out,
+            "{} in {}:{}: {}",
+            "Error".red().bold(),
+            error.file.display().to_string().cyan(),
+            location,
+            error.word.bold()
+        )?;
+        writeln!(self.out, "  | {}", context.trim())?;
+        if col > 0 {
+            writeln!(self.out, "  | {:width$}^", "", width = col - 1)?;
+        }
+        if !error.suggestions.is_empty() {
+            writeln!(self.out, "  = {} {}", "did you mean:".green(), error.suggestions.join(", "))?;
+        }
+        Ok(())
+    }
+
+    fn processing_error(&mut self, message: &str) -> Result<()> {
+        eprintln!("{} {}", "error".red().bold(), message);
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<()> {
+        if summary.fixed > 0 {
+            writeln!(self.out, "{} Fixed {} spelling errors.", "info".blue(), summary.fixed)?;
+        }
+        if summary.spelling_errors == 0 {
+            if summary.processing_errors == 0 {
+                writeln!(self.out, "{}", "Perfect spelling! No errors found.".green().bold())?;
+            } else {
+                writeln!(self.out, "{} Completed with {} processing errors.", "info".blue(), summary.processing_errors)?;
+            }
+        } else {
+            writeln!(self.out, "\nFound {} spelling errors.", summary.spelling_errors)?;
+            if summary.processing_errors > 0 {
+                writeln!(self.out, "(And {} processing errors)", summary.processing_errors)?;
+            }
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
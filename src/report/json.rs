@@ -0,0 +1,153 @@
+//! JSON and newline-delimited JSON output.
+
+use super::{Reporter, Summary};
+use crate::engine::SpellError;
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+/// A single NDJSON record, tagged by its `type`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    SpellingError(&'a SpellError),
+    ProcessingError { message: &'a str },
+    Summary(&'a Summary),
+}
+
+/// Writes one JSON object per line as results arrive.
+///
+/// Each record has a `type` of `spelling_error`, `processing_error` or, last,
+/// `summary`.
+pub struct NdjsonReporter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> NdjsonReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    fn write(&mut self, record: &Record) -> Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        writeln!(self.out)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Reporter for NdjsonReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        self.write(&Record::SpellingError(error))
+    }
+
+    fn processing_error(&mut self, message: &str) -> Result<()> {
+        self.write(&Record::ProcessingError { message })
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<()> {
+        self.write(&Record::Summary(summary))?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes a single JSON document once the scan is complete.
+pub struct JsonReporter<W: Write> {
+    out: W,
+    errors: Vec<SpellError>,
+    processing_errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    errors: &'a [SpellError],
+    processing_errors: &'a [String],
+    summary: &'a Summary,
+}
+
+impl<W: Write> JsonReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out, errors: Vec::new(), processing_errors: Vec::new() }
+    }
+}
+
+impl<W: Write> Reporter for JsonReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        self.errors.push(error.clone());
+        Ok(())
+    }
+
+    fn processing_error(&mut self, message: &str) -> Result<()> {
+        self.processing_errors.push(message.to_string());
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<()> {
+        let document = Document {
+            errors: &self.errors,
+            processing_errors: &self.processing_errors,
+            summary,
+        };
+        serde_json::to_writer_pretty(&mut self.out, &document)?;
+        writeln!(self.out)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn error() -> SpellError {
+        SpellError {
+            file: PathBuf::from("docs/a.md"),
+            line: 2,
+            col: 5,
+            span: 12..21,
+            word: "occurance".to_string(),
+            context: "An occurance.".to_string(),
+            suggestions: vec!["occurrence".to_string()],
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_ndjson_records() -> Result<()> {
+        let mut out = Vec::new();
+        let mut reporter = NdjsonReporter::new(&mut out);
+        reporter.spelling_error(&error())?;
+        reporter.processing_error("Walk error: denied")?;
+        reporter.finish(&Summary { spelling_errors: 1, processing_errors: 1, fixed: 0 })?;
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "spelling_error");
+        assert_eq!(lines[0]["span"]["start"], 12);
+        assert_eq!(lines[0]["suggestions"][0], "occurrence");
+        assert_eq!(lines[1]["message"], "Walk error: denied");
+        assert_eq!(lines[2]["type"], "summary");
+        assert_eq!(lines[2]["spelling_errors"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_document() -> Result<()> {
+        let mut out = Vec::new();
+        let mut reporter = JsonReporter::new(&mut out);
+        reporter.spelling_error(&error())?;
+        reporter.finish(&Summary { spelling_errors: 1, processing_errors: 0, fixed: 0 })?;
+
+        let doc: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(doc["errors"][0]["file"], "docs/a.md");
+        assert_eq!(doc["errors"][0]["line"], 2);
+        assert_eq!(doc["summary"]["spelling_errors"], 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Output formats for scan results.
+//!
+//! A `Reporter` receives each `SpellError` and processing error as the engine
+//! yields them, and a `Summary` once the scan is complete. Streaming formats
+//! write records immediately; document formats buffer until `finish`.
+
+mod ci;
+mod human;
+mod json;
+mod sarif;
+mod xml;
+
+pub use ci::{GithubReporter, GitlabReporter};
+pub use human::HumanReporter;
+pub use json::{JsonReporter, NdjsonReporter};
+pub use sarif::SarifReporter;
+pub use xml::{CheckstyleReporter, JunitReporter};
+
+use crate::engine::SpellError;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+/// The output formats supported by `check --format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Colored, human-readable text.
+    Human,
+    /// A single JSON document with all results.
+    Json,
+    /// One JSON record per line, streamed as results arrive.
+    Ndjson,
+    /// A SARIF 2.1.0 log for code-scanning dashboards.
+    Sarif,
+    /// A JUnit XML report with one test case per file.
+    Junit,
+    /// A Checkstyle XML report.
+    Checkstyle,
+    /// GitHub Actions `::error` workflow commands.
+    Github,
+    /// A GitLab Code Quality JSON report.
+    Gitlab,
+}
+
+/// Totals for a completed scan.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Summary {
+    /// The number of spelling errors reported (excluding fixed ones).
+    pub spelling_errors: usize,
+    /// The number of files or paths that could not be processed.
+    pub processing_errors: usize,
+    /// The number of errors fixed by `--fix`.
+    pub fixed: usize,
+}
+
+/// Receives scan results and renders them in a particular format.
+pub trait Reporter {
+    /// Reports a spelling error.
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()>;
+    /// Reports a file or path that could not be processed.
+    fn processing_error(&mut self, message: &str) -> Result<()>;
+    /// Completes the report.
+    fn finish(&mut self, summary: &Summary) -> Result<()>;
+}
+
+/// Creates a reporter for `format` writing to `out`.
+pub fn reporter(format: Format, out: Box<dyn Write>) -> Box<dyn Reporter> {
+    match format {
+        Format::Human => Box::new(HumanReporter::new(out)),
+        Format::Json => Box::new(JsonReporter::new(out)),
+        Format::Ndjson => Box::new(NdjsonReporter::new(out)),
+        Format::Sarif => Box::new(SarifReporter::new(out)),
+        Format::Junit => Box::new(JunitReporter::new(out)),
+        Format::Checkstyle => Box::new(CheckstyleReporter::new(out)),
+        Format::Github => Box::new(GithubReporter::new(out)),
+        Format::Gitlab => Box::new(GitlabReporter::new(out)),
+    }
+}
+
+/// Forwards every result to several reporters, e.g. the terminal and a file.
+pub struct MultiReporter {
+    reporters: Vec<Box<dyn Reporter>>,
+}
+
+impl MultiReporter {
+    /// Creates a reporter forwarding to all of `reporters`.
+    pub fn new(reporters: Vec<Box<dyn Reporter>>) -> Self {
+        Self { reporters }
+    }
+}
+
+impl Reporter for MultiReporter {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        self.reporters.iter_mut().try_for_each(|r| r.spelling_error(error))
+    }
+
+    fn processing_error(&mut self, message: &str) -> Result<()> {
+        self.reporters.iter_mut().try_for_each(|r| r.processing_error(message))
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<()> {
+        self.reporters.iter_mut().try_for_each(|r| r.finish(summary))
+    }
+}
+
+/// Renders a path as a relative URI with forward slashes (`./docs\\a.md` → `docs/a.md`).
+fn relative_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+/// Returns the 1-based column of an error counted in characters, not bytes.
+fn char_column(error: &SpellError) -> usize {
+    let prefix = error.context.get(..error.col.saturating_sub(1)).unwrap_or_default();
+    prefix.chars().count() + 1
+}
+
+/// Returns the 1-based column just past an error's source text, counted in
+/// characters.
+fn end_char_column(error: &SpellError) -> usize {
+    char_column(error) + error.source().chars().count()
+}
@@ -0,0 +1,178 @@
+//! SARIF 2.1.0 output for code-scanning dashboards.
+
+use super::{Reporter, Summary, char_column, end_char_column, relative_uri};
+use crate::engine::SpellError;
+use crate::fix;
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::io::Write;
+
+/// The rule under which all misspellings are reported.
+const RULE_ID: &str = "spelling/unknown-word";
+
+/// Writes a SARIF log with one run once the scan is complete.
+///
+/// Columns are reported in Unicode code points (`columnKind` is set
+/// accordingly), and every suggestion becomes a `fix` replacing the word.
+pub struct SarifReporter<W: Write> {
+    out: W,
+    results: Vec<Value>,
+    notifications: Vec<Value>,
+}
+
+impl<W: Write> SarifReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out, results: Vec::new(), notifications: Vec::new() }
+    }
+}
+
+impl<W: Write> Reporter for SarifReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        let uri = relative_uri(&error.file);
+        let region = region(error);
+
+        let mut message = format!("Unknown word '{}'.", error.word);
+        if !error.suggestions.is_empty() {
+            message.push_str(&format!(" Did you mean: {}?", error.suggestions.join(", ")));
+        }
+
+        let fixes: Vec<Value> = error
+            .suggestions
+            .iter()
+            .map(|s| {
+                let replacement = fix::match_case(&error.word, s);
+                json!({
+                    "description": { "text": format!("Replace with '{}'", replacement) },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": uri },
+                        "replacements": [{
+                            "deletedRegion": region,
+                            "insertedContent": { "text": replacement },
+                        }],
+                    }],
+                })
+            })
+            .collect();
+
+        self.results.push(json!({
+            "ruleId": RULE_ID,
+            "ruleIndex": 0,
+            "level": "error",
+            "message": { "text": message },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": uri },
+                    "region": region,
+                },
+            }],
+            "fixes": fixes,
+        }));
+        Ok(())
+    }
+
+    fn processing_error(&mut self, message: &str) -> Result<()> {
+        self.notifications.push(json!({
+            "level": "error",
+            "message": { "text": message },
+        }));
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<()> {
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": [{
+                            "id": RULE_ID,
+                            "name": "UnknownWord",
+                            "shortDescription": { "text": "Unknown word" },
+                            "fullDescription": { "text": "The word is not in the dictionary and may be misspelled." },
+                            "defaultConfiguration": { "level": "error" },
+                        }],
+                    },
+                },
+                "columnKind": "unicodeCodePoints",
+                "invocations": [{
+                    "executionSuccessful": summary.processing_errors == 0,
+                    "toolExecutionNotifications": self.notifications,
+                }],
+                "results": self.results,
+            }],
+        });
+        serde_json::to_writer_pretty(&mut self.out, &log)?;
+        writeln!(self.out)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Builds the SARIF region covering the misspelled word.
+fn region(error: &SpellError) -> Value {
+    json!({
+        "startLine": error.line,
+        "startColumn": char_column(error),
+        "endColumn": end_char_column(error),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_sarif_result() -> Result<()> {
+        let error = SpellError {
+            file: PathBuf::from("./docs/a.md"),
+            line: 2,
+            col: 7,
+            span: 12..21,
+            word: "Occurance".to_string(),
+            context: "Ünë Occurance.".to_string(),
+            suggestions: vec!["occurrence".to_string()],
+            cell: None,
+        };
+
+        let mut out = Vec::new();
+        let mut reporter = SarifReporter::new(&mut out);
+        reporter.spelling_error(&error)?;
+        reporter.finish(&Summary { spelling_errors: 1, processing_errors: 0, fixed: 0 })?;
+
+        let log: Value = serde_json::from_slice(&out)?;
+        assert_eq!(log["version"], "2.1.0");
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], RULE_ID);
+        assert_eq!(result["message"]["text"], "Unknown word 'Occurance'. Did you mean: occurrence?");
+
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "docs/a.md");
+        // "Ünë " is 6 bytes but 4 code points.
+        assert_eq!(location["region"], json!({ "startLine": 2, "startColumn": 5, "endColumn": 14 }));
+
+        let replacement = &result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["insertedContent"]["text"], "Occurrence");
+        Ok(())
+    }
+
+    #[test]
+    fn test_region_covers_decoded_word() {
+        let error = SpellError {
+            file: PathBuf::from("index.html"),
+            line: 1,
+            col: 4,
+            span: 3..15,
+            word: "cafée".to_string(),
+            context: "<p>caf&eacute;e</p>".to_string(),
+            suggestions: Vec::new(),
+            cell: None,
+        };
+        assert_eq!(error.source(), "caf&eacute;e");
+        assert_eq!(region(&error), json!({ "startLine": 1, "startColumn": 4, "endColumn": 16 }));
+    }
+}
@@ -0,0 +1,248 @@
+//! JUnit XML and Checkstyle XML output.
+
+use super::{Reporter, Summary, char_column, relative_uri};
+use crate::engine::SpellError;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Collects errors grouped by file, ordered by path and then position.
+#[derive(Default)]
+struct ByFile {
+    files: BTreeMap<String, Vec<SpellError>>,
+    processing_errors: Vec<String>,
+}
+
+impl ByFile {
+    fn add(&mut self, error: &SpellError) {
+        self.files.entry(relative_uri(&error.file)).or_default().push(error.clone());
+    }
+
+    fn sorted(&mut self) -> &BTreeMap<String, Vec<SpellError>> {
+        for errors in self.files.values_mut() {
+            errors.sort_by_key(|e| (e.line, e.col));
+        }
+        &self.files
+    }
+}
+
+/// Writes a JUnit XML report once the scan is complete.
+///
+/// Every file with misspellings is a failed test case listing its errors, and
+/// every processing error is an errored test case. A clean scan is reported as
+/// a single passing test case.
+pub struct JunitReporter<W: Write> {
+    out: W,
+    results: ByFile,
+}
+
+impl<W: Write> JunitReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out, results: ByFile::default() }
+    }
+}
+
+impl<W: Write> Reporter for JunitReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        self.results.add(error);
+        Ok(())
+    }
+
+    fn processing_error(&mut self, message: &str) -> Result<()> {
+        self.results.processing_errors.push(message.to_string());
+        Ok(())
+    }
+
+    fn finish(&mut self, _summary: &Summary) -> Result<()> {
+        let processing_errors = std::mem::take(&mut self.results.processing_errors);
+        let files = self.results.sorted();
+
+        let failures = files.len();
+        let errors = processing_errors.len();
+        let tests = (failures + errors).max(1);
+        let name = env!("CARGO_PKG_NAME");
+
+        let mut xml = String::new();
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(xml, r#"<testsuites name="{name}" tests="{tests}" failures="{failures}" errors="{errors}">"#)?;
+        writeln!(xml, r#"  <testsuite name="{name}" tests="{tests}" failures="{failures}" errors="{errors}">"#)?;
+
+        for (file, file_errors) in files {
+            let mut details = String::new();
+            for e in file_errors {
+                write!(details, "{}:{}:{}: Unknown word '{}'", file, e.line, char_column(e), e.word)?;
+                if !e.suggestions.is_empty() {
+                    write!(details, " (did you mean: {})", e.suggestions.join(", "))?;
+                }
+                details.push('\n');
+            }
+            writeln!(xml, r#"    <testcase classname="{name}" name="{}">"#, escape(file))?;
+            writeln!(
+                xml,
+                r#"      <failure type="spelling" message="{} spelling errors">{}</failure>"#,
+                file_errors.len(),
+                escape(&details)
+            )?;
+            writeln!(xml, "    </testcase>")?;
+        }
+
+        for message in &processing_errors {
+            writeln!(xml, r#"    <testcase classname="{name}" name="processing error">"#)?;
+            writeln!(xml, r#"      <error type="processing" message="{}"/>"#, escape(message))?;
+            writeln!(xml, "    </testcase>")?;
+        }
+
+        if failures + errors == 0 {
+            writeln!(xml, r#"    <testcase classname="{name}" name="spelling"/>"#)?;
+        }
+
+        writeln!(xml, "  </testsuite>")?;
+        writeln!(xml, "</testsuites>")?;
+
+        self.out.write_all(xml.as_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes a Checkstyle XML report once the scan is complete.
+///
+/// Checkstyle has no place for errors that are not tied to a file, so
+/// processing errors are not included.
+pub struct CheckstyleReporter<W: Write> {
+    out: W,
+    results: ByFile,
+}
+
+impl<W: Write> CheckstyleReporter<W> {
+    /// Creates a reporter writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self { out, results: ByFile::default() }
+    }
+}
+
+impl<W: Write> Reporter for CheckstyleReporter<W> {
+    fn spelling_error(&mut self, error: &SpellError) -> Result<()> {
+        self.results.add(error);
+        Ok(())
+    }
+
+    fn processing_error(&mut self, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self, _summary: &Summary) -> Result<()> {
+        let mut xml = String::new();
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(xml, r#"<checkstyle version="4.3">"#)?;
+
+        for (file, file_errors) in self.results.sorted() {
+            writeln!(xml, r#"  <file name="{}">"#, escape(file))?;
+            for e in file_errors {
+                let mut message = format!("Unknown word '{}'", e.word);
+                if !e.suggestions.is_empty() {
+                    write!(message, " (did you mean: {})", e.suggestions.join(", "))?;
+                }
+                writeln!(
+                    xml,
+                    r#"    <error line="{}" column="{}" severity="error" message="{}" source="{}.UnknownWord"/>"#,
+                    e.line,
+                    char_column(e),
+                    escape(&message),
+                    env!("CARGO_PKG_NAME")
+                )?;
+            }
+            writeln!(xml, "  </file>")?;
+        }
+
+        writeln!(xml, "</checkstyle>")?;
+
+        self.out.write_all(xml.as_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Escapes text for use in XML content and attribute values.
+///
+/// Control characters that XML 1.0 cannot represent are dropped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\t' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn error(file: &str, line: usize, word: &str) -> SpellError {
+        SpellError {
+            file: PathBuf::from(file),
+            line,
+            col: 3,
+            span: 0..word.len(),
+            word: word.to_string(),
+            context: format!("A {word}"),
+            suggestions: vec!["occurrence".to_string()],
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_junit_groups_by_file() -> Result<()> {
+        let mut out = Vec::new();
+        let mut reporter = JunitReporter::new(&mut out);
+        reporter.spelling_error(&error("./b.md", 4, "teh"))?;
+        reporter.spelling_error(&error("./a.md", 1, "occurance"))?;
+        reporter.spelling_error(&error("./b.md", 2, "<wrod>"))?;
+        reporter.processing_error("Walk error: denied")?;
+        reporter.finish(&Summary::default())?;
+
+        let xml = String::from_utf8(out)?;
+        assert!(xml.contains(r#"<testsuites name="spell_check" tests="3" failures="2" errors="1">"#));
+        assert!(xml.find(r#"name="a.md""#) < xml.find(r#"name="b.md""#));
+        assert!(xml.contains(r#"<failure type="spelling" message="2 spelling errors">b.md:2:3: Unknown word &apos;&lt;wrod&gt;&apos;"#));
+        assert!(xml.contains(r#"<error type="processing" message="Walk error: denied"/>"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_junit_clean_scan_passes() -> Result<()> {
+        let mut out = Vec::new();
+        JunitReporter::new(&mut out).finish(&Summary::default())?;
+        let xml = String::from_utf8(out)?;
+        assert!(xml.contains(r#"tests="1" failures="0" errors="0""#));
+        assert!(xml.contains(r#"<testcase classname="spell_check" name="spelling"/>"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_checkstyle() -> Result<()> {
+        let mut out = Vec::new();
+        let mut reporter = CheckstyleReporter::new(&mut out);
+        reporter.spelling_error(&error("./docs/a.md", 7, "occurance"))?;
+        reporter.finish(&Summary::default())?;
+
+        let xml = String::from_utf8(out)?;
+        assert!(xml.contains(r#"<file name="docs/a.md">"#));
+        assert!(xml.contains(
+            r#"<error line="7" column="3" severity="error" message="Unknown word &apos;occurance&apos; (did you mean: occurrence)" source="spell_check.UnknownWord"/>"#
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+//! Interactive triage of spelling errors.
+//!
+//! `spell_check review` walks every `SpellError` of a scan and asks what to do
+//! with it. Replacements are collected per file and written once the file has
+//! been reviewed, so the byte spans reported by the engine stay valid.
+
+use crate::config::add_extra_word;
+use crate::engine::SpellError;
+use crate::fix::{self, Edit};
+use anyhow::{Context, Result};
+use colored::*;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// A decision taken for a single error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Replace the word with the given text.
+    Replace(String),
+    /// Ask for a replacement to type in.
+    Type,
+    /// Add the word to the project dictionary.
+    AddToDictionary,
+    /// Ignore the word in the rest of this file.
+    IgnoreInFile,
+    /// Leave this occurrence alone.
+    Skip,
+    /// Stop reviewing.
+    Quit,
+}
+
+/// Counts of the actions taken during a review.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// Occurrences replaced in files.
+    pub replaced: usize,
+    /// Words added to the project dictionary.
+    pub added: usize,
+    /// Occurrences ignored, including later occurrences of ignored words.
+    pub ignored: usize,
+    /// Occurrences skipped or left unreviewed.
+    pub skipped: usize,
+}
+
+/// Parses a menu choice for `error`. Returns `None` for invalid input.
+///
+/// Numbers pick a suggestion (cased like the original word), an empty line skips.
+pub fn parse_choice(input: &str, error: &SpellError) -> Option<Action> {
+    let input = input.trim();
+    if let Ok(n) = input.parse::<usize>() {
+        let suggestion = error.suggestions.get(n.checked_sub(1)?)?;
+        return Some(Action::Replace(fix::match_case(&error.word, suggestion)));
+    }
+    match input.to_ascii_lowercase().as_str() {
+        "r" => Some(Action::Type),
+        "a" => Some(Action::AddToDictionary),
+        "i" => Some(Action::IgnoreInFile),
+        "s" | "" => Some(Action::Skip),
+        "q" => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+/// Reviews `errors` interactively, reading choices from `input`.
+///
+/// Words added to the dictionary are written to `dictionary.extra_words` in the
+/// config file at `config_path` and are not asked about again. End of input is
+/// treated like quitting.
+pub fn review<R: BufRead, W: Write>(
+    mut errors: Vec<SpellError>,
+    config_path: &Path,
+    mut input: R,
+    mut out: W,
+) -> Result<Summary> {
+    errors.sort_by(|a, b| a.file.cmp(&b.file).then(a.span.start.cmp(&b.span.start)));
+
+    let mut summary = Summary::default();
+    let mut added: HashSet<String> = HashSet::new();
+    let mut remaining = errors.as_slice();
+    let mut quit = false;
+
+    while let Some(first) = remaining.first() {
+        let len = remaining.iter().take_while(|e| e.file == first.file).count();
+        let (file_errors, rest) = remaining.split_at(len);
+        remaining = rest;
+
+        if quit {
+            summary.skipped += file_errors.len();
+            continue;
+        }
+
+        let content = fs::read_to_string(&first.file)
+            .with_context(|| format!("Failed to read file {}", first.file.display()))?;
+        let mut edits = Vec::new();
+        let mut ignored: HashSet<String> = HashSet::new();
+
+        for (i, error) in file_errors.iter().enumerate() {
+            let key = error.word.to_lowercase();
+            if added.contains(&key) || ignored.contains(&key) {
+                summary.ignored += 1;
+                continue;
+            }
+            // Skip errors whose text changed since the scan.
+            if content.get(error.span.clone()) != Some(error.word.as_str()) {
+                summary.skipped += 1;
+                continue;
+            }
+
+            match prompt(error, &mut input, &mut out)? {
+                Action::Replace(replacement) => {
+                    edits.push(Edit { span: error.span.clone(), replacement });
+                    summary.replaced += 1;
+                }
+                Action::AddToDictionary => {
+                    add_extra_word(config_path, &error.word)?;
+                    added.insert(key);
+                    summary.added += 1;
+                }
+                Action::IgnoreInFile => {
+                    ignored.insert(key);
+                    summary.ignored += 1;
+                }
+                Action::Skip | Action::Type => summary.skipped += 1,
+                Action::Quit => {
+                    summary.skipped += file_errors.len() - i;
+                    quit = true;
+                    break;
+                }
+            }
+        }
+
+        if !edits.is_empty() {
+            fs::write(&first.file, fix::apply(&content, &edits).0)
+                .with_context(|| format!("Failed to write file {}", first.file.display()))?;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Shows one error and reads choices until a valid action is given.
+fn prompt<R: BufRead, W: Write>(error: &SpellError, input: &mut R, out: &mut W) -> Result<Action> {
+    writeln!(
+        out,
+        "\n{} in {}:{}:{}: {}",
+        "Error".red().bold(),
+        error.file.display().to_string().cyan(),
+        error.line.to_string().yellow(),
+        error.col.to_string().yellow(),
+        error.word.bold()
+    )?;
+    writeln!(out, "  | {}", error.context.trim_end())?;
+    writeln!(out, "  | {:width$}^", "", width = error.col.saturating_sub(1))?;
+    for (i, suggestion) in error.suggestions.iter().enumerate() {
+        writeln!(out, "  [{}] {}", i + 1, fix::match_case(&error.word, suggestion))?;
+    }
+    writeln!(out, "  [r] type a replacement  [a] add to dictionary  [i] ignore in file  [s] skip  [q] quit")?;
+
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+        let Some(line) = read_line(input)? else {
+            return Ok(Action::Quit);
+        };
+        match parse_choice(&line, error) {
+            Some(Action::Type) => {
+                write!(out, "Replacement: ")?;
+                out.flush()?;
+                let Some(replacement) = read_line(input)? else {
+                    return Ok(Action::Quit);
+                };
+                let replacement = replacement.trim();
+                if !replacement.is_empty() {
+                    return Ok(Action::Replace(replacement.to_string()));
+                }
+            }
+            Some(action) => return Ok(action),
+            None => writeln!(out, "Invalid choice: {}", line.trim())?,
+        }
+    }
+}
+
+fn read_line<R: BufRead>(input: &mut R) -> Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn error(file: &Path, content: &str, word: &str, suggestions: &[&str]) -> SpellError {
+        let start = content.find(word).unwrap();
+        SpellError {
+            file: file.to_path_buf(),
+            line: 1,
+            col: start + 1,
+            span: start..start + word.len(),
+            word: word.to_string(),
+            context: content.to_string(),
+            suggestions: suggestions.iter().map(|s| s.to_string()).collect(),
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        let e = error(Path::new("a.md"), "Teh", "Teh", &["the", "tea"]);
+        assert_eq!(parse_choice("2\n", &e), Some(Action::Replace("Tea".to_string())));
+        assert_eq!(parse_choice("3", &e), None);
+        assert_eq!(parse_choice("0", &e), None);
+        assert_eq!(parse_choice("A", &e), Some(Action::AddToDictionary));
+        assert_eq!(parse_choice("", &e), Some(Action::Skip));
+        assert_eq!(parse_choice("x", &e), None);
+    }
+
+    #[test]
+    fn test_review_applies_actions() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("notes.md");
+        let content = "Teh recieve foo wrod foo wrod\n";
+        fs::write(&file, content)?;
+        let config_path = dir.path().join("spellcheck.toml");
+
+        let errors = vec![
+            error(&file, content, "Teh", &["the"]),
+            error(&file, content, "recieve", &["receive"]),
+            error(&file, content, "foo", &[]),
+            error(&file, content, "wrod", &["word"]),
+            SpellError { span: 21..24, ..error(&file, content, "foo", &[]) },
+            SpellError { span: 25..29, ..error(&file, content, "wrod", &["word"]) },
+        ];
+        // Pick a suggestion, type a replacement (after an invalid choice),
+        // add `foo`, ignore `wrod` in the file.
+        let input = Cursor::new("1\nx\nr\nreceive\na\ni\n");
+        let summary = review(errors, &config_path, input, Vec::new())?;
+
+        assert_eq!(summary, Summary { replaced: 2, added: 1, ignored: 3, skipped: 0 });
+        assert_eq!(fs::read_to_string(&file)?, "The receive foo wrod foo wrod\n");
+        let config = crate::config::load_config(&config_path)?;
+        assert_eq!(config.dictionary.extra_words, vec!["foo"]);
+        Ok(())
+    }
+}
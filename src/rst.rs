@@ -0,0 +1,439 @@
+//! reStructuredText prose extraction.
+//!
+//! Finds the prose of a reST document: paragraphs, section titles, list and
+//! table text, footnotes and the bodies of directives such as `note`. Literal
+//! blocks, doctest blocks, code and math directives, hyperlink targets,
+//! substitution definitions, directive arguments and options, inline literals,
+//! roles, interpreted text, link URLs and footnote or citation labels are left
+//! out. Comments are returned as comments.
+
+use crate::lexer::{Region, RegionKind, indentation, lines};
+use std::ops::Range;
+
+/// Directives whose content is not prose and is skipped with the directive.
+const SKIPPED_DIRECTIVES: &[&str] = &[
+    "code",
+    "code-block",
+    "sourcecode",
+    "literalinclude",
+    "highlight",
+    "math",
+    "raw",
+    "include",
+    "toctree",
+    "csv-table",
+    "graphviz",
+    "digraph",
+    "graph",
+    "doctest",
+    "testcode",
+    "testoutput",
+    "testsetup",
+    "testcleanup",
+    "ipython",
+    "jupyter-execute",
+    "productionlist",
+    "autosummary",
+    "mermaid",
+    "uml",
+    "parsed-literal",
+    "role",
+    "default-role",
+    "meta",
+    "tabularcolumns",
+];
+
+/// Directives whose arguments are a title rather than a name or path.
+const TITLED_DIRECTIVES: &[&str] = &[
+    "admonition",
+    "attention",
+    "caution",
+    "danger",
+    "error",
+    "hint",
+    "important",
+    "note",
+    "tip",
+    "warning",
+    "seealso",
+    "todo",
+    "topic",
+    "sidebar",
+    "rubric",
+    "title",
+];
+
+/// Extracts the prose and comments of a reStructuredText document.
+pub fn extract_regions(content: &str) -> Vec<Region> {
+    let mut document = Document {
+        content,
+        lines: lines(content),
+        regions: Vec::new(),
+        paragraph: None,
+        literal_after: None,
+    };
+    document.run();
+    document.regions.sort_by_key(|r| r.range.start);
+    document.regions
+}
+
+struct Document<'a> {
+    content: &'a str,
+    lines: Vec<Range<usize>>,
+    regions: Vec<Region>,
+    /// The paragraph being collected and the indentation of its first line.
+    paragraph: Option<(Range<usize>, usize)>,
+    /// The indentation of a paragraph ending in `::`, whose following
+    /// indented block is a literal block.
+    literal_after: Option<usize>,
+}
+
+impl<'a> Document<'a> {
+    fn line(&self, i: usize) -> &'a str {
+        &self.content[self.lines[i].clone()]
+    }
+
+    fn run(&mut self) {
+        let mut i = 0;
+        while i < self.lines.len() {
+            let line = self.line(i);
+            let indent = indentation(line);
+            let trimmed = line.trim_start();
+
+            if trimmed.is_empty() {
+                self.flush();
+                i += 1;
+                continue;
+            }
+            if self.paragraph.is_none() {
+                if let Some(base) = self.literal_after.take()
+                    && indent > base
+                {
+                    i = self.block_end(i, base);
+                    continue;
+                }
+                if trimmed.starts_with(">>>") {
+                    i = (i..self.lines.len())
+                        .find(|&j| self.line(j).trim().is_empty())
+                        .unwrap_or(self.lines.len());
+                    continue;
+                }
+                if trimmed == ".." || trimmed.starts_with(".. ") {
+                    i = self.explicit_markup(i, indent);
+                    continue;
+                }
+            }
+            let range = self.lines[i].clone();
+            self.paragraph = Some(match self.paragraph.take() {
+                Some((paragraph, first)) => (paragraph.start..range.end, first),
+                None => (range.start + line.len() - trimmed.len()..range.end, indent),
+            });
+            i += 1;
+        }
+        self.flush();
+    }
+
+    /// Checks the collected paragraph.
+    fn flush(&mut self) {
+        let Some((range, indent)) = self.paragraph.take() else {
+            return;
+        };
+        if self.content[range.clone()].trim_end().ends_with("::") {
+            self.literal_after = Some(indent);
+        }
+        Inline {
+            src: self.content.as_bytes(),
+            pos: range.start,
+            end: range.end,
+            text_start: range.start,
+            regions: &mut self.regions,
+        }
+        .run();
+    }
+
+    /// Returns the first line after line `i` that is not blank and not
+    /// indented more than `indent`.
+    fn block_end(&self, i: usize, indent: usize) -> usize {
+        (i + 1..self.lines.len())
+            .find(|&j| {
+                let line = self.line(j);
+                !line.trim().is_empty() && indentation(line) <= indent
+            })
+            .unwrap_or(self.lines.len())
+    }
+
+    /// Handles a line starting with `..`: a directive, target, substitution
+    /// definition, footnote, citation or comment. Returns the next line to read.
+    fn explicit_markup(&mut self, i: usize, indent: usize) -> usize {
+        let line_start = self.lines[i].start;
+        let line = self.line(i);
+        let marker = line.len() - line.trim_start().len();
+        let rest_start =
+            marker + 2 + line[marker + 2..].len() - line[marker + 2..].trim_start().len();
+        let rest = &line[rest_start..];
+        let end = self.block_end(i, indent);
+
+        if rest.starts_with('_') || rest.starts_with('|') {
+            return end;
+        }
+        if rest.starts_with('[')
+            && let Some(close) = rest.find(']')
+        {
+            // A footnote or citation: its text is prose.
+            let text = rest_start + close + 1;
+            self.paragraph = Some((line_start + text..self.lines[i].end, indent));
+            return i + 1;
+        }
+        if let Some(name) = directive_name(rest) {
+            if SKIPPED_DIRECTIVES.contains(&name.as_str()) {
+                return end;
+            }
+            if TITLED_DIRECTIVES.contains(&name.as_str()) {
+                let args = rest_start + rest.find("::").map_or(rest.len(), |p| p + 2);
+                self.paragraph = Some((line_start + args..self.lines[i].end, indent));
+                self.flush();
+            }
+            // Options come first in the body; the rest is prose.
+            let mut next = i + 1;
+            while next < end && self.line(next).trim_start().starts_with(':') {
+                next += 1;
+            }
+            return next;
+        }
+
+        // Anything else is a comment, up to the end of its indented block.
+        let last = (i..end)
+            .rev()
+            .find(|&j| !self.line(j).trim().is_empty())
+            .unwrap_or(i);
+        self.regions.push(Region {
+            kind: RegionKind::Comment,
+            range: line_start + marker..self.lines[last].end,
+        });
+        end
+    }
+}
+
+/// Returns the lowercased name of the directive in `.. name:: args`.
+fn directive_name(rest: &str) -> Option<String> {
+    let close = rest.find("::")?;
+    let name = &rest[..close];
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '+' | '.'));
+    let followed = rest[close + 2..]
+        .chars()
+        .next()
+        .is_none_or(char::is_whitespace);
+    (valid && followed).then(|| name.to_ascii_lowercase())
+}
+
+/// The inline pass over one paragraph.
+struct Inline<'a> {
+    src: &'a [u8],
+    pos: usize,
+    end: usize,
+    /// Where the current run of prose started.
+    text_start: usize,
+    regions: &'a mut Vec<Region>,
+}
+
+impl Inline<'_> {
+    fn run(mut self) {
+        while self.pos < self.end {
+            match self.src[self.pos] {
+                b'\\' => self.pos = (self.pos + 2).min(self.end),
+                b'`' => self.interpreted(self.pos, self.pos),
+                b':' if self.starts_markup() => self.role_or_field(),
+                b'|' if self.starts_markup() => self.substitution(),
+                b'[' => self.label_reference(),
+                _ => self.pos += 1,
+            }
+        }
+        let range = self.text_start..self.end;
+        self.push_text(range);
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        let i = self.pos + offset;
+        (i < self.end).then(|| self.src[i])
+    }
+
+    fn find(&self, from: usize, pat: &[u8]) -> Option<usize> {
+        self.src[from.min(self.end)..self.end]
+            .windows(pat.len())
+            .position(|w| w == pat)
+            .map(|p| from + p)
+    }
+
+    fn push_text(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(Region {
+                kind: RegionKind::Text,
+                range,
+            });
+        }
+    }
+
+    /// Ends the current run of prose before `range` and resumes after it.
+    fn skip(&mut self, range: Range<usize>) {
+        let text = self.text_start..range.start;
+        self.push_text(text);
+        self.text_start = range.end;
+        self.pos = range.end;
+    }
+
+    /// Inline markup must start at the beginning of a word.
+    fn starts_markup(&self) -> bool {
+        self.pos == 0 || !self.src[self.pos - 1].is_ascii_alphanumeric()
+    }
+
+    /// Handles text in backticks starting at `open`, with any role starting at
+    /// `start`: inline literals, hyperlink references and interpreted text.
+    fn interpreted(&mut self, start: usize, open: usize) {
+        if self.src[open..self.end].starts_with(b"``") {
+            let close = self.find(open + 2, b"``").map_or(self.end, |c| c + 2);
+            self.skip(start..close);
+            return;
+        }
+        let Some(close) = self.find(open + 1, b"`") else {
+            self.pos = open + 1;
+            return;
+        };
+        let mut after = close + 1;
+        let reference = self.src.get(after) == Some(&b'_');
+        while after < self.end && self.src[after] == b'_' {
+            after += 1;
+        }
+        // A trailing role, as in `text`:role:.
+        if !reference
+            && self.src.get(after) == Some(&b':')
+            && let Some(role_end) = self.find(after + 1, b":")
+            && self.src[after + 1..role_end]
+                .iter()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+        {
+            after = role_end + 1;
+        }
+
+        // `title <target>` keeps its title for references and roles alike.
+        let target = self.src[open..close]
+            .iter()
+            .rposition(|&b| b == b'<')
+            .map(|p| open + p);
+        let titled = self.src[close - 1] == b'>' && target.is_some_and(|t| t > open + 1);
+        if (reference || start != open) && titled {
+            self.skip(start..open + 1);
+            self.skip(target.unwrap_or(close)..after);
+        } else if reference {
+            self.skip(start..open + 1);
+            self.skip(close..after);
+        } else {
+            self.skip(start..after);
+        }
+    }
+
+    /// Handles `:role:` followed by text in backticks, and field list markers
+    /// such as `:param name:` at the start of a line.
+    fn role_or_field(&mut self) {
+        let Some(close) = self.find(self.pos + 1, b":").filter(|&c| c > self.pos + 1) else {
+            self.pos += 1;
+            return;
+        };
+        let name = &self.src[self.pos + 1..close];
+        if self.src.get(close + 1) == Some(&b'`')
+            && name
+                .iter()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+        {
+            self.interpreted(self.pos, close + 1);
+            return;
+        }
+        let line_start = self.src[..self.pos]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |p| p + 1);
+        let at_line_start = self.src[line_start..self.pos]
+            .iter()
+            .all(|b| *b == b' ' || *b == b'\t');
+        let followed = self
+            .src
+            .get(close + 1)
+            .is_none_or(|b| b.is_ascii_whitespace());
+        if at_line_start && followed && !name.contains(&b'`') && !name.contains(&b'\n') {
+            self.skip(self.pos..close + 1);
+        } else {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips substitution references such as `|version|`.
+    fn substitution(&mut self) {
+        let inner = self.peek(1).is_some_and(|b| !b.is_ascii_whitespace());
+        match self.find(self.pos + 1, b"|") {
+            Some(close)
+                if inner
+                    && !self.src[close - 1].is_ascii_whitespace()
+                    && !self.src[self.pos..close].contains(&b'\n') =>
+            {
+                let mut after = close + 1;
+                while after < self.end && self.src[after] == b'_' {
+                    after += 1;
+                }
+                self.skip(self.pos..after);
+            }
+            _ => self.pos += 1,
+        }
+    }
+
+    /// Skips footnote and citation references such as `[1]_` and `[Smith2020]_`.
+    fn label_reference(&mut self) {
+        match self.find(self.pos + 1, b"]") {
+            Some(close) if self.src.get(close + 1) == Some(&b'_') => self.skip(self.pos..close + 2),
+            _ => self.pos += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prose(content: &str) -> String {
+        extract_regions(content)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::Text)
+            .map(|r| content[r.range].to_string())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    #[test]
+    fn test_blocks() {
+        let doc = "Title\n=====\n\nExample::\n\n    lteral code\n\nAfter.\n\n>>> prnt(1)\n1\n\n\
+            .. note:: Be carefull\n   :class: wrnng\n\n   Body text.\n\n\
+            .. code-block:: pythn\n\n   x = 1\n\n.. _trget: https://exmaple.com\n\n\
+            .. |sbst| replace:: text\n\n.. [#] Footnote text.\n\n.. A commnt\n   continued\n\nEnd.\n";
+        assert_eq!(
+            prose(doc),
+            "Title\n=====|Example::|After.| Be carefull|Body text.| Footnote text.|End."
+        );
+        let comments: Vec<&str> = extract_regions(doc)
+            .into_iter()
+            .filter(|r| r.kind == RegionKind::Comment)
+            .map(|r| &doc[r.range])
+            .collect();
+        assert_eq!(comments, vec![".. A commnt\n   continued"]);
+    }
+
+    #[test]
+    fn test_inline() {
+        let doc = "Use ``prnt()`` and :func:`os.pth` or :ref:`the guide <gide>`, see `Docs <https://x.io>`_, \
+            `Python`_ |vrsion| [1]_ [Smth2020]_ `dflt`.\n\n:param nme: The name.\n";
+        assert_eq!(
+            prose(doc),
+            "Use | and | or |the guide |, see |Docs |, |Python| | | | |.| The name."
+        );
+    }
+}
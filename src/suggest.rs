@@ -0,0 +1,313 @@
+//! Spelling suggestions based on edit distance.
+//!
+//! Candidates are found with a symmetric delete index (SymSpell): two words
+//! within edit distance `k` of each other can both be reduced to the same
+//! string by deleting at most `k` characters from each. The index maps the
+//! deletions of the first `PREFIX_LEN` characters of every dictionary word to
+//! the words with that prefix, so a lookup generates the deletions of the
+//! misspelling's prefix, collects the words they lead to and verifies each
+//! with a bounded distance. Characters past the prefix behave like further
+//! deletions, so limiting the index to prefixes keeps it small without
+//! missing any candidate.
+//!
+//! Candidates are ranked by distance, then single transpositions (`teh` →
+//! `the`) before other edits, then common words before rare ones, using the
+//! bundled `resources/frequency.txt`.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Common English words, most frequent first.
+const COMMON_WORDS: &str = include_str!("../resources/frequency.txt");
+
+/// The number of leading characters of each word whose deletions are indexed.
+const PREFIX_LEN: usize = 6;
+
+/// The largest edit distance a lookup supports.
+pub const MAX_DISTANCE: usize = 2;
+
+/// A symmetric delete index of dictionary words.
+pub struct SuggestionIndex {
+    /// The words, sorted so that words sharing a prefix are adjacent.
+    words: Vec<Box<[char]>>,
+    /// The index of the first word of each prefix group, followed by the
+    /// number of words.
+    groups: Vec<u32>,
+    /// For each deletion of each group's prefix, its hash in the high 32 bits
+    /// and the group in the low 32 bits, sorted.
+    deletions: Vec<u64>,
+}
+
+impl SuggestionIndex {
+    /// Builds an index containing all given words.
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut words: Vec<Box<[char]>> = words.into_iter().map(|w| w.as_ref().chars().collect()).collect();
+        words.sort_unstable();
+        words.dedup();
+
+        let mut groups = Vec::new();
+        let mut deletions = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 && prefix(&words[i - 1]) == prefix(word) {
+                continue;
+            }
+            let group = groups.len() as u64;
+            groups.push(i as u32);
+            for_each_deletion(prefix(word), MAX_DISTANCE, |hash| deletions.push(u64::from(hash) << 32 | group));
+        }
+        groups.push(words.len() as u32);
+        deletions.sort_unstable();
+        deletions.dedup();
+        Self { words, groups, deletions }
+    }
+
+    /// Returns all words within `max_distance` of `word`, with their distances.
+    ///
+    /// `max_distance` is capped at `MAX_DISTANCE`.
+    pub fn find(&self, word: &str, max_distance: usize) -> Vec<(String, usize)> {
+        let max_distance = max_distance.min(MAX_DISTANCE);
+        let chars: Vec<char> = word.chars().collect();
+
+        let mut groups = Vec::new();
+        for_each_deletion(prefix(&chars), max_distance, |hash| {
+            let key = u64::from(hash) << 32;
+            let start = self.deletions.partition_point(|&d| d < key);
+            let matching = self.deletions[start..].iter().take_while(|&&d| d >> 32 == key >> 32);
+            groups.extend(matching.map(|&d| (d & u64::from(u32::MAX)) as usize));
+        });
+        groups.sort_unstable();
+        groups.dedup();
+
+        let mut found = Vec::new();
+        let mut rows = Rows::default();
+        for group in groups {
+            let range = self.groups[group] as usize..self.groups[group + 1] as usize;
+            for candidate in &self.words[range] {
+                if let Some(dist) = bounded_distance(&chars, candidate, max_distance, &mut rows) {
+                    found.push((candidate.iter().collect(), dist));
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Returns the indexed prefix of a word.
+fn prefix(word: &[char]) -> &[char] {
+    &word[..word.len().min(PREFIX_LEN)]
+}
+
+/// Calls `f` with the hash of every string obtained by deleting at most
+/// `depth` characters from `chars`, which has at most `PREFIX_LEN` characters.
+///
+/// A string reachable in several ways is hashed more than once.
+fn for_each_deletion(chars: &[char], depth: usize, mut f: impl FnMut(u32)) {
+    for deleted in 0u32..1 << chars.len() {
+        if deleted.count_ones() as usize > depth {
+            continue;
+        }
+        // FNV-1a over the kept characters.
+        let mut hash: u32 = 0x811c_9dc5;
+        for (i, &c) in chars.iter().enumerate() {
+            if deleted & (1 << i) == 0 {
+                hash = (hash ^ u32::from(c)).wrapping_mul(0x0100_0193);
+            }
+        }
+        f(hash);
+    }
+}
+
+/// Returns up to `limit` ranked suggestions for a (lowercase) misspelled word.
+///
+/// Candidates are ordered by `score`, then by whether they share the first
+/// letter with the misspelling, then by length difference.
+pub fn rank(index: &SuggestionIndex, word: &str, limit: usize) -> Vec<String> {
+    let len = word.chars().count();
+    let max_distance = match len {
+        0..=2 => return Vec::new(),
+        3..=4 => 1,
+        _ => MAX_DISTANCE,
+    };
+
+    let chars: Vec<char> = word.chars().collect();
+    let mut candidates: Vec<_> = index
+        .find(word, max_distance)
+        .into_iter()
+        .map(|(candidate, dist)| {
+            let other: Vec<char> = candidate.chars().collect();
+            let key = (
+                (dist, !is_transposition(&chars, &other), frequency_rank(&candidate)),
+                other.first() != chars.first(),
+                other.len().abs_diff(len),
+            );
+            (key, candidate)
+        })
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(limit)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Scores a candidate correction of a (lowercase) misspelled word; lower is
+/// better.
+///
+/// The score orders by edit distance, then prefers a single transposition of
+/// adjacent letters, then common words by their frequency rank. Two candidates
+/// with the same score are equally likely corrections.
+pub fn score(word: &str, candidate: &str) -> (usize, bool, usize) {
+    let word: Vec<char> = word.chars().collect();
+    let other: Vec<char> = candidate.chars().collect();
+    (distance(&word, &other), !is_transposition(&word, &other), frequency_rank(candidate))
+}
+
+/// Returns `true` if `b` is `a` with two adjacent characters swapped.
+fn is_transposition(a: &[char], b: &[char]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = (0..a.len()).filter(|&i| a[i] != b[i]);
+    match (diff.next(), diff.next(), diff.next()) {
+        (Some(i), Some(j), None) => j == i + 1 && a[i] == b[j] && a[j] == b[i],
+        _ => false,
+    }
+}
+
+/// Returns the position of a word in the list of common words, or
+/// `usize::MAX` for words not in it.
+fn frequency_rank(word: &str) -> usize {
+    static RANKS: OnceLock<HashMap<&'static str, usize>> = OnceLock::new();
+    let ranks = RANKS.get_or_init(|| COMMON_WORDS.lines().enumerate().map(|(i, w)| (w, i)).collect());
+    ranks.get(word).copied().unwrap_or(usize::MAX)
+}
+
+/// Computes the optimal string alignment distance between two words.
+///
+/// Like Levenshtein distance, but a transposition of two adjacent characters
+/// counts as a single edit (`teh` → `the`).
+pub fn distance(a: &[char], b: &[char]) -> usize {
+    bounded_distance(a, b, usize::MAX, &mut Rows::default()).unwrap_or(usize::MAX)
+}
+
+/// Reusable row buffers for `bounded_distance`.
+#[derive(Default)]
+struct Rows {
+    prev2: Vec<usize>,
+    prev: Vec<usize>,
+    curr: Vec<usize>,
+}
+
+/// Computes the optimal string alignment distance, giving up with `None` as
+/// soon as it is certain to exceed `max`.
+fn bounded_distance(a: &[char], b: &[char], max: usize, rows: &mut Rows) -> Option<usize> {
+    let (n, m) = (a.len(), b.len());
+    if n.abs_diff(m) > max {
+        return None;
+    }
+
+    let Rows { prev2, prev, curr } = rows;
+    prev2.clear();
+    prev2.resize(m + 1, 0);
+    prev.clear();
+    prev.extend(0..=m);
+    curr.clear();
+    curr.resize(m + 1, 0);
+
+    for i in 1..=n {
+        curr[0] = i;
+        let mut row_min = curr[0];
+        for j in 1..=m {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(prev2[j - 2] + 1);
+            }
+            curr[j] = best;
+            row_min = row_min.min(best);
+        }
+        // Every later cell derives from this row (or the one before it for
+        // transpositions, which costs one more), so the distance cannot recover.
+        if row_min > max && (i == n || prev.iter().min().is_some_and(|&p| p > max)) {
+            return None;
+        }
+        std::mem::swap(prev2, prev);
+        std::mem::swap(prev, curr);
+    }
+
+    let dist = prev[m];
+    (dist <= max).then_some(dist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dist(a: &str, b: &str) -> usize {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        distance(&a, &b)
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(dist("kitten", "sitting"), 3);
+        assert_eq!(dist("teh", "the"), 1);
+        assert_eq!(dist("occurance", "occurrence"), 2);
+        assert_eq!(dist("", "abc"), 3);
+        assert_eq!(dist("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_rank() {
+        let index = SuggestionIndex::new(["occurrence", "occurrences", "reference", "referee", "the"]);
+        assert_eq!(rank(&index, "occurence", 5), vec!["occurrence", "occurrences"]);
+        assert_eq!(rank(&index, "referance", 5), vec!["reference"]);
+        assert_eq!(rank(&index, "teh", 1), vec!["the"]);
+    }
+
+    #[test]
+    fn test_rank_prefers_transpositions_and_common_words() {
+        let index = SuggestionIndex::new(["tch", "tea", "tec", "ted", "tee", "the", "woold", "world", "form", "from"]);
+        assert_eq!(rank(&index, "teh", 3), vec!["the", "tch", "tea"]);
+        assert_eq!(rank(&index, "wrold", 2), vec!["world", "woold"]);
+        assert_eq!(rank(&index, "frm", 2), vec!["from", "form"]);
+        assert!(score("teh", "the") < score("teh", "tea"));
+    }
+
+    #[test]
+    fn test_index_finds_every_candidate() {
+        let words = [
+            "a", "ab", "abc", "abcdef", "abcdefgh", "xabcdefgh", "xyabcdefgh", "bacdefgh", "abdcefgh", "abcdefhg",
+            "abcdegfh", "zbcdefgh", "abcdxfgh", "abcdefghij", "cdefgh", "bcdefgh", "the", "teh", "het", "hte",
+        ];
+        let index = SuggestionIndex::new(words);
+        for query in words.iter().copied().chain(["abcdefghi", "bcd", "qwerty", "ba", ""]) {
+            let chars: Vec<char> = query.chars().collect();
+            for max in 0..=MAX_DISTANCE {
+                let mut expected: Vec<(String, usize)> = words
+                    .iter()
+                    .map(|w| (w.to_string(), distance(&chars, &w.chars().collect::<Vec<_>>())))
+                    .filter(|&(_, d)| d <= max)
+                    .collect();
+                let mut found = index.find(query, max);
+                expected.sort();
+                found.sort();
+                assert_eq!(found, expected, "{query} within {max}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_bounded_distance_gives_up() {
+        let a: Vec<char> = "abcdefgh".chars().collect();
+        let b: Vec<char> = "zyxwvuts".chars().collect();
+        assert_eq!(bounded_distance(&a, &b, 2, &mut Rows::default()), None);
+        assert_eq!(bounded_distance(&a, &a, 2, &mut Rows::default()), Some(0));
+    }
+}
@@ -0,0 +1,166 @@
+//! Word and identifier tokenization.
+//!
+//! Source code rarely contains plain words: most of the interesting text lives
+//! inside identifiers such as `parseConfigFile` or `max_retry_count`. This module
+//! extracts candidate tokens from a line and splits identifiers into the
+//! sub-words that are actually looked up in the dictionary.
+
+/// Extracts candidate tokens from a string slice.
+///
+/// A token is a run of alphanumeric characters, underscores, hyphens and
+/// apostrophes, trimmed of leading and trailing punctuation. Returns a list of
+/// (byte_offset, token) pairs; single characters and tokens without any
+/// alphabetic character are dropped.
+pub fn extract_tokens(content: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in content.char_indices() {
+        if is_token_char(c) {
+            if start.is_none() {
+                start = Some(i);
+            }
+        } else if let Some(s) = start.take() {
+            push_token(&mut tokens, content, s, i);
+        }
+    }
+
+    if let Some(s) = start {
+        push_token(&mut tokens, content, s, content.len());
+    }
+
+    tokens
+}
+
+/// Splits an identifier into its sub-words.
+///
+/// Splits on underscores, hyphens, lower-to-upper case transitions and acronym
+/// boundaries (`HTTPServer` becomes `HTTP`, `Server`). Segments between
+/// underscores and hyphens that contain digits, such as hashes (`a3fbe9c2dd`)
+/// or `base64Encode`, are skipped whole. Apostrophes are kept inside words so
+/// contractions like `don't` survive. Returns a list of (byte_offset, sub_word)
+/// pairs relative to the start of `ident`; single-letter pieces are dropped.
+pub fn split_identifier(ident: &str) -> Vec<(usize, &str)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, c) in ident.char_indices() {
+        if !(c.is_alphanumeric() || c == '\'') {
+            split_segment(&mut parts, ident, start, i);
+            start = i + c.len_utf8();
+        }
+    }
+    split_segment(&mut parts, ident, start, ident.len());
+    parts
+}
+
+/// Splits `ident[from..to]`, a segment without separators, on case changes.
+fn split_segment<'a>(parts: &mut Vec<(usize, &'a str)>, ident: &'a str, from: usize, to: usize) {
+    let segment = &ident[from..to];
+    if segment.chars().any(char::is_numeric) {
+        return;
+    }
+    let mut start: Option<usize> = None;
+    let chars: Vec<(usize, char)> = segment.char_indices().map(|(i, c)| (from + i, c)).collect();
+
+    for (idx, &(i, c)) in chars.iter().enumerate() {
+        let Some(s) = start else {
+            start = Some(i);
+            continue;
+        };
+
+        if c.is_uppercase() {
+            let prev = previous_letter(&chars, idx);
+            let next_is_lower = chars
+                .get(idx + 1)
+                .is_some_and(|&(_, n)| n.is_lowercase());
+            let boundary = match prev {
+                // `parseConfig`: lower followed by upper.
+                Some(p) if p.is_lowercase() => true,
+                // `HTTPServer`: the last upper of an acronym starts a new word.
+                Some(p) if p.is_uppercase() => next_is_lower,
+                _ => false,
+            };
+            if boundary {
+                push_part(parts, ident, s, i);
+                start = Some(i);
+            }
+        }
+    }
+
+    if let Some(s) = start {
+        push_part(parts, ident, s, to);
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '\''
+}
+
+fn push_token<'a>(tokens: &mut Vec<(usize, &'a str)>, content: &'a str, start: usize, end: usize) {
+    let raw = &content[start..end];
+    let trimmed_start = raw.trim_start_matches(['\'', '-', '_']);
+    let offset = start + (raw.len() - trimmed_start.len());
+    let token = trimmed_start.trim_end_matches(['\'', '-', '_']);
+    if token.chars().count() > 1 && token.chars().any(char::is_alphabetic) {
+        tokens.push((offset, token));
+    }
+}
+
+fn push_part<'a>(parts: &mut Vec<(usize, &'a str)>, ident: &'a str, start: usize, end: usize) {
+    let raw = &ident[start..end];
+    let trimmed_start = raw.trim_start_matches('\'');
+    let offset = start + (raw.len() - trimmed_start.len());
+    let part = trimmed_start.trim_end_matches('\'');
+    if part.chars().count() > 1 {
+        parts.push((offset, part));
+    }
+}
+
+/// Returns the closest preceding character, skipping apostrophes.
+fn previous_letter(chars: &[(usize, char)], idx: usize) -> Option<char> {
+    chars[..idx].iter().rev().map(|&(_, c)| c).find(|&c| c != '\'')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(parts: Vec<(usize, &str)>) -> Vec<&str> {
+        parts.into_iter().map(|(_, w)| w).collect()
+    }
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(words(split_identifier("parseConfigFile")), vec!["parse", "Config", "File"]);
+        assert_eq!(words(split_identifier("max_retry_count")), vec!["max", "retry", "count"]);
+        assert_eq!(words(split_identifier("kebab-case-name")), vec!["kebab", "case", "name"]);
+        assert_eq!(words(split_identifier("HTTPServer")), vec!["HTTP", "Server"]);
+        assert_eq!(words(split_identifier("MAX_VALUE")), vec!["MAX", "VALUE"]);
+        assert_eq!(words(split_identifier("utf8_decode")), vec!["decode"]);
+        assert_eq!(words(split_identifier("don't")), vec!["don't"]);
+    }
+
+    #[test]
+    fn test_split_identifier_offsets() {
+        let parts = split_identifier("recieveMessage");
+        assert_eq!(parts, vec![(0, "recieve"), (7, "Message")]);
+
+        let parts = split_identifier("XMLHttpRequest");
+        assert_eq!(parts, vec![(0, "XML"), (3, "Http"), (7, "Request")]);
+    }
+
+    #[test]
+    fn test_split_identifier_skips_digits() {
+        assert!(split_identifier("a3fbe9c2dd").is_empty());
+        assert!(split_identifier("base64Encode").is_empty());
+        assert!(split_identifier("deadbeef42").is_empty());
+        assert_eq!(split_identifier("sha256-e3b0c44298fc-digest"), vec![(20, "digest")]);
+        assert_eq!(split_identifier("commit_9fceb02_messsage"), vec![(0, "commit"), (15, "messsage")]);
+    }
+
+    #[test]
+    fn test_extract_tokens() {
+        let tokens = extract_tokens("let _recieve_msg = --flag 42;");
+        assert_eq!(tokens, vec![(0, "let"), (5, "recieve_msg"), (21, "flag")]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Re-checking files as they change.
+//!
+//! After an initial `Engine::run`, the file system notifies the watcher of
+//! changes under the watched path. Events are debounced, filtered through the
+//! engine's include and exclude globs, hidden paths and the root's ignore
+//! files, and only the reported files are checked again (or dropped, if they
+//! were removed) before the results are redrawn. A change to a config file or
+//! a root ignore file rebuilds the engine and starts over.
+
+use crate::config::CONFIG_FILE;
+use crate::engine::{Engine, SpellError};
+use crate::report::{HumanReporter, Reporter, Summary};
+use anyhow::{Context, Result};
+use colored::*;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{Event, EventKind, RecursiveMode, Watcher as _};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Ignore files at the watched root whose rules the watcher applies.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".spellcheckignore"];
+
+/// The current results for a watched path.
+pub struct Watcher {
+    engine: Engine,
+    root: PathBuf,
+    /// The forms of `root` that reported paths may start with: as given,
+    /// absolute and canonical.
+    prefixes: Vec<PathBuf>,
+    /// The rules of the root's ignore files.
+    ignore: Gitignore,
+    errors: BTreeMap<PathBuf, Vec<SpellError>>,
+    /// Files that could not be checked, with the reason.
+    failures: BTreeMap<PathBuf, String>,
+    /// Processing errors from the initial run that are not tied to a file.
+    messages: Vec<String>,
+}
+
+impl Watcher {
+    /// Checks everything under `root`.
+    pub async fn start(engine: Engine, root: PathBuf) -> Self {
+        let mut errors: BTreeMap<PathBuf, Vec<SpellError>> = BTreeMap::new();
+        let mut messages = Vec::new();
+        let mut rx = engine.run(root.clone());
+        while let Some(res) = rx.recv().await {
+            match res {
+                Ok(error) => errors.entry(error.file.clone()).or_default().push(error),
+                Err(e) => messages.push(e),
+            }
+        }
+
+        let mut prefixes = vec![root.clone()];
+        prefixes.extend(std::path::absolute(&root).ok());
+        prefixes.extend(root.canonicalize().ok());
+        let mut builder = GitignoreBuilder::new(&root);
+        for name in IGNORE_FILES {
+            let file = root.join(name);
+            if file.is_file() {
+                // Invalid lines are skipped, as when walking.
+                let _ = builder.add(file);
+            }
+        }
+        let ignore = builder.build().unwrap_or_else(|_| Gitignore::empty());
+        Self { engine, root, prefixes, ignore, errors, failures: BTreeMap::new(), messages }
+    }
+
+    /// Returns a reported path relative to the watched root, or `None` if it
+    /// is outside it.
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        self.prefixes.iter().find_map(|prefix| path.strip_prefix(prefix).ok())
+    }
+
+    /// Returns `true` if a path relative to the root is one `check` would
+    /// visit: matched by the globs, not hidden and not ignored.
+    fn is_watched(&self, relative: &Path) -> bool {
+        !relative.as_os_str().is_empty()
+            && self.engine.should_check(relative)
+            && !relative.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+            && !self.ignore.matched_path_or_any_parents(relative, false).is_ignore()
+    }
+
+    /// Re-checks the files at `paths`, as reported by the file system, and
+    /// drops the results of those that were removed.
+    ///
+    /// Returns the watched paths that were checked again or removed.
+    pub fn update(&mut self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let changed: BTreeSet<PathBuf> = paths
+            .iter()
+            .filter_map(|path| self.relative(path))
+            .filter(|relative| self.is_watched(relative))
+            .map(|relative| self.root.join(relative))
+            .collect();
+
+        for path in &changed {
+            self.errors.remove(path);
+            self.failures.remove(path);
+            if !path.is_file() {
+                continue;
+            }
+            match self.engine.check_path(&self.root, path) {
+                Ok(errors) if errors.is_empty() => {}
+                Ok(errors) => {
+                    self.errors.insert(path.clone(), errors);
+                }
+                Err(e) => {
+                    self.failures.insert(path.clone(), format!("{:#}", e));
+                }
+            }
+        }
+        changed.into_iter().collect()
+    }
+
+    /// Returns the current errors of `path`.
+    pub fn errors(&self, path: &Path) -> &[SpellError] {
+        self.errors.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Prints all current results, like `check` does, followed by a status line.
+    pub fn render(&self, out: &mut dyn Write, status: &str) -> Result<()> {
+        let mut reporter = HumanReporter::new(&mut *out);
+        let mut summary = Summary::default();
+        for error in self.errors.values().flatten() {
+            summary.spelling_errors += 1;
+            reporter.spelling_error(error)?;
+        }
+        for message in self.messages.iter().chain(self.failures.values()) {
+            summary.processing_errors += 1;
+            reporter.processing_error(message)?;
+        }
+        reporter.finish(&summary)?;
+        writeln!(out, "\n{} {}", "watch".blue(), status)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Watches `root` until interrupted, redrawing the results after each change.
+///
+/// Events are collected until none arrive for `debounce`. `build` creates
+/// the engine and returns it with the config files to watch; it is called
+/// again whenever one of them, or a root ignore file, changes.
+pub async fn watch(root: PathBuf, debounce: Duration, build: impl Fn() -> Result<(Engine, Vec<PathBuf>)>) -> Result<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut notifier = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let _ = tx.send(res);
+    })
+    .context("Failed to start watching for changes")?;
+    notifier
+        .watch(&root, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", root.display()))?;
+
+    let (engine, mut config_files) = build()?;
+    watch_config_files(&mut notifier, &root, &config_files);
+    let mut watcher = Watcher::start(engine, root.clone()).await;
+    let idle = format!("Watching {} for changes. Press Ctrl-C to stop.", root.display());
+    redraw(&watcher, &idle)?;
+
+    while let Some(res) = rx.recv().await {
+        // Collect events until none arrive for `debounce`.
+        let mut paths = Vec::new();
+        collect(res, &mut paths);
+        while let Ok(Some(res)) = tokio::time::timeout(debounce, rx.recv()).await {
+            collect(res, &mut paths);
+        }
+        if paths.is_empty() {
+            continue;
+        }
+
+        if paths.iter().any(|path| reloads(path, &watcher, &config_files)) {
+            match build() {
+                Ok((engine, files)) => {
+                    config_files = files;
+                    watch_config_files(&mut notifier, &root, &config_files);
+                    watcher = Watcher::start(engine, root.clone()).await;
+                    redraw(&watcher, &format!("Reloaded the configuration. {}", idle))?;
+                }
+                Err(e) => eprintln!("{} Failed to reload the configuration: {:#}", "error".red().bold(), e),
+            }
+            continue;
+        }
+
+        let changed = watcher.update(&paths);
+        if let Some(first) = changed.first() {
+            let what = match changed.len() {
+                1 => first.display().to_string(),
+                n => format!("{} files", n),
+            };
+            redraw(&watcher, &format!("Re-checked {}. {}", what, idle))?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds the paths of an event to `paths`. Access events are skipped, since
+/// checking a file opens it.
+fn collect(res: notify::Result<Event>, paths: &mut Vec<PathBuf>) {
+    match res {
+        Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+        Ok(event) => paths.extend(event.paths),
+        Err(e) => eprintln!("{} Failed to watch for changes: {}", "error".red().bold(), e),
+    }
+}
+
+/// Watches the config files outside `root`, which the recursive watch of
+/// `root` does not cover. Files that do not exist are skipped.
+fn watch_config_files(notifier: &mut impl notify::Watcher, root: &Path, files: &[PathBuf]) {
+    let root = std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf());
+    for file in files {
+        let absolute = std::path::absolute(file).unwrap_or_else(|_| file.clone());
+        if !absolute.starts_with(&root) && file.is_file() {
+            let _ = notifier.watch(file, RecursiveMode::NonRecursive);
+        }
+    }
+}
+
+/// Returns `true` if a change to `path` requires rebuilding the engine: it
+/// is a config file, in use or new, or an ignore file at the root.
+fn reloads(path: &Path, watcher: &Watcher, config_files: &[PathBuf]) -> bool {
+    let absolute = |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    path.file_name().is_some_and(|name| name == CONFIG_FILE)
+        || config_files.iter().any(|file| absolute(file) == absolute(path))
+        || watcher
+            .relative(path)
+            .is_some_and(|relative| IGNORE_FILES.iter().any(|name| relative == Path::new(name)))
+}
+
+/// Clears the terminal, if stdout is one, and renders the results.
+fn redraw(watcher: &Watcher, status: &str) -> Result<()> {
+    use std::io::IsTerminal;
+    let mut stdout = std::io::stdout().lock();
+    if stdout.is_terminal() {
+        write!(stdout, "\x1b[2J\x1b[H")?;
+    }
+    watcher.render(&mut stdout, status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_schema::Config;
+    use crate::dictionary::Dictionary;
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_update_rechecks_reported_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().to_path_buf();
+        let (a, b) = (root.join("a.md"), root.join("b.md"));
+        fs::write(&a, "teh cat\n")?;
+        fs::write(root.join("skip.bin"), "teh\n")?;
+        fs::write(root.join(".spellcheckignore"), "drafts/\n")?;
+
+        let mut dictionary = Dictionary::new();
+        dictionary.add_words(["the", "cat", "dog"]);
+        let engine = Engine::try_new(Config::default(), dictionary)?;
+        let mut watcher = Watcher::start(engine, root.clone()).await;
+        assert_eq!(watcher.errors(&a).len(), 1);
+        assert!(watcher.update(&[]).is_empty());
+
+        fs::write(&a, "the cat\n")?;
+        fs::write(&b, "teh dgo\n")?;
+        fs::create_dir_all(root.join("drafts"))?;
+        fs::create_dir_all(root.join(".hidden"))?;
+        let reported = [
+            a.clone(),
+            b.clone(),
+            b.clone(),
+            root.join("skip.bin"),
+            root.join("drafts/c.md"),
+            root.join(".hidden/d.md"),
+            dir.path().parent().unwrap_or(dir.path()).join("elsewhere.md"),
+        ];
+        assert_eq!(watcher.update(&reported), vec![a.clone(), b.clone()]);
+        assert!(watcher.errors(&a).is_empty());
+        assert_eq!(watcher.errors(&b).iter().map(|e| e.word.as_str()).collect::<Vec<_>>(), vec!["teh", "dgo"]);
+
+        fs::remove_file(&b)?;
+        assert_eq!(watcher.update(std::slice::from_ref(&b)), vec![b.clone()]);
+        assert!(watcher.errors(&b).is_empty());
+
+        let mut out = Vec::new();
+        watcher.render(&mut out, "idle")?;
+        assert!(String::from_utf8_lossy(&out).contains("idle"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reloads_on_config_and_ignore_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().to_path_buf();
+        let engine = Engine::try_new(Config::default(), Dictionary::new())?;
+        let watcher = Watcher::start(engine, root.clone()).await;
+        let config = dir.path().join("shared.toml");
+
+        assert!(reloads(&root.join("docs").join(CONFIG_FILE), &watcher, &[]));
+        assert!(reloads(&config, &watcher, std::slice::from_ref(&config)));
+        assert!(reloads(&root.join(".gitignore"), &watcher, &[]));
+        assert!(!reloads(&root.join("docs/.gitignore"), &watcher, &[]));
+        assert!(!reloads(&root.join("a.md"), &watcher, &[]));
+        Ok(())
+    }
+}
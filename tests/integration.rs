@@ -62,3 +62,255 @@ fn test_cli_init() {
     let config_file = temp_dir.path().join("spellcheck.toml");
     assert!(config_file.exists());
 }
+
+#[test]
+fn test_cli_check_fix() {
+    let bin = get_bin_path();
+    if !bin.exists() {
+        println!("Skipping test: binary not found at {:?}", bin);
+        return;
+    }
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let file = temp_dir.path().join("notes.txt");
+    std::fs::write(&file, "The Referance manual.\n").expect("Failed to write file");
+
+    // A dry run prints the diff and leaves the file untouched.
+    let output = Command::new(&bin)
+        .args(["check", "--fix", "--dry-run", "."])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("failed to execute process");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("+The Reference manual."), "Expected a diff. STDOUT: {}", stdout);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "The Referance manual.\n");
+
+    let output = Command::new(&bin)
+        .args(["check", "--fix", "."])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success(), "Expected all errors to be fixed");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "The Reference manual.\n");
+}
+
+#[test]
+fn test_cli_check_json() {
+    let bin = get_bin_path();
+    if !bin.exists() {
+        println!("Skipping test: binary not found at {:?}", bin);
+        return;
+    }
+
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests/fixtures/proj1");
+
+    let output = Command::new(bin)
+        .args(["check", "--no-cache", "--format", "json", "."])
+        .current_dir(&path)
+        .output()
+        .expect("failed to execute process");
+    assert_eq!(output.status.code(), Some(1), "Expected exit code 1 due to spelling errors");
+
+    let doc: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    let words: Vec<&str> = doc["errors"]
+        .as_array()
+        .expect("errors should be an array")
+        .iter()
+        .filter_map(|e| e["word"].as_str())
+        .collect();
+    assert!(words.contains(&"occurance"), "Should have found 'occurance'");
+    assert_eq!(doc["summary"]["spelling_errors"], words.len());
+}
+
+#[test]
+fn test_cli_check_relative_file() {
+    let bin = get_bin_path();
+    if !bin.exists() {
+        println!("Skipping test: binary not found at {:?}", bin);
+        return;
+    }
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    std::fs::write(temp_dir.path().join("README.md"), "An occurance of a typo.\n").expect("Failed to write file");
+
+    // A bare file name has an empty parent directory.
+    let output = Command::new(&bin)
+        .args(["check", "--no-cache", "README.md"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("failed to execute process");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "Expected exit code 1. STDERR: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("occurance"), "Should have found 'occurance'. STDOUT: {}", stdout);
+}
+
+#[test]
+fn test_cli_dict_compile() {
+    let bin = get_bin_path();
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    std::fs::write(temp_dir.path().join("words.txt"), "Kubernetes\nkubectl\n").unwrap();
+    std::fs::write(temp_dir.path().join("notes.md"), "Deploy with kubectl to Kubernetes.\n").unwrap();
+    std::fs::write(
+        temp_dir.path().join("spellcheck.toml"),
+        "[dictionary]\nextra_dictionaries = [\"words.dict\"]\n",
+    )
+    .unwrap();
+
+    let output = Command::new(&bin)
+        .args(["dict", "compile", "words.txt", "--output", "words.dict"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success(), "Compile failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Compiled 2 words"));
+
+    let output = Command::new(&bin)
+        .args(["check", "."])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success(), "Check failed: {}", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_cli_cache() {
+    let bin = get_bin_path();
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    std::fs::write(temp_dir.path().join("notes.md"), "An occurance of a typo.\n").unwrap();
+    let check = || {
+        Command::new(&bin)
+            .args(["check", "--cache", "."])
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("failed to execute process")
+    };
+
+    // The cache is opt-in.
+    let output = Command::new(&bin)
+        .args(["check", "."])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("failed to execute process");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!temp_dir.path().join(".spellcheck-cache").exists());
+
+    let first = check();
+    assert_eq!(first.status.code(), Some(1));
+    assert!(temp_dir.path().join(".spellcheck-cache/results.json").exists());
+    let second = check();
+    assert_eq!(second.stdout, first.stdout, "Cached results should match a fresh check");
+
+    let output = Command::new(&bin)
+        .args(["cache", "clean"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed cache"));
+    assert!(!temp_dir.path().join(".spellcheck-cache").exists());
+}
+
+#[test]
+fn test_cli_check_changed_lines() {
+    let bin = get_bin_path();
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("failed to run git")
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    std::fs::write(temp_dir.path().join("legacy.md"), "An old occurance.\n").unwrap();
+    std::fs::write(temp_dir.path().join("notes.md"), "An old occurance.\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "init"]);
+    std::fs::write(temp_dir.path().join("notes.md"), "An old occurance.\nA new recieve.\n").unwrap();
+
+    let check = |args: &[&str]| {
+        let output = Command::new(&bin)
+            .args(["check", "--no-cache", "--format", "json"])
+            .args(args)
+            .arg(".")
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("failed to execute process");
+        let doc: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+        let mut words: Vec<String> = doc["errors"]
+            .as_array()
+            .expect("errors should be an array")
+            .iter()
+            .map(|e| format!("{}:{}", e["file"].as_str().unwrap().trim_start_matches("./"), e["word"].as_str().unwrap()))
+            .collect();
+        words.sort();
+        words
+    };
+
+    assert_eq!(check(&["--changed"]), vec!["notes.md:occurance", "notes.md:recieve"]);
+    assert_eq!(check(&["--changed", "--changed-lines"]), vec!["notes.md:recieve"]);
+    assert!(check(&["--staged"]).is_empty());
+}
+
+#[test]
+fn test_cli_baseline() {
+    let bin = get_bin_path();
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let notes = temp_dir.path().join("notes.md");
+    std::fs::write(&notes, "An old occurance.\nAnother wrod.\n").unwrap();
+    let run = |args: &[&str]| {
+        Command::new(&bin)
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("failed to execute process")
+    };
+
+    let output = run(&["baseline", "create"]);
+    assert!(output.status.success(), "Create failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Recorded 2 errors"));
+
+    let output = run(&["check", "--baseline", "spellcheck-baseline.json", "."]);
+    assert!(output.status.success(), "Known errors should not fail: {}", String::from_utf8_lossy(&output.stdout));
+
+    // Shift the known error down, fix the other one and add a new one.
+    std::fs::write(&notes, "A new recieve.\nAn old occurance.\nAnother word.\n").unwrap();
+    let output = run(&["check", "--no-cache", "--baseline", "spellcheck-baseline.json", "."]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("recieve"));
+    assert!(!stdout.contains("occurance"), "Shifted known error should stay suppressed: {}", stdout);
+    assert!(stdout.contains("1 baseline entries no longer occur"));
+    assert!(stdout.contains("notes.md: wrod"));
+}
+
+#[test]
+fn test_cli_baseline_skips_itself() {
+    let bin = get_bin_path();
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    std::fs::write(temp_dir.path().join("notes.md"), "An old occurance.\nAnother wrod.\n").unwrap();
+    // A custom exclude list replaces the defaults.
+    std::fs::write(temp_dir.path().join("spellcheck.toml"), "[files]\nexclude = [\"target/**\"]\n").unwrap();
+    let run = |args: &[&str]| {
+        Command::new(&bin)
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("failed to execute process")
+    };
+
+    for _ in 0..2 {
+        let output = run(&["baseline", "create", "-o", "my-baseline.json", "."]);
+        assert!(output.status.success(), "Create failed: {}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Recorded 2 errors"));
+    }
+
+    let output = run(&["check", "--baseline", "my-baseline.json", "."]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "The baseline should not be checked: {}", stdout);
+    assert!(!stdout.contains("my-baseline.json:"), "{}", stdout);
+}